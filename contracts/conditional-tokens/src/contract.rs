@@ -0,0 +1,381 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    coins, from_json, to_json_binary, Addr, BankMsg, Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response,
+    StdError, StdResult, SubMsg, Uint128, WasmMsg,
+};
+use cw2::set_contract_version;
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, MinterResponse};
+use cw_utils::{must_pay, nonpayable, parse_reply_instantiate_data};
+use lavs_apis::id::TaskId;
+use lavs_task_queue::msg::{MarketPhase, QueryMsg as TaskQueueQueryMsg, TaskInfoResponse};
+
+use crate::error::ContractError;
+use crate::msg::{ConditionResponse, ExecuteMsg, InstantiateMsg, OutcomeToken, QueryMsg, ReceiveMsg};
+use crate::state::{Condition, Config, Outcome, CONDITIONS, CONFIG, DEPOSITS, PREPARING, TOKENS};
+
+// version info for migration info
+const CONTRACT_NAME: &str = "crates.io:conditional-tokens";
+const CONTRACT_VERSION: &str = "1.0.0";
+
+/// cw20-base only accepts letters and dashes, so the details go into the name
+const TOKEN_SYMBOL: &str = "OUTCOME";
+const TOKEN_DECIMALS: u8 = 6;
+/// Longest name cw20-base accepts
+const MAX_NAME_LENGTH: usize = 50;
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    let config = Config {
+        task_queue: deps.api.addr_validate(&msg.task_queue)?,
+        collateral_denom: msg.collateral_denom,
+        cw20_code_id: msg.cw20_code_id,
+    };
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "instantiate")
+        .add_attribute("task_queue", config.task_queue))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::Prepare { task_id } => execute::prepare(deps, env, info, task_id),
+        ExecuteMsg::Split { task_id } => execute::split(deps, env, info, task_id),
+        ExecuteMsg::Reclaim { task_id } => execute::reclaim(deps, info, task_id),
+        ExecuteMsg::Receive(msg) => execute::receive(deps, env, info, msg),
+    }
+}
+
+/// Stores the address of each outcome token as it gets instantiated.
+/// The reply id is the option's index
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    let index = msg.id as usize;
+    let task_id = PREPARING.load(deps.storage)?;
+    let res = parse_reply_instantiate_data(msg)?;
+    let token = deps.api.addr_validate(&res.contract_address)?;
+
+    let mut condition = CONDITIONS.load(deps.storage, task_id)?;
+    let option = condition.options[index].clone();
+    condition.tokens.push(token.clone());
+    if condition.tokens.len() == condition.options.len() {
+        PREPARING.remove(deps.storage);
+    }
+    CONDITIONS.save(deps.storage, task_id, &condition)?;
+    TOKENS.save(deps.storage, &token, &Outcome { task_id, option: option.clone() })?;
+
+    Ok(Response::new()
+        .add_attribute("action", "outcome_token")
+        .add_attribute("task_id", task_id.to_string())
+        .add_attribute("option", option)
+        .add_attribute("token", token))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => to_json_binary(&CONFIG.load(deps.storage)?),
+        QueryMsg::Condition { task_id } => to_json_binary(&query::condition(deps, task_id)?),
+        QueryMsg::Token { address } => to_json_binary(&query::token(deps, address)?),
+    }
+}
+
+mod execute {
+    use super::*;
+
+    pub fn prepare(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        task_id: TaskId,
+    ) -> Result<Response, ContractError> {
+        nonpayable(&info)?;
+        let config = CONFIG.load(deps.storage)?;
+        if CONDITIONS.has(deps.storage, task_id) {
+            return Err(ContractError::ConditionExists(task_id.u64()));
+        }
+        let task = load_task(deps.as_ref(), &config, task_id)?;
+        if task.options.is_empty() {
+            return Err(ContractError::NotCategorical);
+        }
+        if task.market_phase(&env) != MarketPhase::Open {
+            return Err(ContractError::TaskClosed);
+        }
+
+        let instantiate_tokens = task
+            .options
+            .iter()
+            .enumerate()
+            .map(|(index, option)| -> StdResult<_> {
+                let name: String = format!("Task {} {}", task_id, option)
+                    .chars()
+                    .take(MAX_NAME_LENGTH)
+                    .collect();
+                let msg = cw20_base::msg::InstantiateMsg {
+                    name: name.clone(),
+                    symbol: TOKEN_SYMBOL.to_string(),
+                    decimals: TOKEN_DECIMALS,
+                    initial_balances: vec![],
+                    mint: Some(MinterResponse {
+                        minter: env.contract.address.to_string(),
+                        cap: None,
+                    }),
+                    marketing: None,
+                };
+                let instantiate = WasmMsg::Instantiate {
+                    admin: None,
+                    code_id: config.cw20_code_id,
+                    msg: to_json_binary(&msg)?,
+                    funds: vec![],
+                    label: name,
+                };
+                Ok(SubMsg::reply_on_success(instantiate, index as u64))
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+
+        let condition = Condition {
+            options: task.options,
+            tokens: vec![],
+            collateral: Uint128::zero(),
+        };
+        CONDITIONS.save(deps.storage, task_id, &condition)?;
+        PREPARING.save(deps.storage, &task_id)?;
+
+        Ok(Response::new()
+            .add_submessages(instantiate_tokens)
+            .add_attribute("action", "prepare")
+            .add_attribute("task_id", task_id.to_string()))
+    }
+
+    pub fn split(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        task_id: TaskId,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+        let amount = must_pay(&info, &config.collateral_denom)?;
+        let mut condition = CONDITIONS
+            .may_load(deps.storage, task_id)?
+            .ok_or(ContractError::ConditionNotFound(task_id.u64()))?;
+        let task = load_task(deps.as_ref(), &config, task_id)?;
+        if task.market_phase(&env) != MarketPhase::Open {
+            return Err(ContractError::TaskClosed);
+        }
+
+        condition.collateral += amount;
+        CONDITIONS.save(deps.storage, task_id, &condition)?;
+
+        let mint = condition
+            .tokens
+            .iter()
+            .map(|token| {
+                cw20_execute(
+                    token,
+                    &Cw20ExecuteMsg::Mint {
+                        recipient: info.sender.to_string(),
+                        amount,
+                    },
+                )
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+
+        Ok(Response::new()
+            .add_messages(mint)
+            .add_attribute("action", "split")
+            .add_attribute("task_id", task_id.to_string())
+            .add_attribute("owner", info.sender)
+            .add_attribute("amount", amount.to_string()))
+    }
+
+    pub fn reclaim(deps: DepsMut, info: MessageInfo, task_id: TaskId) -> Result<Response, ContractError> {
+        nonpayable(&info)?;
+        let condition = CONDITIONS
+            .may_load(deps.storage, task_id)?
+            .ok_or(ContractError::ConditionNotFound(task_id.u64()))?;
+        let deposits = DEPOSITS
+            .may_load(deps.storage, (task_id, &info.sender))?
+            .ok_or(ContractError::NothingToReclaim)?;
+        DEPOSITS.remove(deps.storage, (task_id, &info.sender));
+
+        let transfers = condition
+            .tokens
+            .iter()
+            .zip(deposits)
+            .filter(|(_, amount)| !amount.is_zero())
+            .map(|(token, amount)| {
+                cw20_execute(
+                    token,
+                    &Cw20ExecuteMsg::Transfer {
+                        recipient: info.sender.to_string(),
+                        amount,
+                    },
+                )
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+
+        Ok(Response::new()
+            .add_messages(transfers)
+            .add_attribute("action", "reclaim")
+            .add_attribute("task_id", task_id.to_string())
+            .add_attribute("owner", info.sender))
+    }
+
+    pub fn receive(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        wrapper: Cw20ReceiveMsg,
+    ) -> Result<Response, ContractError> {
+        let outcome = TOKENS
+            .may_load(deps.storage, &info.sender)?
+            .ok_or_else(|| ContractError::UnknownToken(info.sender.to_string()))?;
+        let owner = deps.api.addr_validate(&wrapper.sender)?;
+        match from_json(&wrapper.msg)? {
+            ReceiveMsg::Merge {} => merge(deps, outcome, owner, wrapper.amount),
+            ReceiveMsg::Redeem {} => redeem(deps, env, info.sender, outcome, owner, wrapper.amount),
+        }
+    }
+
+    /// Tokens are held until the owner sent some of every option, so sending each in one
+    /// transaction merges them right away. Full sets are burned from our own balance
+    fn merge(deps: DepsMut, outcome: Outcome, owner: Addr, amount: Uint128) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+        let mut condition = CONDITIONS.load(deps.storage, outcome.task_id)?;
+        let index = condition
+            .options
+            .iter()
+            .position(|option| *option == outcome.option)
+            .ok_or_else(|| ContractError::UnknownToken(outcome.option.clone()))?;
+        let mut deposits = DEPOSITS
+            .may_load(deps.storage, (outcome.task_id, &owner))?
+            .unwrap_or_else(|| vec![Uint128::zero(); condition.options.len()]);
+        deposits[index] += amount;
+
+        let merged = deposits.iter().min().copied().unwrap_or_default();
+        deposits.iter_mut().for_each(|deposit| *deposit -= merged);
+        if deposits.iter().all(Uint128::is_zero) {
+            DEPOSITS.remove(deps.storage, (outcome.task_id, &owner));
+        } else {
+            DEPOSITS.save(deps.storage, (outcome.task_id, &owner), &deposits)?;
+        }
+
+        let mut res = Response::new()
+            .add_attribute("action", "merge")
+            .add_attribute("task_id", outcome.task_id.to_string())
+            .add_attribute("option", outcome.option)
+            .add_attribute("owner", owner.to_string())
+            .add_attribute("amount", amount.to_string())
+            .add_attribute("merged", merged.to_string());
+        if merged.is_zero() {
+            return Ok(res);
+        }
+
+        // A full set of tokens is always backed by its collateral
+        condition.collateral = condition.collateral.checked_sub(merged).map_err(StdError::from)?;
+        CONDITIONS.save(deps.storage, outcome.task_id, &condition)?;
+        for token in &condition.tokens {
+            res = res.add_message(cw20_execute(token, &Cw20ExecuteMsg::Burn { amount: merged })?);
+        }
+        Ok(res.add_message(BankMsg::Send {
+            to_address: owner.to_string(),
+            amount: coins(merged.u128(), config.collateral_denom),
+        }))
+    }
+
+    fn redeem(
+        deps: DepsMut,
+        env: Env,
+        token: Addr,
+        outcome: Outcome,
+        owner: Addr,
+        amount: Uint128,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+        let mut condition = CONDITIONS.load(deps.storage, outcome.task_id)?;
+        let task = load_task(deps.as_ref(), &config, outcome.task_id)?;
+        let payout = match task.market_phase(&env) {
+            MarketPhase::Open | MarketPhase::Pending => return Err(ContractError::NotResolved),
+            MarketPhase::Resolved(winner) if winner == outcome.option => amount,
+            MarketPhase::Resolved(_) => return Err(ContractError::LosingOutcome(outcome.option)),
+            // every full set is worth one unit of collateral, so each token gets an equal share.
+            // Only whole shares are paid out, as the tokens sent are burned
+            MarketPhase::Expired => {
+                let options = condition.options.len() as u128;
+                if amount.u128() % options != 0 {
+                    return Err(ContractError::UnevenRedemption(options));
+                }
+                amount.multiply_ratio(1u128, options)
+            }
+        };
+        condition.collateral -= payout;
+        CONDITIONS.save(deps.storage, outcome.task_id, &condition)?;
+
+        let mut res = Response::new()
+            .add_message(cw20_execute(&token, &Cw20ExecuteMsg::Burn { amount })?)
+            .add_attribute("action", "redeem")
+            .add_attribute("task_id", outcome.task_id.to_string())
+            .add_attribute("option", outcome.option)
+            .add_attribute("owner", owner.to_string())
+            .add_attribute("amount", amount.to_string())
+            .add_attribute("payout", payout.to_string());
+        if !payout.is_zero() {
+            res = res.add_message(BankMsg::Send {
+                to_address: owner.to_string(),
+                amount: coins(payout.u128(), config.collateral_denom),
+            });
+        }
+        Ok(res)
+    }
+
+    fn cw20_execute(token: &Addr, msg: &Cw20ExecuteMsg) -> StdResult<WasmMsg> {
+        Ok(WasmMsg::Execute {
+            contract_addr: token.to_string(),
+            msg: to_json_binary(msg)?,
+            funds: vec![],
+        })
+    }
+
+    fn load_task(deps: Deps, config: &Config, task_id: TaskId) -> StdResult<TaskInfoResponse> {
+        deps.querier
+            .query_wasm_smart(&config.task_queue, &TaskQueueQueryMsg::TaskInfo { task_id })
+    }
+}
+
+mod query {
+    use super::*;
+
+    pub fn condition(deps: Deps, task_id: TaskId) -> StdResult<ConditionResponse> {
+        let condition = CONDITIONS.load(deps.storage, task_id)?;
+        let outcomes = condition
+            .options
+            .into_iter()
+            .zip(condition.tokens)
+            .map(|(option, token)| OutcomeToken { option, token })
+            .collect();
+        Ok(ConditionResponse {
+            task_id,
+            outcomes,
+            collateral: condition.collateral,
+        })
+    }
+
+    pub fn token(deps: Deps, address: String) -> StdResult<Outcome> {
+        let address = deps.api.addr_validate(&address)?;
+        TOKENS.load(deps.storage, &address)
+    }
+}
@@ -0,0 +1,42 @@
+use cosmwasm_std::StdError;
+use cw_utils::{ParseReplyError, PaymentError};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("{0}")]
+    Payment(#[from] PaymentError),
+
+    #[error("{0}")]
+    ParseReply(#[from] ParseReplyError),
+
+    #[error("Conditions need a task with options")]
+    NotCategorical,
+
+    #[error("Task {0} already has outcome tokens")]
+    ConditionExists(u64),
+
+    #[error("Task {0} has no outcome tokens")]
+    ConditionNotFound(u64),
+
+    #[error("The task is no longer open")]
+    TaskClosed,
+
+    #[error("No tokens of this task are waiting to be merged")]
+    NothingToReclaim,
+
+    #[error("Not an outcome token: {0}")]
+    UnknownToken(String),
+
+    #[error("The task has not completed yet")]
+    NotResolved,
+
+    #[error("Option {0} did not win")]
+    LosingOutcome(String),
+
+    #[error("Tokens of an expired task are redeemed in multiples of {0}")]
+    UnevenRedemption(u128),
+}
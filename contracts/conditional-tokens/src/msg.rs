@@ -0,0 +1,81 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{Addr, Uint128};
+use cw20::Cw20ReceiveMsg;
+use lavs_apis::id::TaskId;
+
+pub use crate::state::{Config, Outcome};
+
+#[cw_serde]
+pub struct InstantiateMsg {
+    /// Address of the task queue whose tasks the conditions are about
+    pub task_queue: String,
+    /// Native token backing the outcome tokens
+    pub collateral_denom: String,
+    /// Code id of cw20-base, instantiated for every outcome
+    pub cw20_code_id: u64,
+}
+
+#[cw_serde]
+#[derive(cw_orch::ExecuteFns)]
+#[cw_orch(disable_fields_sorting)]
+pub enum ExecuteMsg {
+    /// Instantiates a cw20 token for each option of an open task, minted by this contract
+    Prepare {
+        task_id: TaskId,
+    },
+    /// Locks the sent collateral and mints the same amount of every outcome token to the sender.
+    /// Only while the task is open
+    #[cw_orch(payable)]
+    Split {
+        task_id: TaskId,
+    },
+    /// Returns the outcome tokens the sender sent to merge that did not form full sets yet
+    Reclaim {
+        task_id: TaskId,
+    },
+    /// Outcome tokens sent with a [`ReceiveMsg`]
+    Receive(Cw20ReceiveMsg),
+}
+
+#[cw_serde]
+pub enum ReceiveMsg {
+    /// Holds the sent tokens until the sender sent the same amount of every option.
+    /// Full sets are then burned and their collateral returned
+    Merge {},
+    /// Burns the sent tokens for collateral once the task is over.
+    /// Tokens of the winning option pay out 1:1. If the task expired, every token pays `1 / n`,
+    /// so the amount must be a multiple of `n`
+    Redeem {},
+}
+
+#[cw_serde]
+#[derive(QueryResponses, cw_orch::QueryFns)]
+#[cw_orch(disable_fields_sorting)]
+pub enum QueryMsg {
+    #[returns(Config)]
+    Config {},
+    /// The outcome tokens of a task
+    #[returns(ConditionResponse)]
+    Condition {
+        task_id: TaskId,
+    },
+    /// The outcome a token stands for
+    #[returns(Outcome)]
+    Token {
+        address: String,
+    },
+}
+
+#[cw_serde]
+pub struct ConditionResponse {
+    pub task_id: TaskId,
+    pub outcomes: Vec<OutcomeToken>,
+    /// Collateral backing the tokens still outstanding
+    pub collateral: Uint128,
+}
+
+#[cw_serde]
+pub struct OutcomeToken {
+    pub option: String,
+    pub token: Addr,
+}
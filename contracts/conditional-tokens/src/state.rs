@@ -0,0 +1,40 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Uint128};
+use cw_storage_plus::{Item, Map};
+use lavs_apis::id::TaskId;
+
+pub const CONFIG: Item<Config> = Item::new("config");
+pub const CONDITIONS: Map<TaskId, Condition> = Map::new("conditions");
+/// The outcome each token stands for, to recognize tokens sent to us
+pub const TOKENS: Map<&Addr, Outcome> = Map::new("tokens");
+/// Outcome tokens sent to merge, per task and owner in the order of the options, until they form full sets
+pub const DEPOSITS: Map<(TaskId, &Addr), Vec<Uint128>> = Map::new("deposits");
+/// The condition whose tokens are being instantiated, read when the replies come in
+pub const PREPARING: Item<TaskId> = Item::new("preparing");
+
+#[cw_serde]
+pub struct Config {
+    /// The task queue whose tasks the conditions are about
+    pub task_queue: Addr,
+    /// Native token backing the outcome tokens
+    pub collateral_denom: String,
+    /// cw20-base code instantiated for every outcome
+    pub cw20_code_id: u64,
+}
+
+/// A task whose outcomes have tokens
+#[cw_serde]
+pub struct Condition {
+    /// The task's options, copied when the condition was prepared
+    pub options: Vec<String>,
+    /// The token of each option, in the same order
+    pub tokens: Vec<Addr>,
+    /// Collateral backing the tokens still outstanding
+    pub collateral: Uint128,
+}
+
+#[cw_serde]
+pub struct Outcome {
+    pub task_id: TaskId,
+    pub option: String,
+}
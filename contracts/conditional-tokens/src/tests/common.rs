@@ -0,0 +1,288 @@
+use cosmwasm_std::{coins, to_json_binary, Addr, Empty, Uint128};
+use cw20::BalanceResponse;
+use cw20_base::msg::{ExecuteMsg as Cw20ExecuteMsg, InstantiateMsg as Cw20InstantiateMsg, QueryMsg as Cw20QueryMsg};
+use cw_orch::environment::{ChainState, CwEnv};
+use cw_orch::{interface, prelude::*};
+
+use lavs_apis::id::TaskId;
+use lavs_apis::tasks::{Requestor, TimeoutInfo};
+use lavs_orch::{Addressable, AltSigner};
+use serde_json::json;
+
+use lavs_task_queue::{
+    interface::Contract as TasksContract,
+    msg::{ExecuteMsgFns as TasksExecuteMsgFns, InstantiateMsg as TasksInstantiateMsg},
+};
+
+use crate::interface::Contract;
+use crate::msg::{ExecuteMsgFns, InstantiateMsg, QueryMsgFns, ReceiveMsg};
+use crate::ContractError;
+
+pub const BECH_PREFIX: &str = "slay3r";
+pub const DENOM: &str = "uslay";
+/// Signer that resolves tasks in place of a real verifier
+const VERIFIER_INDEX: u32 = 9;
+const TIMEOUT: u64 = 600;
+
+/// cw20-base, to upload it and interact with the outcome tokens
+#[interface(Cw20InstantiateMsg, Cw20ExecuteMsg, Cw20QueryMsg, Empty)]
+pub struct Cw20;
+
+impl<Chain> Uploadable for Cw20<Chain> {
+    fn wrapper() -> Box<dyn MockContract<Empty>> {
+        Box::new(ContractWrapper::new_with_empty(
+            cw20_base::contract::execute,
+            cw20_base::contract::instantiate,
+            cw20_base::contract::query,
+        ))
+    }
+}
+
+pub fn setup<Chain: CwEnv>(chain: Chain, tasker: &TasksContract<Chain>) -> Contract<Chain> {
+    let cw20 = Cw20::new("cw20_base", chain.clone());
+    cw20.upload().unwrap();
+    let msg = InstantiateMsg {
+        task_queue: tasker.addr_str().unwrap(),
+        collateral_denom: DENOM.to_string(),
+        cw20_code_id: cw20.code_id().unwrap(),
+    };
+    let contract = Contract::new(chain);
+    contract.upload().unwrap();
+    contract.instantiate(&msg, None, &[]).unwrap();
+    contract
+}
+
+pub fn split_merge_and_redeem<C>(chain: C)
+where
+    C: CwEnv + AltSigner,
+    C::Sender: Addressable,
+{
+    let trader1 = chain.alt_signer(1);
+    let trader2 = chain.alt_signer(2);
+    let verifier = chain.alt_signer(VERIFIER_INDEX);
+
+    let tasker = setup_task_queue(chain.clone());
+    let conditional = setup(chain.clone(), &tasker);
+
+    let numeric = make_task(&tasker, vec![]);
+    let err = conditional.prepare(numeric).unwrap_err();
+    assert!(
+        err.root().to_string().contains(&ContractError::NotCategorical.to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+
+    let task_id = make_task(&tasker, vec!["yes".to_string(), "no".to_string()]);
+    conditional.prepare(task_id).unwrap();
+    let err = conditional.prepare(task_id).unwrap_err();
+    assert!(
+        err.root()
+            .to_string()
+            .contains(&ContractError::ConditionExists(task_id.u64()).to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+
+    let outcomes = conditional.condition(task_id).unwrap().outcomes;
+    assert_eq!(outcomes.len(), 2);
+    assert_eq!(outcomes[0].option, "yes");
+    let yes = token_at(&chain, &outcomes[0].token);
+    let no = token_at(&chain, &outcomes[1].token);
+    let outcome = conditional.token(outcomes[1].token.to_string()).unwrap();
+    assert_eq!(outcome.option, "no");
+
+    // 100 collateral becomes 100 of each token
+    conditional.call_as(&trader1).split(task_id, &coins(100, DENOM)).unwrap();
+    assert_eq!(balance(&yes, &trader1.addr()), 100);
+    assert_eq!(balance(&no, &trader1.addr()), 100);
+
+    // the tokens trade like any cw20
+    yes.call_as(&trader1)
+        .execute(
+            &Cw20ExecuteMsg::Transfer {
+                recipient: trader2.addr().to_string(),
+                amount: Uint128::new(40),
+            },
+            &[],
+        )
+        .unwrap();
+
+    // tokens are held until every option was sent, then full sets are merged
+    let res = send(&conditional, &yes, &trader1, 50, &ReceiveMsg::Merge {}).unwrap();
+    assert_eq!(res.event_attr_value("wasm", "merged").unwrap(), "0");
+    assert_eq!(conditional.condition(task_id).unwrap().collateral, Uint128::new(100));
+    let res = send(&conditional, &no, &trader1, 70, &ReceiveMsg::Merge {}).unwrap();
+    assert_eq!(res.event_attr_value("wasm", "merged").unwrap(), "50");
+    assert_eq!(balance(&no, &trader1.addr()), 30);
+
+    // and the rest can be taken back
+    conditional.call_as(&trader1).reclaim(task_id).unwrap();
+    let err = conditional.call_as(&trader1).reclaim(task_id).unwrap_err();
+    assert!(
+        err.root().to_string().contains(&ContractError::NothingToReclaim.to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+    assert_eq!(balance(&yes, &trader1.addr()), 10);
+    assert_eq!(balance(&no, &trader1.addr()), 50);
+    assert_eq!(conditional.condition(task_id).unwrap().collateral, Uint128::new(50));
+
+    let err = redeem(&conditional, &yes, &trader2, 40).unwrap_err();
+    assert!(
+        err.root().to_string().contains(&ContractError::NotResolved.to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+
+    tasker
+        .call_as(&verifier)
+        .resolve_outcome(task_id, "yes".to_string())
+        .unwrap();
+    let err = conditional
+        .call_as(&trader1)
+        .split(task_id, &coins(100, DENOM))
+        .unwrap_err();
+    assert!(
+        err.root().to_string().contains(&ContractError::TaskClosed.to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+
+    let res = redeem(&conditional, &yes, &trader2, 40).unwrap();
+    assert_eq!(res.event_attr_value("wasm", "payout").unwrap(), "40");
+    assert_eq!(balance(&yes, &trader2.addr()), 0);
+    let err = redeem(&conditional, &no, &trader1, 50).unwrap_err();
+    assert!(
+        err.root()
+            .to_string()
+            .contains(&ContractError::LosingOutcome("no".to_string()).to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+    redeem(&conditional, &yes, &trader1, 10).unwrap();
+    assert_eq!(conditional.condition(task_id).unwrap().collateral, Uint128::zero());
+}
+
+pub fn expired_conditions_pay_equal_shares<C>(chain: C)
+where
+    C: CwEnv + AltSigner,
+    C::Sender: Addressable,
+{
+    let trader = chain.alt_signer(1);
+
+    let tasker = setup_task_queue(chain.clone());
+    let conditional = setup(chain.clone(), &tasker);
+    let task_id = make_task(&tasker, vec!["yes".to_string(), "no".to_string()]);
+    conditional.prepare(task_id).unwrap();
+    let outcomes = conditional.condition(task_id).unwrap().outcomes;
+    let yes = token_at(&chain, &outcomes[0].token);
+    let no = token_at(&chain, &outcomes[1].token);
+    conditional.call_as(&trader).split(task_id, &coins(100, DENOM)).unwrap();
+
+    // the task never resolves, so each of the two tokens is worth half
+    chain.wait_seconds(TIMEOUT + 1).unwrap();
+    let err = redeem(&conditional, &yes, &trader, 1).unwrap_err();
+    assert!(
+        err.root()
+            .to_string()
+            .contains(&ContractError::UnevenRedemption(2).to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+    assert_eq!(balance(&yes, &trader.addr()), 100);
+    let res = redeem(&conditional, &yes, &trader, 100).unwrap();
+    assert_eq!(res.event_attr_value("wasm", "payout").unwrap(), "50");
+    let res = redeem(&conditional, &no, &trader, 100).unwrap();
+    assert_eq!(res.event_attr_value("wasm", "payout").unwrap(), "50");
+    assert_eq!(conditional.condition(task_id).unwrap().collateral, Uint128::zero());
+}
+
+/// Sends outcome tokens to the conditional tokens contract to redeem them
+pub fn redeem<C>(
+    conditional: &Contract<C>,
+    token: &Cw20<C>,
+    owner: &C::Sender,
+    amount: u128,
+) -> Result<C::Response, CwOrchError>
+where
+    C: CwEnv,
+{
+    send(conditional, token, owner, amount, &ReceiveMsg::Redeem {})
+}
+
+/// Sends outcome tokens to the conditional tokens contract with the given message
+pub fn send<C>(
+    conditional: &Contract<C>,
+    token: &Cw20<C>,
+    owner: &C::Sender,
+    amount: u128,
+    msg: &ReceiveMsg,
+) -> Result<C::Response, CwOrchError>
+where
+    C: CwEnv,
+{
+    token.call_as(owner).execute(
+        &Cw20ExecuteMsg::Send {
+            contract: conditional.addr_str().unwrap(),
+            amount: Uint128::new(amount),
+            msg: to_json_binary(msg).unwrap(),
+        },
+        &[],
+    )
+}
+
+pub fn token_at<C: CwEnv>(chain: &C, address: &Addr) -> Cw20<C> {
+    let token = Cw20::new(address.to_string(), chain.clone());
+    token.set_address(address);
+    token
+}
+
+pub fn balance<C: CwEnv>(token: &Cw20<C>, owner: &Addr) -> u128 {
+    let res: BalanceResponse = token
+        .query(&Cw20QueryMsg::Balance {
+            address: owner.to_string(),
+        })
+        .unwrap();
+    res.balance.u128()
+}
+
+#[track_caller]
+pub fn make_task<C: ChainState + TxHandler>(tasker: &TasksContract<C>, options: Vec<String>) -> TaskId {
+    let res = tasker
+        .create(
+            "Will it rain?".to_string(),
+            None,
+            json!({"question": "Will it rain?"}),
+            options,
+            String::new(),
+            None,
+            &[],
+        )
+        .unwrap();
+    let id = res
+        .event_attr_value("wasm", "task_id")
+        .unwrap()
+        .parse()
+        .unwrap();
+    TaskId::new(id)
+}
+
+pub fn setup_task_queue<C>(chain: C) -> TasksContract<C>
+where
+    C: CwEnv + AltSigner,
+    C::Sender: Addressable,
+{
+    let msg = TasksInstantiateMsg {
+        requestor: Requestor::Fixed(chain.sender_addr().into()),
+        timeout: TimeoutInfo::new(TIMEOUT),
+        verifier: chain.alt_signer(VERIFIER_INDEX).addr().to_string(),
+        dispute: None,
+        arbitrator: None,
+        cancel_fee: None,
+        keeper_reward: None,
+    };
+    let tasker = TasksContract::new(chain);
+    tasker.upload().unwrap();
+    tasker.instantiate(&msg, None, &[]).unwrap();
+    tasker
+}
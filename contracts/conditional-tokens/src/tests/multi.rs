@@ -0,0 +1,25 @@
+use cosmwasm_std::coins;
+use cw_orch::prelude::MockBech32;
+use lavs_orch::{Addressable, AltSigner};
+
+use super::common::{BECH_PREFIX, DENOM};
+
+fn funded_chain() -> MockBech32 {
+    let chain = MockBech32::new(BECH_PREFIX);
+    for i in [1, 2] {
+        chain
+            .add_balance(&chain.alt_signer(i).addr(), coins(1000, DENOM))
+            .unwrap();
+    }
+    chain
+}
+
+#[test]
+fn split_merge_and_redeem() {
+    super::common::split_merge_and_redeem(funded_chain());
+}
+
+#[test]
+fn expired_conditions_pay_equal_shares() {
+    super::common::expired_conditions_pay_equal_shares(funded_chain());
+}
@@ -0,0 +1,327 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    coins, to_json_binary, BankMsg, Binary, Deps, DepsMut, Env, MessageInfo, Order, Response, StdError,
+    StdResult, Uint128,
+};
+use cw2::set_contract_version;
+use cw_utils::{must_pay, nonpayable};
+use lavs_task_queue::msg::{MarketPhase, QueryMsg as TaskQueueQueryMsg, TaskInfoResponse};
+
+use crate::error::ContractError;
+use crate::math;
+use crate::msg::{
+    BalanceResponse, ExecuteMsg, InstantiateMsg, OptionPrice, OptionShares, PricesResponse, QueryMsg,
+    QuoteResponse,
+};
+use crate::state::{Config, BALANCES, CONFIG, SHARES};
+
+// version info for migration info
+const CONTRACT_NAME: &str = "crates.io:lmsr-amm";
+const CONTRACT_VERSION: &str = "1.0.0";
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    if msg.liquidity.is_zero() {
+        return Err(ContractError::ZeroLiquidity);
+    }
+    let task_queue = deps.api.addr_validate(&msg.task_queue)?;
+    let task: TaskInfoResponse = deps
+        .querier
+        .query_wasm_smart(&task_queue, &TaskQueueQueryMsg::TaskInfo { task_id: msg.task_id })?;
+    if task.options.is_empty() {
+        return Err(ContractError::NotCategorical);
+    }
+    if task.market_phase(&env) != MarketPhase::Open {
+        return Err(ContractError::TradingClosed);
+    }
+
+    let subsidy = must_pay(&info, &msg.denom)?;
+    let needed = math::subsidy(msg.liquidity, task.options.len())?;
+    if subsidy < needed {
+        return Err(ContractError::InsufficientSubsidy(needed));
+    }
+
+    let config = Config {
+        creator: info.sender,
+        task_queue,
+        task_id: msg.task_id,
+        options: task.options,
+        denom: msg.denom,
+        liquidity: msg.liquidity,
+        subsidy,
+    };
+    CONFIG.save(deps.storage, &config)?;
+    SHARES.save(deps.storage, &vec![Uint128::zero(); config.options.len()])?;
+
+    Ok(Response::new()
+        .add_attribute("action", "instantiate")
+        .add_attribute("task_id", config.task_id.to_string())
+        .add_attribute("liquidity", config.liquidity.to_string())
+        .add_attribute("subsidy", subsidy.to_string()))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::Buy { option, shares } => execute::buy(deps, env, info, option, shares),
+        ExecuteMsg::Sell { option, shares, min_return } => {
+            execute::sell(deps, env, info, option, shares, min_return)
+        }
+        ExecuteMsg::Redeem {} => execute::redeem(deps, env, info),
+        ExecuteMsg::Withdraw {} => execute::withdraw(deps, env, info),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => to_json_binary(&CONFIG.load(deps.storage)?),
+        QueryMsg::QuoteBuy { option, shares } => to_json_binary(&query::quote_buy(deps, option, shares)?),
+        QueryMsg::QuoteSell { option, shares } => to_json_binary(&query::quote_sell(deps, option, shares)?),
+        QueryMsg::Prices {} => to_json_binary(&query::prices(deps)?),
+        QueryMsg::Balance { address } => to_json_binary(&query::balance(deps, address)?),
+    }
+}
+
+mod execute {
+    use super::*;
+
+    pub fn buy(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        option: String,
+        shares: Uint128,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+        let sent = must_pay(&info, &config.denom)?;
+        let index = config
+            .option_index(&option)
+            .ok_or_else(|| ContractError::UnknownOption(option.clone()))?;
+        if shares.is_zero() {
+            return Err(ContractError::ZeroShares);
+        }
+        if load_phase(deps.as_ref(), &env, &config)? != MarketPhase::Open {
+            return Err(ContractError::TradingClosed);
+        }
+
+        let mut outstanding = SHARES.load(deps.storage)?;
+        let cost = math::buy_cost(config.liquidity, &outstanding, index, shares)?;
+        if cost > sent {
+            return Err(ContractError::InsufficientFunds(cost));
+        }
+        outstanding[index] += shares;
+        SHARES.save(deps.storage, &outstanding)?;
+        BALANCES.update(deps.storage, (&info.sender, &option), |held| -> StdResult<_> {
+            Ok(held.unwrap_or_default() + shares)
+        })?;
+
+        let refund = sent - cost;
+        let mut res = Response::new()
+            .add_attribute("action", "buy")
+            .add_attribute("trader", info.sender.to_string())
+            .add_attribute("option", option)
+            .add_attribute("shares", shares.to_string())
+            .add_attribute("cost", cost.to_string());
+        if !refund.is_zero() {
+            res = res.add_message(BankMsg::Send {
+                to_address: info.sender.to_string(),
+                amount: coins(refund.u128(), config.denom),
+            });
+        }
+        Ok(res)
+    }
+
+    pub fn sell(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        option: String,
+        shares: Uint128,
+        min_return: Uint128,
+    ) -> Result<Response, ContractError> {
+        nonpayable(&info)?;
+        let config = CONFIG.load(deps.storage)?;
+        let index = config
+            .option_index(&option)
+            .ok_or_else(|| ContractError::UnknownOption(option.clone()))?;
+        if shares.is_zero() {
+            return Err(ContractError::ZeroShares);
+        }
+        // selling at the old prices after a result is known would drain the market maker
+        if matches!(load_phase(deps.as_ref(), &env, &config)?, MarketPhase::Pending | MarketPhase::Resolved(_)) {
+            return Err(ContractError::TradingClosed);
+        }
+
+        let held = BALANCES.may_load(deps.storage, (&info.sender, &option))?.unwrap_or_default();
+        if shares > held {
+            return Err(ContractError::InsufficientShares(held));
+        }
+        let mut outstanding = SHARES.load(deps.storage)?;
+        let returned = math::sell_return(config.liquidity, &outstanding, index, shares)?;
+        if returned < min_return {
+            return Err(ContractError::Slippage { returned, min_return });
+        }
+        outstanding[index] -= shares;
+        SHARES.save(deps.storage, &outstanding)?;
+        if shares == held {
+            BALANCES.remove(deps.storage, (&info.sender, &option));
+        } else {
+            BALANCES.save(deps.storage, (&info.sender, &option), &(held - shares))?;
+        }
+
+        let mut res = Response::new()
+            .add_attribute("action", "sell")
+            .add_attribute("trader", info.sender.to_string())
+            .add_attribute("option", option)
+            .add_attribute("shares", shares.to_string())
+            .add_attribute("returned", returned.to_string());
+        if !returned.is_zero() {
+            res = res.add_message(BankMsg::Send {
+                to_address: info.sender.to_string(),
+                amount: coins(returned.u128(), config.denom),
+            });
+        }
+        Ok(res)
+    }
+
+    pub fn redeem(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+        nonpayable(&info)?;
+        let config = CONFIG.load(deps.storage)?;
+        let winner = match load_phase(deps.as_ref(), &env, &config)? {
+            MarketPhase::Resolved(winner) => winner,
+            MarketPhase::Open | MarketPhase::Pending | MarketPhase::Expired => return Err(ContractError::NotResolved),
+        };
+        let index = config
+            .option_index(&winner)
+            .ok_or_else(|| ContractError::UnknownOption(winner.clone()))?;
+
+        let held = BALANCES.may_load(deps.storage, (&info.sender, &winner))?.unwrap_or_default();
+        if held.is_zero() {
+            return Err(ContractError::NothingToRedeem);
+        }
+        BALANCES.remove(deps.storage, (&info.sender, &winner));
+        let mut outstanding = SHARES.load(deps.storage)?;
+        outstanding[index] -= held;
+        SHARES.save(deps.storage, &outstanding)?;
+
+        Ok(Response::new()
+            .add_message(BankMsg::Send {
+                to_address: info.sender.to_string(),
+                amount: coins(held.u128(), config.denom),
+            })
+            .add_attribute("action", "redeem")
+            .add_attribute("trader", info.sender)
+            .add_attribute("option", winner)
+            .add_attribute("shares", held.to_string()))
+    }
+
+    pub fn withdraw(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+        nonpayable(&info)?;
+        let config = CONFIG.load(deps.storage)?;
+        if info.sender != config.creator {
+            return Err(ContractError::Unauthorized);
+        }
+        // every share that can still be redeemed or sold back stays covered
+        let outstanding = SHARES.load(deps.storage)?;
+        let owed = match load_phase(deps.as_ref(), &env, &config)? {
+            MarketPhase::Resolved(winner) => {
+                let index = config
+                    .option_index(&winner)
+                    .ok_or_else(|| ContractError::UnknownOption(winner.clone()))?;
+                outstanding[index]
+            }
+            MarketPhase::Expired => math::sell_liability(config.liquidity, &outstanding)?,
+            MarketPhase::Open | MarketPhase::Pending => return Err(ContractError::NotResolved),
+        };
+        let held = deps.querier.query_balance(&env.contract.address, &config.denom)?.amount;
+        let amount = held.saturating_sub(owed);
+        if amount.is_zero() {
+            return Err(ContractError::NothingToWithdraw);
+        }
+
+        Ok(Response::new()
+            .add_message(BankMsg::Send {
+                to_address: config.creator.to_string(),
+                amount: coins(amount.u128(), config.denom),
+            })
+            .add_attribute("action", "withdraw")
+            .add_attribute("creator", config.creator)
+            .add_attribute("amount", amount.to_string()))
+    }
+
+    fn load_phase(deps: Deps, env: &Env, config: &Config) -> StdResult<MarketPhase> {
+        let task: TaskInfoResponse = deps.querier.query_wasm_smart(
+            &config.task_queue,
+            &TaskQueueQueryMsg::TaskInfo { task_id: config.task_id },
+        )?;
+        Ok(task.market_phase(env))
+    }
+}
+
+mod query {
+    use super::*;
+
+    fn option_index(config: &Config, option: &str) -> StdResult<usize> {
+        config
+            .option_index(option)
+            .ok_or_else(|| StdError::generic_err(format!("Unknown option: {}", option)))
+    }
+
+    pub fn quote_buy(deps: Deps, option: String, shares: Uint128) -> StdResult<QuoteResponse> {
+        let config = CONFIG.load(deps.storage)?;
+        let index = option_index(&config, &option)?;
+        let outstanding = SHARES.load(deps.storage)?;
+        let amount = math::buy_cost(config.liquidity, &outstanding, index, shares)
+            .map_err(|err| StdError::generic_err(err.to_string()))?;
+        Ok(QuoteResponse { amount })
+    }
+
+    pub fn quote_sell(deps: Deps, option: String, shares: Uint128) -> StdResult<QuoteResponse> {
+        let config = CONFIG.load(deps.storage)?;
+        let index = option_index(&config, &option)?;
+        let outstanding = SHARES.load(deps.storage)?;
+        if shares > outstanding[index] {
+            return Err(StdError::generic_err(format!("Only {} shares outstanding", outstanding[index])));
+        }
+        let amount = math::sell_return(config.liquidity, &outstanding, index, shares)?;
+        Ok(QuoteResponse { amount })
+    }
+
+    pub fn prices(deps: Deps) -> StdResult<PricesResponse> {
+        let config = CONFIG.load(deps.storage)?;
+        let outstanding = SHARES.load(deps.storage)?;
+        let prices = math::prices(config.liquidity, &outstanding);
+        let prices = config
+            .options
+            .into_iter()
+            .zip(prices)
+            .zip(outstanding)
+            .map(|((option, price), shares)| OptionPrice { option, price, shares })
+            .collect();
+        Ok(PricesResponse { prices })
+    }
+
+    pub fn balance(deps: Deps, address: String) -> StdResult<BalanceResponse> {
+        let address = deps.api.addr_validate(&address)?;
+        let shares = BALANCES
+            .prefix(&address)
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| item.map(|(option, shares)| OptionShares { option, shares }))
+            .collect::<StdResult<Vec<_>>>()?;
+        Ok(BalanceResponse { shares })
+    }
+}
@@ -0,0 +1,54 @@
+use cosmwasm_std::{StdError, Uint128};
+use cw_utils::PaymentError;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("{0}")]
+    Payment(#[from] PaymentError),
+
+    #[error("Markets need a task with options")]
+    NotCategorical,
+
+    #[error("Liquidity must be greater than zero")]
+    ZeroLiquidity,
+
+    #[error("Subsidy must be at least {0}")]
+    InsufficientSubsidy(Uint128),
+
+    #[error("Unknown option: {0}")]
+    UnknownOption(String),
+
+    #[error("Cannot trade zero shares")]
+    ZeroShares,
+
+    #[error("Buying costs {0}, more than was sent")]
+    InsufficientFunds(Uint128),
+
+    #[error("Selling returns {returned}, less than the minimum of {min_return}")]
+    Slippage { returned: Uint128, min_return: Uint128 },
+
+    #[error("Only {0} shares held")]
+    InsufficientShares(Uint128),
+
+    #[error("Trading is closed")]
+    TradingClosed,
+
+    #[error("The task has not completed yet")]
+    NotResolved,
+
+    #[error("No winning shares to redeem")]
+    NothingToRedeem,
+
+    #[error("Unauthorized")]
+    Unauthorized,
+
+    #[error("Nothing left beyond what the winning shares redeem for")]
+    NothingToWithdraw,
+
+    #[error("Too many shares outstanding")]
+    SharesOverflow,
+}
@@ -0,0 +1,29 @@
+use cw_orch::{interface, prelude::*};
+use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use cosmwasm_std::Empty;
+
+type MigrateMsg = cosmwasm_std::Empty;
+pub const CONTRACT_ID: &str = "lmsr_amm";
+
+#[interface(InstantiateMsg, ExecuteMsg, QueryMsg, MigrateMsg, id = CONTRACT_ID)]
+pub struct Contract;
+
+impl<Chain> Uploadable for Contract<Chain> {
+    /// Return the path to the wasm file corresponding to the contract
+    fn wasm(chain: &ChainInfoOwned) -> WasmPath {
+        artifacts_dir_from_workspace!()
+            .find_wasm_path(CONTRACT_ID)
+            .unwrap()
+    }
+
+    /// Returns a CosmWasm contract wrapper
+    fn wrapper() -> Box<dyn MockContract<Empty>> {
+        Box::new(
+            ContractWrapper::new_with_empty(
+                crate::contract::execute,
+                crate::contract::instantiate,
+                crate::contract::query,
+            ), // .with_migrate(crate::contract::migrate),
+        )
+    }
+}
\ No newline at end of file
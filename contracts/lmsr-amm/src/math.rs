@@ -0,0 +1,216 @@
+//! Fixed point LMSR math on `Decimal256`.
+//!
+//! The cost function is `C(q) = b * ln(sum(exp(q_i / b)))`. It is evaluated as
+//! `max(q) + b * ln(sum(exp((q_i - max(q)) / b)))`, so every exponent is at most zero
+//! and the logarithm's argument lies between 1 and the number of options.
+//! That keeps all intermediate values small, and all of them unsigned.
+
+use cosmwasm_std::{Decimal256, StdResult, Uint128};
+
+use crate::error::ContractError;
+
+/// ln(2), rounded down to 18 decimals
+pub const LN_2: Decimal256 = Decimal256::raw(693_147_180_559_945_309);
+
+/// e^-x is below the smallest `Decimal256` from here on
+const EXP_NEG_CUTOFF: u64 = 42;
+
+/// e^x for 0 <= x <= EXP_NEG_CUTOFF
+fn exp(x: Decimal256) -> Decimal256 {
+    // x = k * ln(2) + r with 0 <= r < ln(2), so e^x = 2^k * e^r
+    let mut r = x;
+    let mut scale = Decimal256::one();
+    while r >= LN_2 {
+        r -= LN_2;
+        scale = scale * Decimal256::from_ratio(2u8, 1u8);
+    }
+
+    // Taylor series, which converges quickly for r < ln(2)
+    let mut term = Decimal256::one();
+    let mut sum = Decimal256::one();
+    let mut i = 1u64;
+    while !term.is_zero() {
+        term = term * r / Decimal256::from_ratio(i, 1u8);
+        sum += term;
+        i += 1;
+    }
+    sum * scale
+}
+
+/// e^-x for x >= 0
+pub fn exp_neg(x: Decimal256) -> Decimal256 {
+    if x > Decimal256::from_ratio(EXP_NEG_CUTOFF, 1u8) {
+        return Decimal256::zero();
+    }
+    Decimal256::one() / exp(x)
+}
+
+/// Natural logarithm for y >= 1
+pub fn ln(y: Decimal256) -> Decimal256 {
+    assert!(y >= Decimal256::one(), "ln is only defined from 1 here");
+
+    // y = 2^k * m with 1 <= m < 2, so ln(y) = k * ln(2) + ln(m)
+    let two = Decimal256::from_ratio(2u8, 1u8);
+    let mut m = y;
+    let mut k = 0u64;
+    while m >= two {
+        m = m / two;
+        k += 1;
+    }
+
+    // ln(m) = 2 * atanh(z) with z = (m - 1) / (m + 1) <= 1/3
+    let z = (m - Decimal256::one()) / (m + Decimal256::one());
+    let z2 = z * z;
+    let mut term = z;
+    let mut sum = Decimal256::zero();
+    let mut i = 1u64;
+    while !term.is_zero() {
+        sum += term / Decimal256::from_ratio(i, 1u8);
+        term = term * z2;
+        i += 2;
+    }
+    LN_2 * Decimal256::from_ratio(k, 1u8) + sum * two
+}
+
+/// exp((q_i - max(q)) / b) for every option
+fn weights(liquidity: Uint128, shares: &[Uint128]) -> Vec<Decimal256> {
+    let max = shares.iter().max().copied().unwrap_or_default();
+    shares
+        .iter()
+        .map(|q| exp_neg(Decimal256::from_ratio(max - q, liquidity)))
+        .collect()
+}
+
+/// The LMSR cost function for the outstanding shares of every option
+pub fn cost(liquidity: Uint128, shares: &[Uint128]) -> Decimal256 {
+    let max = shares.iter().max().copied().unwrap_or_default();
+    let sum = weights(liquidity, shares).into_iter().sum();
+    Decimal256::from_ratio(max, 1u8) + Decimal256::from_ratio(liquidity, 1u8) * ln(sum)
+}
+
+/// Current price of each option, between 0 and 1 and summing up to 1
+pub fn prices(liquidity: Uint128, shares: &[Uint128]) -> Vec<Decimal256> {
+    let weights = weights(liquidity, shares);
+    let sum: Decimal256 = weights.iter().sum();
+    weights.into_iter().map(|weight| weight / sum).collect()
+}
+
+/// What the market maker can lose at most: b * ln(n), rounded up
+pub fn subsidy(liquidity: Uint128, options: usize) -> StdResult<Uint128> {
+    let loss = Decimal256::from_ratio(liquidity, 1u8) * ln(Decimal256::from_ratio(options as u64, 1u8));
+    Ok(Uint128::try_from(loss.to_uint_ceil())?)
+}
+
+/// Price of buying `amount` shares of `option`, rounded up in favour of the market maker
+pub fn buy_cost(
+    liquidity: Uint128,
+    shares: &[Uint128],
+    option: usize,
+    amount: Uint128,
+) -> Result<Uint128, ContractError> {
+    let mut after = shares.to_vec();
+    after[option] = after[option]
+        .checked_add(amount)
+        .map_err(|_| ContractError::SharesOverflow)?;
+    // saturating, as rounding could otherwise underflow for options priced close to zero
+    let cost = cost(liquidity, &after).saturating_sub(cost(liquidity, shares));
+    Ok(Uint128::try_from(cost.to_uint_ceil())?)
+}
+
+/// What selling every outstanding share back would return at most, rounded up. Sales round down
+/// and add up to the same cost difference in any order, so this covers all of them
+pub fn sell_liability(liquidity: Uint128, shares: &[Uint128]) -> StdResult<Uint128> {
+    let start = vec![Uint128::zero(); shares.len()];
+    let liability = cost(liquidity, shares).saturating_sub(cost(liquidity, &start));
+    Ok(Uint128::try_from(liability.to_uint_ceil())?)
+}
+
+/// What selling `amount` shares of `option` returns, rounded down in favour of the market maker
+pub fn sell_return(liquidity: Uint128, shares: &[Uint128], option: usize, amount: Uint128) -> StdResult<Uint128> {
+    let mut after = shares.to_vec();
+    after[option] -= amount;
+    let returned = cost(liquidity, shares).saturating_sub(cost(liquidity, &after));
+    Ok(Uint128::try_from(returned.to_uint_floor())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn dec(value: &str) -> Decimal256 {
+        Decimal256::from_str(value).unwrap()
+    }
+
+    /// Asserts two values are at most `1e-15` apart
+    #[track_caller]
+    fn assert_close(actual: Decimal256, expected: Decimal256) {
+        let diff = actual.abs_diff(expected);
+        assert!(diff <= dec("0.000000000000001"), "{} is not close to {}", actual, expected);
+    }
+
+    #[test]
+    fn exp_neg_matches_known_values() {
+        assert_eq!(exp_neg(Decimal256::zero()), Decimal256::one());
+        assert_close(exp_neg(dec("0.1")), dec("0.904837418035959573"));
+        assert_close(exp_neg(Decimal256::one()), dec("0.367879441171442321"));
+        assert_close(exp_neg(dec("10")), dec("0.000045399929762484"));
+        assert_eq!(exp_neg(dec("50")), Decimal256::zero());
+    }
+
+    #[test]
+    fn ln_matches_known_values() {
+        assert_eq!(ln(Decimal256::one()), Decimal256::zero());
+        assert_close(ln(dec("2")), LN_2);
+        assert_close(ln(dec("1.5")), dec("0.405465108108164381"));
+        assert_close(ln(dec("10")), dec("2.302585092994045684"));
+    }
+
+    #[test]
+    fn cost_is_max_loss_at_start() {
+        let liquidity = Uint128::new(1000);
+        let shares = [Uint128::zero(), Uint128::zero()];
+        assert_close(cost(liquidity, &shares), dec("693.147180559945309417"));
+        assert_eq!(subsidy(liquidity, 2).unwrap(), Uint128::new(694));
+    }
+
+    #[test]
+    fn prices_follow_shares() {
+        let liquidity = Uint128::new(1000);
+        let even = prices(liquidity, &[Uint128::zero(), Uint128::zero()]);
+        assert_eq!(even, vec![dec("0.5"), dec("0.5")]);
+
+        let skewed = prices(liquidity, &[Uint128::new(100), Uint128::zero()]);
+        assert_close(skewed[0], dec("0.524979187478939902"));
+        assert_close(skewed[0] + skewed[1], Decimal256::one());
+    }
+
+    #[test]
+    fn trades_round_in_favour_of_the_market_maker() {
+        let liquidity = Uint128::new(1000);
+        let start = [Uint128::zero(), Uint128::zero()];
+        // exact cost is 51.249...
+        let paid = buy_cost(liquidity, &start, 0, Uint128::new(100)).unwrap();
+        assert_eq!(paid, Uint128::new(52));
+        let returned = sell_return(liquidity, &[Uint128::new(100), Uint128::zero()], 0, Uint128::new(100)).unwrap();
+        assert_eq!(returned, Uint128::new(51));
+    }
+
+    #[test]
+    fn liability_covers_selling_everything() {
+        let liquidity = Uint128::new(1000);
+        let shares = [Uint128::new(100), Uint128::new(300)];
+        let first = sell_return(liquidity, &shares, 1, Uint128::new(300)).unwrap();
+        let second = sell_return(liquidity, &[Uint128::new(100), Uint128::zero()], 0, Uint128::new(100)).unwrap();
+        assert!(first + second <= sell_liability(liquidity, &shares).unwrap());
+        assert_eq!(sell_liability(liquidity, &[Uint128::zero(), Uint128::zero()]).unwrap(), Uint128::zero());
+    }
+
+    #[test]
+    fn buying_past_the_share_limit_fails() {
+        let liquidity = Uint128::new(1000);
+        let shares = [Uint128::MAX, Uint128::zero()];
+        let err = buy_cost(liquidity, &shares, 0, Uint128::one()).unwrap_err();
+        assert!(matches!(err, ContractError::SharesOverflow));
+    }
+}
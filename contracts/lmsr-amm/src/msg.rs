@@ -0,0 +1,102 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{Decimal256, Uint128};
+use lavs_apis::id::TaskId;
+
+pub use crate::state::Config;
+
+/// The sent funds are the subsidy, and must cover `b * ln(n)` for `n` options
+#[cw_serde]
+pub struct InstantiateMsg {
+    /// Address of the task queue whose task settles the market
+    pub task_queue: String,
+    /// The task to trade on. It must be open and have options
+    pub task_id: TaskId,
+    /// Native token shares are traded for
+    pub denom: String,
+    /// The LMSR liquidity parameter `b`
+    pub liquidity: Uint128,
+}
+
+#[cw_serde]
+#[derive(cw_orch::ExecuteFns)]
+#[cw_orch(disable_fields_sorting)]
+pub enum ExecuteMsg {
+    /// Buys shares of an option while the task is open. The sent funds cap the cost,
+    /// anything left over is refunded
+    #[cw_orch(payable)]
+    Buy {
+        option: String,
+        shares: Uint128,
+    },
+    /// Sells shares back to the market maker, until the task completes
+    Sell {
+        option: String,
+        shares: Uint128,
+        /// Fails if selling returns less than this
+        min_return: Uint128,
+    },
+    /// Pays out the sender's winning shares 1:1 once the task completed
+    Redeem {},
+    /// Sends the creator what the market holds beyond the winning shares still to be redeemed,
+    /// i.e. the rest of the subsidy and the trading profit. Only once the task completed, or
+    /// can no longer complete, in which case what selling all outstanding shares back returns is kept
+    Withdraw {},
+}
+
+#[cw_serde]
+#[derive(QueryResponses, cw_orch::QueryFns)]
+#[cw_orch(disable_fields_sorting)]
+pub enum QueryMsg {
+    #[returns(Config)]
+    Config {},
+    /// What buying the shares would cost now
+    #[returns(QuoteResponse)]
+    QuoteBuy {
+        option: String,
+        shares: Uint128,
+    },
+    /// What selling the shares would return now
+    #[returns(QuoteResponse)]
+    QuoteSell {
+        option: String,
+        shares: Uint128,
+    },
+    /// Current price and outstanding shares of every option
+    #[returns(PricesResponse)]
+    Prices {},
+    /// Shares held by a trader
+    #[returns(BalanceResponse)]
+    Balance {
+        address: String,
+    },
+}
+
+#[cw_serde]
+pub struct QuoteResponse {
+    pub amount: Uint128,
+}
+
+#[cw_serde]
+pub struct PricesResponse {
+    pub prices: Vec<OptionPrice>,
+}
+
+#[cw_serde]
+pub struct OptionPrice {
+    pub option: String,
+    /// Between 0 and 1, the market's probability of the option winning
+    pub price: Decimal256,
+    /// Outstanding shares
+    pub shares: Uint128,
+}
+
+#[cw_serde]
+pub struct BalanceResponse {
+    pub shares: Vec<OptionShares>,
+}
+
+#[cw_serde]
+pub struct OptionShares {
+    pub option: String,
+    pub shares: Uint128,
+}
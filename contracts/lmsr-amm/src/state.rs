@@ -0,0 +1,33 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Uint128};
+use cw_storage_plus::{Item, Map};
+use lavs_apis::id::TaskId;
+
+pub const CONFIG: Item<Config> = Item::new("config");
+/// Outstanding shares of each option, in the order of `Config::options`
+pub const SHARES: Item<Vec<Uint128>> = Item::new("shares");
+/// Shares held by each trader, by option
+pub const BALANCES: Map<(&Addr, &str), Uint128> = Map::new("balances");
+
+#[cw_serde]
+pub struct Config {
+    /// Paid the subsidy, and can withdraw what is left of it once the market settled
+    pub creator: Addr,
+    /// The task queue whose task settles the market
+    pub task_queue: Addr,
+    pub task_id: TaskId,
+    /// The task's options, copied at instantiation
+    pub options: Vec<String>,
+    /// Native token shares are traded for. A winning share redeems for one unit
+    pub denom: String,
+    /// The LMSR liquidity parameter `b`. Higher means prices move less per trade
+    pub liquidity: Uint128,
+    /// Paid in at instantiation to cover the market maker's worst case loss of `b * ln(n)`
+    pub subsidy: Uint128,
+}
+
+impl Config {
+    pub fn option_index(&self, option: &str) -> Option<usize> {
+        self.options.iter().position(|o| o == option)
+    }
+}
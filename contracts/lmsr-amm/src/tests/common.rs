@@ -0,0 +1,277 @@
+use cosmwasm_std::{coins, Decimal256, Uint128};
+use cw_orch::environment::{ChainState, CwEnv};
+use cw_orch::prelude::*;
+
+use lavs_apis::id::TaskId;
+use lavs_apis::tasks::{Requestor, TimeoutInfo};
+use lavs_orch::{Addressable, AltSigner};
+use serde_json::json;
+
+use lavs_task_queue::{
+    interface::Contract as TasksContract,
+    msg::{ExecuteMsgFns as TasksExecuteMsgFns, InstantiateMsg as TasksInstantiateMsg},
+};
+
+use crate::interface::Contract;
+use crate::msg::{ExecuteMsgFns, InstantiateMsg, OptionShares, QueryMsgFns};
+use crate::ContractError;
+
+pub const BECH_PREFIX: &str = "slay3r";
+pub const DENOM: &str = "uslay";
+/// Signer that resolves tasks in place of a real verifier
+const VERIFIER_INDEX: u32 = 9;
+const TIMEOUT: u64 = 600;
+const LIQUIDITY: u128 = 1000;
+/// 1000 * ln(2), rounded up
+const SUBSIDY: u128 = 694;
+
+pub fn setup<Chain: CwEnv>(chain: Chain, msg: InstantiateMsg) -> Contract<Chain> {
+    let contract = Contract::new(chain);
+    contract.upload().unwrap();
+    contract.instantiate(&msg, None, &coins(SUBSIDY, DENOM)).unwrap();
+    contract
+}
+
+pub fn trade_and_redeem<C>(chain: C)
+where
+    C: CwEnv + AltSigner,
+    C::Sender: Addressable,
+{
+    let trader1 = chain.alt_signer(1);
+    let trader2 = chain.alt_signer(2);
+    let verifier = chain.alt_signer(VERIFIER_INDEX);
+
+    let tasker = setup_task_queue(chain.clone());
+    let task_id = make_task(&tasker);
+
+    // the subsidy must cover the worst case loss
+    let underfunded = Contract::new(chain.clone());
+    underfunded.upload().unwrap();
+    let err = underfunded
+        .instantiate(&instantiate_msg(&tasker, task_id), None, &coins(SUBSIDY - 1, DENOM))
+        .unwrap_err();
+    assert!(
+        err.root()
+            .to_string()
+            .contains(&ContractError::InsufficientSubsidy(Uint128::new(SUBSIDY)).to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+    let amm = setup(chain.clone(), instantiate_msg(&tasker, task_id));
+
+    let prices = amm.prices().unwrap().prices;
+    assert_eq!(prices[0].price, Decimal256::percent(50));
+    assert_eq!(prices[1].price, Decimal256::percent(50));
+
+    // 100 yes shares cost 51.25, rounded up
+    let quote = amm.quote_buy("yes".to_string(), Uint128::new(100)).unwrap();
+    assert_eq!(quote.amount, Uint128::new(52));
+    let err = amm
+        .call_as(&trader1)
+        .buy("yes".to_string(), Uint128::new(100), &coins(51, DENOM))
+        .unwrap_err();
+    assert!(
+        err.root()
+            .to_string()
+            .contains(&ContractError::InsufficientFunds(Uint128::new(52)).to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+    let res = amm
+        .call_as(&trader1)
+        .buy("yes".to_string(), Uint128::new(100), &coins(100, DENOM))
+        .unwrap();
+    assert_eq!(res.event_attr_value("wasm", "cost").unwrap(), "52");
+
+    let prices = amm.prices().unwrap().prices;
+    assert!(prices[0].price > prices[1].price);
+    assert_eq!(prices[0].shares, Uint128::new(100));
+
+    amm.call_as(&trader2)
+        .buy("no".to_string(), Uint128::new(300), &coins(154, DENOM))
+        .unwrap();
+    let prices = amm.prices().unwrap().prices;
+    assert!(prices[1].price > prices[0].price);
+
+    // selling 100 of them back returns 53.74, rounded down
+    let quote = amm.quote_sell("no".to_string(), Uint128::new(100)).unwrap();
+    assert_eq!(quote.amount, Uint128::new(53));
+    let err = amm
+        .call_as(&trader2)
+        .sell("no".to_string(), Uint128::new(100), Uint128::new(54))
+        .unwrap_err();
+    assert!(
+        err.root().to_string().contains(
+            &ContractError::Slippage {
+                returned: Uint128::new(53),
+                min_return: Uint128::new(54)
+            }
+            .to_string()
+        ),
+        "Unexpected error: {}",
+        err.root()
+    );
+    amm.call_as(&trader2)
+        .sell("no".to_string(), Uint128::new(100), Uint128::new(53))
+        .unwrap();
+    let balance = amm.balance(trader2.addr().to_string()).unwrap().shares;
+    assert_eq!(balance, vec![shares("no", 200)]);
+
+    let err = amm.call_as(&trader1).redeem().unwrap_err();
+    assert!(
+        err.root().to_string().contains(&ContractError::NotResolved.to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+    let err = amm.withdraw().unwrap_err();
+    assert!(
+        err.root().to_string().contains(&ContractError::NotResolved.to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+
+    tasker
+        .call_as(&verifier)
+        .resolve_outcome(task_id, "yes".to_string())
+        .unwrap();
+    let err = amm
+        .call_as(&trader2)
+        .sell("no".to_string(), Uint128::new(100), Uint128::zero())
+        .unwrap_err();
+    assert!(
+        err.root().to_string().contains(&ContractError::TradingClosed.to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+
+    // the creator takes everything but the 100 winning shares: 694 + 52 + 154 - 53 - 100
+    let err = amm.call_as(&trader1).withdraw().unwrap_err();
+    assert!(
+        err.root().to_string().contains(&ContractError::Unauthorized.to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+    let res = amm.withdraw().unwrap();
+    assert_eq!(res.event_attr_value("wasm", "amount").unwrap(), "747");
+    let err = amm.withdraw().unwrap_err();
+    assert!(
+        err.root().to_string().contains(&ContractError::NothingToWithdraw.to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+
+    let res = amm.call_as(&trader1).redeem().unwrap();
+    assert_eq!(res.event_attr_value("wasm", "shares").unwrap(), "100");
+    let err = amm.call_as(&trader1).redeem().unwrap_err();
+    assert!(
+        err.root().to_string().contains(&ContractError::NothingToRedeem.to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+    let err = amm.call_as(&trader2).redeem().unwrap_err();
+    assert!(
+        err.root().to_string().contains(&ContractError::NothingToRedeem.to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+}
+
+pub fn expired_markets_only_sell<C>(chain: C)
+where
+    C: CwEnv + AltSigner,
+    C::Sender: Addressable,
+{
+    let trader = chain.alt_signer(1);
+
+    let tasker = setup_task_queue(chain.clone());
+    let task_id = make_task(&tasker);
+    let amm = setup(chain.clone(), instantiate_msg(&tasker, task_id));
+    amm.call_as(&trader)
+        .buy("yes".to_string(), Uint128::new(100), &coins(52, DENOM))
+        .unwrap();
+
+    chain.wait_seconds(TIMEOUT + 1).unwrap();
+    let err = amm
+        .call_as(&trader)
+        .buy("yes".to_string(), Uint128::new(100), &coins(100, DENOM))
+        .unwrap_err();
+    assert!(
+        err.root().to_string().contains(&ContractError::TradingClosed.to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+
+    // the creator takes all but the 52 it would cost to buy back every share: 694 + 52 - 52
+    let res = amm.withdraw().unwrap();
+    assert_eq!(res.event_attr_value("wasm", "amount").unwrap(), "694");
+
+    // the task will never resolve, but shares can still be sold back
+    let res = amm
+        .call_as(&trader)
+        .sell("yes".to_string(), Uint128::new(100), Uint128::zero())
+        .unwrap();
+    assert_eq!(res.event_attr_value("wasm", "returned").unwrap(), "51");
+    let balance = amm.balance(trader.addr().to_string()).unwrap().shares;
+    assert!(balance.is_empty());
+
+    // and the rounding is left once nobody holds shares anymore
+    let res = amm.withdraw().unwrap();
+    assert_eq!(res.event_attr_value("wasm", "amount").unwrap(), "1");
+}
+
+pub fn instantiate_msg<C: CwEnv>(tasker: &TasksContract<C>, task_id: TaskId) -> InstantiateMsg {
+    InstantiateMsg {
+        task_queue: tasker.addr_str().unwrap(),
+        task_id,
+        denom: DENOM.to_string(),
+        liquidity: Uint128::new(LIQUIDITY),
+    }
+}
+
+pub fn shares(option: &str, amount: u128) -> OptionShares {
+    OptionShares {
+        option: option.to_string(),
+        shares: Uint128::new(amount),
+    }
+}
+
+#[track_caller]
+pub fn make_task<C: ChainState + TxHandler>(tasker: &TasksContract<C>) -> TaskId {
+    let res = tasker
+        .create(
+            "Will it rain?".to_string(),
+            None,
+            json!({"question": "Will it rain?"}),
+            vec!["yes".to_string(), "no".to_string()],
+            String::new(),
+            None,
+            &[],
+        )
+        .unwrap();
+    let id = res
+        .event_attr_value("wasm", "task_id")
+        .unwrap()
+        .parse()
+        .unwrap();
+    TaskId::new(id)
+}
+
+pub fn setup_task_queue<C>(chain: C) -> TasksContract<C>
+where
+    C: CwEnv + AltSigner,
+    C::Sender: Addressable,
+{
+    let msg = TasksInstantiateMsg {
+        requestor: Requestor::Fixed(chain.sender_addr().into()),
+        timeout: TimeoutInfo::new(TIMEOUT),
+        verifier: chain.alt_signer(VERIFIER_INDEX).addr().to_string(),
+        dispute: None,
+        arbitrator: None,
+        cancel_fee: None,
+        keeper_reward: None,
+    };
+    let tasker = TasksContract::new(chain);
+    tasker.upload().unwrap();
+    tasker.instantiate(&msg, None, &[]).unwrap();
+    tasker
+}
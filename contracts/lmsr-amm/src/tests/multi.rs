@@ -0,0 +1,27 @@
+use cosmwasm_std::coins;
+use cw_orch::prelude::*;
+use lavs_orch::{Addressable, AltSigner};
+
+use super::common::{BECH_PREFIX, DENOM};
+
+/// The sender pays the subsidy, signers 1 and 2 trade
+fn funded_chain() -> MockBech32 {
+    let chain = MockBech32::new(BECH_PREFIX);
+    chain.add_balance(&chain.sender_addr(), coins(10_000, DENOM)).unwrap();
+    for i in [1, 2] {
+        chain
+            .add_balance(&chain.alt_signer(i).addr(), coins(1000, DENOM))
+            .unwrap();
+    }
+    chain
+}
+
+#[test]
+fn trade_and_redeem() {
+    super::common::trade_and_redeem(funded_chain());
+}
+
+#[test]
+fn expired_markets_only_sell() {
+    super::common::expired_markets_only_sell(funded_chain());
+}
@@ -1,18 +1,22 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Uint128,
-    Addr, Decimal, CosmosMsg, WasmMsg, Reply, SubMsg,
+    coins, to_json_binary, Addr, BankMsg, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult,
+    Storage, Uint128, Decimal,
 };
-use cw2::set_contract_version;
+use cw2::{get_contract_version, set_contract_version};
+use semver::Version;
 
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, InstantiateMsg, InstantiateOperator, QueryMsg};
-use crate::state::{Config, OpInfo, CONFIG};
+use crate::migration;
+use crate::msg::{ExecuteMsg, InstantiateMsg, InstantiateOperator, MigrateMsg, QueryMsg};
+use crate::state::{
+    Config, Delegation, Unbonding, BONDS, CONFIG, DELEGATIONS, OPERATORS, TOTAL_POWER, UNBONDING,
+};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:mock-operators";
-const CONTRACT_VERSION: &str = "1.0.0";
+const CONTRACT_VERSION: &str = "2.0.0";
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -23,24 +27,30 @@ pub fn instantiate(
 ) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
-    let oracle_verifier_addr = deps.api.addr_validate(&msg.oracle_verifier)?;
+    let oracle_verifier = msg
+        .oracle_verifier
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?;
     let mut total_power = Uint128::zero();
-
-    let operators = msg
-        .operators
-        .into_iter()
-        .map(|InstantiateOperator { addr, voting_power }| {
-            let op = deps.api.addr_validate(&addr)?;
-            let power = Uint128::from(voting_power);
-            total_power += power;
-            Ok(OpInfo { op, power })
-        })
-        .collect::<StdResult<Vec<>>>()?;
+    for InstantiateOperator { addr, voting_power } in msg.operators {
+        let op = deps.api.addr_validate(&addr)?;
+        if OPERATORS.has(deps.storage, &op) {
+            return Err(ContractError::OperatorAlreadyRegistered(op.to_string()));
+        }
+        let power = Uint128::from(voting_power);
+        total_power += power;
+        OPERATORS.save(deps.storage, &op, &power, env.block.height)?;
+    }
+    TOTAL_POWER.save(deps.storage, &total_power, env.block.height)?;
 
     let config = Config {
-        operators,
-        total_power,
-        oracle_verifier: oracle_verifier_addr,
+        admin: info.sender,
+        pending_admin: None,
+        guardian: None,
+        paused: false,
+        oracle_verifier,
+        bond_denom: msg.bond_denom,
+        unbonding_period: msg.unbonding_period,
     };
 
     CONFIG.save(deps.storage, &config)?;
@@ -56,101 +66,647 @@ pub fn execute(
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::SubmitVote { task_id, result } => execute::submit_vote(deps, info, task_id, result),
+        ExecuteMsg::SetOracleVerifier { oracle_verifier } => {
+            execute::update_config(deps, info, Some(oracle_verifier), None)
+        }
+        ExecuteMsg::UpdateConfig { oracle_verifier, guardian } => {
+            execute::update_config(deps, info, oracle_verifier, guardian)
+        }
+        ExecuteMsg::Slash { operator, fraction } => execute::slash(deps, env, info, operator, fraction),
+        ExecuteMsg::RegisterOperator { operator, voting_power } => {
+            execute::register_operator(deps, env, info, operator, voting_power)
+        }
+        ExecuteMsg::DeregisterOperator { operator } => {
+            execute::deregister_operator(deps, env, info, operator)
+        }
+        ExecuteMsg::UpdatePower { operator, voting_power } => {
+            execute::update_power(deps, env, info, operator, voting_power)
+        }
+        ExecuteMsg::TransferAdmin { admin } => execute::transfer_admin(deps, info, admin),
+        ExecuteMsg::AcceptAdmin {} => execute::accept_admin(deps, info),
+        ExecuteMsg::Pause {} => execute::set_paused(deps, info, true),
+        ExecuteMsg::Unpause {} => execute::set_paused(deps, info, false),
+        ExecuteMsg::Bond {} => execute::bond(deps, env, info),
+        ExecuteMsg::Unbond { amount } => execute::unbond(deps, env, info, amount),
+        ExecuteMsg::Delegate { operator } => execute::delegate(deps, env, info, operator),
+        ExecuteMsg::Undelegate { operator, amount } => {
+            execute::undelegate(deps, env, info, operator, amount)
+        }
+        ExecuteMsg::Claim {} => execute::claim(deps, env, info),
+    }
+}
+
+/// Upgrades the state of any older release of this contract. Downgrades are refused,
+/// as older code cannot read what newer releases store
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+    if stored.contract != CONTRACT_NAME {
+        return Err(ContractError::WrongContract(stored.contract));
+    }
+    let from: Version = stored.version.parse()?;
+    let to: Version = CONTRACT_VERSION.parse()?;
+    if from > to {
+        return Err(ContractError::CannotDowngrade {
+            from: stored.version,
+            to: CONTRACT_VERSION.to_string(),
+        });
+    }
+
+    match msg {
+        MigrateMsg::FromV1 { bond_denom, unbonding_period } if from.major < 2 => {
+            // only the contract admin can migrate, so it becomes the admin here too
+            let admin = deps
+                .querier
+                .query_wasm_contract_info(&env.contract.address)?
+                .admin
+                .ok_or(ContractError::Unauthorized)?;
+            migration::v1_to_v2(deps.storage, admin, bond_denom, unbonding_period)?;
+        }
+        MigrateMsg::Upgrade {} if from.major >= 2 => {}
+        _ => return Err(ContractError::WrongMigrateMsg(stored.version)),
     }
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    Ok(Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("from", stored.version)
+        .add_attribute("to", CONTRACT_VERSION))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
+        QueryMsg::Config {} => to_json_binary(&CONFIG.load(deps.storage)?),
         QueryMsg::AllVoters {} => to_json_binary(&query::all_voters(deps)?),
         QueryMsg::VotingPowerAtHeight { address, height } => {
-            to_json_binary(&query::voting_power(deps, height, address)?)
+            to_json_binary(&query::voting_power(deps, env, height, address)?)
         }
         QueryMsg::TotalPowerAtHeight { height } => {
-            to_json_binary(&query::total_power(deps, height)?)
+            to_json_binary(&query::total_power(deps, env, height)?)
+        }
+        QueryMsg::Bond { address } => to_json_binary(&query::bond(deps, address)?),
+        QueryMsg::DelegationsByDelegator { delegator, start_after, limit } => {
+            to_json_binary(&query::delegations_by_delegator(deps, delegator, start_after, limit)?)
+        }
+        QueryMsg::DelegationsByOperator { operator, start_after, limit } => {
+            to_json_binary(&query::delegations_by_operator(deps, operator, start_after, limit)?)
         }
     }
 }
 
 mod execute {
     use super::*;
-    use cosmwasm_std::{StdError, Reply, ReplyOn};
+    use cosmwasm_std::Order;
+
+    pub fn update_config(
+        deps: DepsMut,
+        info: MessageInfo,
+        oracle_verifier: Option<String>,
+        guardian: Option<String>,
+    ) -> Result<Response, ContractError> {
+        let mut config = assert_admin(deps.as_ref(), &info)?;
+        let mut res = Response::new().add_attribute("action", "update_config");
+        if let Some(oracle_verifier) = oracle_verifier {
+            let oracle_verifier = deps.api.addr_validate(&oracle_verifier)?;
+            res = res.add_attribute("oracle_verifier", oracle_verifier.as_str());
+            config.oracle_verifier = Some(oracle_verifier);
+        }
+        if let Some(guardian) = guardian {
+            let guardian = deps.api.addr_validate(&guardian)?;
+            res = res.add_attribute("guardian", guardian.as_str());
+            config.guardian = Some(guardian);
+        }
+        CONFIG.save(deps.storage, &config)?;
+
+        Ok(res)
+    }
+
+    pub fn register_operator(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        operator: String,
+        voting_power: u32,
+    ) -> Result<Response, ContractError> {
+        assert_admin(deps.as_ref(), &info)?;
+        if voting_power == 0 {
+            return Err(ContractError::ZeroPower);
+        }
+
+        let operator = deps.api.addr_validate(&operator)?;
+        if OPERATORS.has(deps.storage, &operator) {
+            return Err(ContractError::OperatorAlreadyRegistered(operator.to_string()));
+        }
+        let total_power = set_power(deps, &env, &operator, Some(Uint128::from(voting_power)))?;
 
-    pub fn submit_vote(
+        Ok(Response::new()
+            .add_attribute("action", "register_operator")
+            .add_attribute("operator", operator)
+            .add_attribute("power", voting_power.to_string())
+            .add_attribute("total_power", total_power.to_string()))
+    }
+
+    pub fn deregister_operator(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        operator: String,
+    ) -> Result<Response, ContractError> {
+        assert_admin(deps.as_ref(), &info)?;
+
+        let operator = deps.api.addr_validate(&operator)?;
+        if !OPERATORS.has(deps.storage, &operator) {
+            return Err(ContractError::OperatorNotRegistered(operator.to_string()));
+        }
+        let staked = staked(deps.storage, &operator)?;
+        let total_power = set_power(deps, &env, &operator, Some(staked))?;
+
+        Ok(Response::new()
+            .add_attribute("action", "deregister_operator")
+            .add_attribute("operator", operator)
+            .add_attribute("total_power", total_power.to_string()))
+    }
+
+    pub fn update_power(
         deps: DepsMut,
+        env: Env,
         info: MessageInfo,
-        task_id: TaskId,
-        result: Decimal,
+        operator: String,
+        voting_power: u32,
     ) -> Result<Response, ContractError> {
+        assert_admin(deps.as_ref(), &info)?;
+        if voting_power == 0 {
+            return Err(ContractError::ZeroPower);
+        }
+
+        let operator = deps.api.addr_validate(&operator)?;
+        if !OPERATORS.has(deps.storage, &operator) {
+            return Err(ContractError::OperatorNotRegistered(operator.to_string()));
+        }
+        let staked = staked(deps.storage, &operator)?;
+        let total_power = set_power(deps, &env, &operator, Some(Uint128::from(voting_power) + staked))?;
+
+        Ok(Response::new()
+            .add_attribute("action", "update_power")
+            .add_attribute("operator", operator)
+            .add_attribute("power", voting_power.to_string())
+            .add_attribute("total_power", total_power.to_string()))
+    }
+
+    pub fn transfer_admin(
+        deps: DepsMut,
+        info: MessageInfo,
+        admin: String,
+    ) -> Result<Response, ContractError> {
+        let mut config = assert_admin(deps.as_ref(), &info)?;
+        let pending = deps.api.addr_validate(&admin)?;
+        config.pending_admin = Some(pending.clone());
+        CONFIG.save(deps.storage, &config)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "transfer_admin")
+            .add_attribute("pending_admin", pending))
+    }
+
+    pub fn accept_admin(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+        let mut config = CONFIG.load(deps.storage)?;
+        if config.pending_admin.as_ref() != Some(&info.sender) {
+            return Err(ContractError::Unauthorized);
+        }
+        config.admin = info.sender;
+        config.pending_admin = None;
+        CONFIG.save(deps.storage, &config)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "accept_admin")
+            .add_attribute("admin", config.admin))
+    }
+
+    pub fn set_paused(deps: DepsMut, info: MessageInfo, paused: bool) -> Result<Response, ContractError> {
+        let mut config = CONFIG.load(deps.storage)?;
+        if info.sender != config.admin && config.guardian.as_ref() != Some(&info.sender) {
+            return Err(ContractError::Unauthorized);
+        }
+        config.paused = paused;
+        CONFIG.save(deps.storage, &config)?;
+
+        Ok(Response::new()
+            .add_attribute("action", if paused { "pause" } else { "unpause" })
+            .add_attribute("sender", info.sender))
+    }
+
+    fn assert_admin(deps: Deps, info: &MessageInfo) -> Result<Config, ContractError> {
         let config = CONFIG.load(deps.storage)?;
+        if info.sender != config.admin {
+            return Err(ContractError::Unauthorized);
+        }
+        Ok(config)
+    }
 
-        // Ensure that the sender is a registered operator
-        let operator = config
-            .operators
-            .iter()
-            .find(|op| op.op == info.sender)
-            .ok_or(ContractError::Unauthorized)?;
-
-        // Construct the message to call the Oracle Verifier's SubmitVote function
-        let verifier_contract = config.oracle_verifier;
-        let submit_vote_msg = CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: verifier_contract.to_string(),
-            msg: to_binary(&crate::msg::verifier::SubmitVoteMsg { task_id, result })?,
-            funds: vec![],
-        });
+    pub fn bond(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+        let amount = cw_utils::must_pay(&info, &config.bond_denom)?;
+
+        let bonded = BONDS.may_load(deps.storage, &info.sender)?.unwrap_or_default() + amount;
+        BONDS.save(deps.storage, &info.sender, &bonded)?;
+        let power = OPERATORS.may_load(deps.storage, &info.sender)?.unwrap_or_default() + amount;
+        let total_power = set_power(deps, &env, &info.sender, Some(power))?;
 
-        // Optional: You can include additional logic or events here
-        let response = Response::new()
-            .add_message(submit_vote_msg)
-            .add_attribute("action", "submit_vote")
+        Ok(Response::new()
+            .add_attribute("action", "bond")
             .add_attribute("operator", info.sender)
-            .add_attribute("task_id", task_id.to_string())
-            .add_attribute("result", result.to_string());
+            .add_attribute("amount", amount.to_string())
+            .add_attribute("power", power.to_string())
+            .add_attribute("total_power", total_power.to_string()))
+    }
 
-        Ok(response)
+    pub fn unbond(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        amount: Uint128,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+        if amount.is_zero() {
+            return Err(ContractError::ZeroAmount);
+        }
+        let bonded = BONDS.may_load(deps.storage, &info.sender)?.unwrap_or_default();
+        if amount > bonded {
+            return Err(ContractError::InsufficientBond(bonded));
+        }
+        BONDS.save(deps.storage, &info.sender, &(bonded - amount))?;
+
+        let release_at = env.block.time.seconds() + config.unbonding_period;
+        start_unbonding(deps.storage, &info.sender, &info.sender, amount, release_at)?;
+
+        // Power always includes the whole bond, so this cannot underflow
+        let power = OPERATORS.load(deps.storage, &info.sender)? - amount;
+        let total_power = set_power(deps, &env, &info.sender, Some(power))?;
+
+        Ok(Response::new()
+            .add_attribute("action", "unbond")
+            .add_attribute("operator", info.sender)
+            .add_attribute("amount", amount.to_string())
+            .add_attribute("release_at", release_at.to_string())
+            .add_attribute("power", power.to_string())
+            .add_attribute("total_power", total_power.to_string()))
+    }
+
+    pub fn delegate(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        operator: String,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+        let amount = cw_utils::must_pay(&info, &config.bond_denom)?;
+        let operator = deps.api.addr_validate(&operator)?;
+        let power = OPERATORS
+            .may_load(deps.storage, &operator)?
+            .ok_or_else(|| ContractError::OperatorNotRegistered(operator.to_string()))?;
+
+        DELEGATIONS.update(deps.storage, (&info.sender, &operator), |delegation| -> StdResult<_> {
+            let mut delegation = delegation.unwrap_or(Delegation {
+                delegator: info.sender.clone(),
+                operator: operator.clone(),
+                amount: Uint128::zero(),
+            });
+            delegation.amount += amount;
+            Ok(delegation)
+        })?;
+        let power = power + amount;
+        let total_power = set_power(deps, &env, &operator, Some(power))?;
+
+        Ok(Response::new()
+            .add_attribute("action", "delegate")
+            .add_attribute("delegator", info.sender)
+            .add_attribute("operator", operator)
+            .add_attribute("amount", amount.to_string())
+            .add_attribute("power", power.to_string())
+            .add_attribute("total_power", total_power.to_string()))
+    }
+
+    pub fn undelegate(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        operator: String,
+        amount: Uint128,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+        if amount.is_zero() {
+            return Err(ContractError::ZeroAmount);
+        }
+        let operator = deps.api.addr_validate(&operator)?;
+        let key = (&info.sender, &operator);
+        let mut delegation = DELEGATIONS
+            .may_load(deps.storage, key)?
+            .ok_or(ContractError::InsufficientDelegation(Uint128::zero()))?;
+        if amount > delegation.amount {
+            return Err(ContractError::InsufficientDelegation(delegation.amount));
+        }
+        delegation.amount -= amount;
+        if delegation.amount.is_zero() {
+            DELEGATIONS.remove(deps.storage, key)?;
+        } else {
+            DELEGATIONS.save(deps.storage, key, &delegation)?;
+        }
+
+        let release_at = env.block.time.seconds() + config.unbonding_period;
+        start_unbonding(deps.storage, &info.sender, &operator, amount, release_at)?;
+
+        // Power always includes all delegations, so this cannot underflow
+        let power = OPERATORS.load(deps.storage, &operator)? - amount;
+        let total_power = set_power(deps, &env, &operator, Some(power))?;
+
+        Ok(Response::new()
+            .add_attribute("action", "undelegate")
+            .add_attribute("delegator", info.sender)
+            .add_attribute("operator", operator)
+            .add_attribute("amount", amount.to_string())
+            .add_attribute("release_at", release_at.to_string())
+            .add_attribute("power", power.to_string())
+            .add_attribute("total_power", total_power.to_string()))
+    }
+
+    pub fn claim(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+        let now = env.block.time.seconds();
+        let released = UNBONDING
+            .sub_prefix(&info.sender)
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| item.map(|(_, unbonding)| unbonding))
+            .filter(|item| !matches!(item, Ok(unbonding) if unbonding.release_at > now))
+            .collect::<StdResult<Vec<_>>>()?;
+
+        let amount: Uint128 = released.iter().map(|unbonding| unbonding.amount).sum();
+        if amount.is_zero() {
+            return Err(ContractError::NothingToClaim);
+        }
+        for unbonding in released {
+            UNBONDING.remove(deps.storage, (&info.sender, &unbonding.operator, unbonding.release_at))?;
+        }
+
+        Ok(Response::new()
+            .add_message(BankMsg::Send {
+                to_address: info.sender.to_string(),
+                amount: coins(amount.u128(), config.bond_denom),
+            })
+            .add_attribute("action", "claim")
+            .add_attribute("operator", info.sender)
+            .add_attribute("amount", amount.to_string()))
+    }
+
+    /// Adds to the tokens `owner` can claim from `release_at`
+    fn start_unbonding(
+        storage: &mut dyn Storage,
+        owner: &Addr,
+        operator: &Addr,
+        amount: Uint128,
+        release_at: u64,
+    ) -> StdResult<Unbonding> {
+        UNBONDING.update(storage, (owner, operator, release_at), |unbonding| -> StdResult<_> {
+            let mut unbonding = unbonding.unwrap_or(Unbonding {
+                owner: owner.clone(),
+                operator: operator.clone(),
+                amount: Uint128::zero(),
+                release_at,
+            });
+            unbonding.amount += amount;
+            Ok(unbonding)
+        })
+    }
+
+    /// Tokens counted in the operator's power: its own bond and all delegations
+    fn staked(storage: &dyn Storage, operator: &Addr) -> StdResult<Uint128> {
+        let bonded = BONDS.may_load(storage, operator)?.unwrap_or_default();
+        DELEGATIONS
+            .idx
+            .operator
+            .prefix(operator.clone())
+            .range(storage, None, None, Order::Ascending)
+            .try_fold(bonded, |staked, item| -> StdResult<_> { Ok(staked + item?.1.amount) })
+    }
+
+    /// Sets or removes an operator's power and keeps the total in sync.
+    /// Zero power removes the operator. Returns the new total power
+    fn set_power(
+        deps: DepsMut,
+        env: &Env,
+        operator: &Addr,
+        power: Option<Uint128>,
+    ) -> StdResult<Uint128> {
+        let old = OPERATORS.may_load(deps.storage, operator)?.unwrap_or_default();
+        match power.filter(|power| !power.is_zero()) {
+            Some(power) => OPERATORS.save(deps.storage, operator, &power, env.block.height)?,
+            None => OPERATORS.remove(deps.storage, operator, env.block.height)?,
+        }
+        TOTAL_POWER.update(deps.storage, env.block.height, |total| -> StdResult<_> {
+            Ok(total - old + power.unwrap_or_default())
+        })
+    }
+
+    pub fn slash(
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        operator: String,
+        fraction: Decimal,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+
+        // Only the Oracle Verifier decides who gets slashed
+        if config.oracle_verifier.as_ref() != Some(&info.sender) {
+            return Err(ContractError::Unauthorized);
+        }
+        if config.paused {
+            return Err(ContractError::Paused);
+        }
+        if fraction > Decimal::one() {
+            return Err(ContractError::InvalidSlashFraction(fraction));
+        }
+
+        // An operator that is no longer registered has nothing left to slash.
+        // This must not fail, or the verifier could not complete the task
+        let operator = deps.api.addr_validate(&operator)?;
+        let power = OPERATORS.may_load(deps.storage, &operator)?.unwrap_or_default();
+        let staked = staked(deps.storage, &operator)?;
+        // Tokens that are still unbonding are slashed as well
+        let (slashed_stake, slashed_tokens) = slash_stake(deps.storage, &operator, fraction)?;
+        // The assigned power and every stake are slashed separately, so the power
+        // keeps covering all stake after rounding
+        let slashed = (power - staked).mul_floor(fraction) + slashed_stake;
+        if !power.is_zero() {
+            set_power(deps.branch(), &env, &operator, Some(power - slashed))?;
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "slash")
+            .add_attribute("operator", operator)
+            .add_attribute("fraction", fraction.to_string())
+            .add_attribute("slashed_power", slashed.to_string())
+            .add_attribute("slashed_tokens", slashed_tokens.to_string()))
+    }
+
+    /// Takes `fraction` of the operator's bond, of every delegation to it and of all tokens
+    /// still unbonding from it. Every delegator loses the same share. The tokens stay in the contract.
+    /// Returns the amount taken from stake counted in power, and the amount taken in total
+    fn slash_stake(
+        storage: &mut dyn Storage,
+        operator: &Addr,
+        fraction: Decimal,
+    ) -> StdResult<(Uint128, Uint128)> {
+        let mut slashed_stake = Uint128::zero();
+        if let Some(bonded) = BONDS.may_load(storage, operator)? {
+            let amount = bonded.mul_floor(fraction);
+            BONDS.save(storage, operator, &(bonded - amount))?;
+            slashed_stake += amount;
+        }
+
+        let delegations = DELEGATIONS
+            .idx
+            .operator
+            .prefix(operator.clone())
+            .range(storage, None, None, Order::Ascending)
+            .map(|item| item.map(|(_, delegation)| delegation))
+            .collect::<StdResult<Vec<_>>>()?;
+        for mut delegation in delegations {
+            let amount = delegation.amount.mul_floor(fraction);
+            delegation.amount -= amount;
+            slashed_stake += amount;
+            let key = (&delegation.delegator, operator);
+            if delegation.amount.is_zero() {
+                DELEGATIONS.remove(storage, key)?;
+            } else {
+                DELEGATIONS.save(storage, key, &delegation)?;
+            }
+        }
+
+        let mut slashed_tokens = slashed_stake;
+        let unbonding = UNBONDING
+            .idx
+            .operator
+            .prefix(operator.clone())
+            .range(storage, None, None, Order::Ascending)
+            .map(|item| item.map(|(_, unbonding)| unbonding))
+            .collect::<StdResult<Vec<_>>>()?;
+        for mut unbonding in unbonding {
+            let amount = unbonding.amount.mul_floor(fraction);
+            unbonding.amount -= amount;
+            slashed_tokens += amount;
+            let key = (&unbonding.owner, operator, unbonding.release_at);
+            if unbonding.amount.is_zero() {
+                UNBONDING.remove(storage, key)?;
+            } else {
+                UNBONDING.save(storage, key, &unbonding)?;
+            }
+        }
+
+        Ok((slashed_stake, slashed_tokens))
     }
 }
 
 mod query {
     use super::*;
+    use cosmwasm_std::Order;
+    use crate::msg::{BondResponse, DelegationsResponse};
+    use cw_storage_plus::Bound;
     use lavs_apis::verifier_simple::{AllVotersResponse, TotalPowerResponse, VotingPowerResponse};
 
+    /// Power at the start of block `height`, or the current power if no height is given
     pub fn voting_power(
         deps: Deps,
+        env: Env,
         height: Option<u64>,
         address: String,
     ) -> StdResult<VotingPowerResponse> {
         let addr = deps.api.addr_validate(&address)?;
-        let config = CONFIG.load(deps.storage)?;
-        let op = config.operators.iter().find(|op| op.op == addr);
-        let power = op.map(|op| op.power).unwrap_or_default();
+        let power = match height {
+            Some(height) => OPERATORS.may_load_at_height(deps.storage, &addr, height)?,
+            None => OPERATORS.may_load(deps.storage, &addr)?,
+        };
 
         Ok(VotingPowerResponse {
-            power,
-            height: height.unwrap_or(0),
+            power: power.unwrap_or_default(),
+            height: height.unwrap_or(env.block.height),
         })
     }
 
-    pub fn total_power(deps: Deps, height: Option<u64>) -> StdResult<TotalPowerResponse> {
-        let config = CONFIG.load(deps.storage)?;
+    pub fn total_power(deps: Deps, env: Env, height: Option<u64>) -> StdResult<TotalPowerResponse> {
+        let power = match height {
+            Some(height) => TOTAL_POWER.may_load_at_height(deps.storage, height)?,
+            None => TOTAL_POWER.may_load(deps.storage)?,
+        };
         Ok(TotalPowerResponse {
-            power: config.total_power,
-            height: height.unwrap_or(0),
+            power: power.unwrap_or_default(),
+            height: height.unwrap_or(env.block.height),
         })
     }
 
+    pub fn bond(deps: Deps, address: String) -> StdResult<BondResponse> {
+        let addr = deps.api.addr_validate(&address)?;
+        let unbonding = UNBONDING
+            .sub_prefix(&addr)
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| item.map(|(_, unbonding)| unbonding))
+            .collect::<StdResult<Vec<_>>>()?;
+        Ok(BondResponse {
+            bonded: BONDS.may_load(deps.storage, &addr)?.unwrap_or_default(),
+            unbonding,
+        })
+    }
+
+    pub fn delegations_by_delegator(
+        deps: Deps,
+        delegator: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<DelegationsResponse> {
+        let delegator = deps.api.addr_validate(&delegator)?;
+        let start_after = start_after.map(|addr| deps.api.addr_validate(&addr)).transpose()?;
+        let limit = limit.unwrap_or(10) as usize;
+        let delegations = DELEGATIONS
+            .prefix(&delegator)
+            .range(deps.storage, start_after.as_ref().map(Bound::exclusive), None, Order::Ascending)
+            .take(limit)
+            .map(|item| item.map(|(_, delegation)| delegation))
+            .collect::<StdResult<Vec<_>>>()?;
+        Ok(DelegationsResponse { delegations })
+    }
+
+    pub fn delegations_by_operator(
+        deps: Deps,
+        operator: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<DelegationsResponse> {
+        let operator = deps.api.addr_validate(&operator)?;
+        let start_after = start_after.map(|addr| deps.api.addr_validate(&addr)).transpose()?;
+        let limit = limit.unwrap_or(10) as usize;
+        let start = start_after.map(|delegator| Bound::exclusive((delegator, operator.clone())));
+        let delegations = DELEGATIONS
+            .idx
+            .operator
+            .prefix(operator)
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| item.map(|(_, delegation)| delegation))
+            .collect::<StdResult<Vec<_>>>()?;
+        Ok(DelegationsResponse { delegations })
+    }
+
     pub fn all_voters(deps: Deps) -> StdResult<AllVotersResponse> {
-        let config = CONFIG.load(deps.storage)?;
-        let voters = config
-            .operators
-            .iter()
-            .map(|op| lavs_apis::verifier_simple::VoterInfo {
-                power: op.power,
-                address: op.op.to_string(),
+        let voters = OPERATORS
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| {
+                item.map(|(op, power)| lavs_apis::verifier_simple::VoterInfo {
+                    power,
+                    address: op.to_string(),
+                })
             })
-            .collect();
+            .collect::<StdResult<_>>()?;
 
         Ok(AllVotersResponse { voters })
     }
-}
\ No newline at end of file
+}
@@ -1,15 +1,49 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{Decimal, StdError, Uint128};
+use cw_utils::PaymentError;
 use thiserror::Error;
 #[derive(Error, Debug)]
 pub enum ContractError {
 #[error("{0}")]
 Std(#[from] StdError),
+#[error("{0}")]
+Payment(#[from] PaymentError),
 #[error("Unauthorized")]
 Unauthorized,
 #[error("Failed to submit vote to Oracle Verifier")]
 SubmitVoteError,
 #[error("Invalid Vote Result")]
 InvalidVoteResult,
+#[error("Slash fraction must be at most 1, got {0}")]
+InvalidSlashFraction(Decimal),
+#[error("Operator already registered: {0}")]
+OperatorAlreadyRegistered(String),
+#[error("Operator not registered: {0}")]
+OperatorNotRegistered(String),
+#[error("Voting power must be greater than 0, deregister the operator instead")]
+ZeroPower,
+#[error("Amount must be greater than 0")]
+ZeroAmount,
+#[error("Cannot unbond more than is bonded: {0}")]
+InsufficientBond(Uint128),
+#[error("Cannot undelegate more than is delegated: {0}")]
+InsufficientDelegation(Uint128),
+#[error("No unbonded tokens ready to claim")]
+NothingToClaim,
+#[error("Slashing is paused")]
+Paused,
+#[error("Cannot migrate from a different contract: {0}")]
+WrongContract(String),
+#[error("Cannot migrate from {from} down to {to}")]
+CannotDowngrade { from: String, to: String },
+#[error("Migrating from {0} needs the other MigrateMsg variant")]
+WrongMigrateMsg(String),
+#[error("Invalid version: {0}")]
+SemVer(String),
 // Add any other custom errors you like here.
 // Look at https://docs.rs/thiserror/1.0.21/thiserror/ for details.
+}
+impl From<semver::Error> for ContractError {
+fn from(err: semver::Error) -> Self {
+ContractError::SemVer(err.to_string())
+}
 }
\ No newline at end of file
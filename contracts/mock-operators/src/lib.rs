@@ -1,5 +1,6 @@
 pub mod contract;
 mod error;
+mod migration;
 pub mod msg;
 pub mod state;
 
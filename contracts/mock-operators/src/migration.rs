@@ -0,0 +1,59 @@
+//! State upgrades from earlier releases. Each step reads the state as the older version
+//! stored it and rewrites it in the current layout.
+
+use cosmwasm_std::{Addr, StdResult, Storage, Uint128};
+
+use crate::state::{Config, CONFIG, OPERATORS, TOTAL_POWER};
+
+/// The state of 1.x releases
+pub mod v1 {
+    use cosmwasm_schema::cw_serde;
+    use cosmwasm_std::{Addr, Uint128};
+    use cw_storage_plus::Item;
+
+    pub const CONFIG: Item<Config> = Item::new("config");
+
+    #[cw_serde]
+    pub struct Config {
+        pub operators: Vec<OpInfo>,
+        pub total_power: Uint128,
+        pub oracle_verifier: Addr,
+    }
+
+    #[cw_serde]
+    pub struct OpInfo {
+        pub op: Addr,
+        pub power: Uint128,
+    }
+}
+
+/// Moves the operators out of the config into the power snapshots. 1.x kept no history,
+/// so their power is recorded from height 0 on and counts for tasks created before the migration.
+/// `admin` takes over the role nobody had in 1.x
+pub fn v1_to_v2(
+    storage: &mut dyn Storage,
+    admin: Addr,
+    bond_denom: String,
+    unbonding_period: u64,
+) -> StdResult<()> {
+    let old = v1::CONFIG.load(storage)?;
+    let mut total_power = Uint128::zero();
+    for v1::OpInfo { op, power } in old.operators {
+        total_power += power;
+        OPERATORS.update(storage, &op, 0, |existing| -> StdResult<_> {
+            Ok(existing.unwrap_or_default() + power)
+        })?;
+    }
+    TOTAL_POWER.save(storage, &total_power, 0)?;
+
+    let config = Config {
+        admin,
+        pending_admin: None,
+        guardian: None,
+        paused: false,
+        oracle_verifier: Some(old.oracle_verifier),
+        bond_denom,
+        unbonding_period,
+    };
+    CONFIG.save(storage, &config)
+}
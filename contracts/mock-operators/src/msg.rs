@@ -1,12 +1,21 @@
-use cosmwasm_schema::cw_serde;
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{Decimal, Uint128};
 // This pulls in the queries
 pub use lavs_apis::interfaces::voting::*;
+use lavs_apis::verifier_simple::{AllVotersResponse, TotalPowerResponse, VotingPowerResponse};
+
+pub use crate::state::{Config, Delegation, Unbonding};
 
 #[cw_serde]
 pub struct InstantiateMsg {
     pub operators: Vec<InstantiateOperator>,
-    /// The address of the Oracle Verifier contract
-    pub oracle_verifier: String,
+    /// The address of the Oracle Verifier contract.
+    /// The verifier is usually instantiated afterwards and linked with `SetOracleVerifier`
+    pub oracle_verifier: Option<String>,
+    /// Denom operators bond to gain voting power
+    pub bond_denom: String,
+    /// Seconds unbonded tokens stay slashable before they can be claimed
+    pub unbonding_period: u64,
 }
 
 #[cw_serde]
@@ -23,13 +32,139 @@ impl InstantiateOperator {
     }
 }
 
+#[cw_serde]
+pub enum MigrateMsg {
+    /// From 1.x, which had no bonding yet. They mean the same as in `InstantiateMsg`
+    FromV1 {
+        bond_denom: String,
+        unbonding_period: u64,
+    },
+    /// From any 2.x release
+    Upgrade {},
+}
+
 #[cw_serde]
 #[derive(cw_orch::ExecuteFns)]
 #[cw_orch(disable_fields_sorting)]
 pub enum ExecuteMsg {
-    /// Allows an operator to submit a vote for a specific task
-    SubmitVote {
-        task_id: TaskId,
-        result: Decimal,
+    /// Links the Oracle Verifier contract, like `UpdateConfig` with only the verifier.
+    /// Only callable by the admin
+    SetOracleVerifier {
+        oracle_verifier: String,
+    },
+    /// Changes the settings that are given, leaving the others as they are. Only callable by the admin
+    UpdateConfig {
+        oracle_verifier: Option<String>,
+        guardian: Option<String>,
+    },
+    /// Reduces the operator's power by `fraction`. Only callable by the Oracle Verifier
+    Slash {
+        operator: String,
+        fraction: Decimal,
+    },
+    /// Adds a new operator. Only callable by the admin
+    RegisterOperator {
+        operator: String,
+        voting_power: u32,
     },
+    /// Removes the power the admin assigned to an operator. Power from its own bond
+    /// stays until it unbonds. Only callable by the admin
+    DeregisterOperator {
+        operator: String,
+    },
+    /// Replaces the power the admin assigned to a registered operator, on top of
+    /// anything it bonded. Only callable by the admin
+    UpdatePower {
+        operator: String,
+        voting_power: u32,
+    },
+    /// Proposes another address as admin. It takes over once it accepts,
+    /// until then the current admin stays in charge and may propose someone else.
+    /// Only callable by the admin
+    TransferAdmin {
+        admin: String,
+    },
+    /// Takes over the admin role. Only callable by the proposed admin
+    AcceptAdmin {},
+    /// Stops all slashing, e.g. while an operator's data source is compromised.
+    /// Bonding, unbonding and claims keep working. Only callable by the guardian or the admin
+    Pause {},
+    /// Allows slashing again. Only callable by the guardian or the admin
+    Unpause {},
+    /// Bonds the sent `bond_denom` tokens, adding them to the sender's voting power
+    #[cw_orch(payable)]
+    Bond {},
+    /// Removes bonded tokens from the sender's voting power. They can be claimed
+    /// once the unbonding period is over and can be slashed until then
+    Unbond {
+        amount: Uint128,
+    },
+    /// Delegates the sent `bond_denom` tokens to a registered operator, adding them to its voting power.
+    /// Delegations are slashed together with the operator
+    #[cw_orch(payable)]
+    Delegate {
+        operator: String,
+    },
+    /// Removes delegated tokens from the operator's voting power. They can be claimed
+    /// once the unbonding period is over and can be slashed until then
+    Undelegate {
+        operator: String,
+        amount: Uint128,
+    },
+    /// Sends all tokens whose unbonding period is over back to the sender,
+    /// both unbonded and undelegated
+    Claim {},
+}
+
+#[cw_serde]
+#[derive(QueryResponses, cw_orch::QueryFns)]
+#[cw_orch(disable_fields_sorting)]
+pub enum QueryMsg {
+    /// The admin, linked verifier and bonding settings
+    #[returns(Config)]
+    Config {},
+    /// Voting power of an operator at the start of a block, or the latest if no height is given
+    #[returns(VotingPowerResponse)]
+    VotingPowerAtHeight {
+        address: String,
+        height: Option<u64>,
+    },
+    /// Total voting power at the start of a block, or the latest if no height is given
+    #[returns(TotalPowerResponse)]
+    TotalPowerAtHeight {
+        height: Option<u64>,
+    },
+    /// All operators and their current voting power
+    #[returns(AllVotersResponse)]
+    AllVoters {},
+    /// Tokens an address has bonded, and is unbonding or undelegating
+    #[returns(BondResponse)]
+    Bond {
+        address: String,
+    },
+    /// Delegations of a delegator, ordered by operator
+    #[returns(DelegationsResponse)]
+    DelegationsByDelegator {
+        delegator: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Delegations to an operator, ordered by delegator
+    #[returns(DelegationsResponse)]
+    DelegationsByOperator {
+        operator: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+}
+
+#[cw_serde]
+pub struct DelegationsResponse {
+    pub delegations: Vec<Delegation>,
+}
+
+#[cw_serde]
+pub struct BondResponse {
+    pub bonded: Uint128,
+    pub unbonding: Vec<Unbonding>,
 }
\ No newline at end of file
@@ -1,16 +1,84 @@
 use cosmwasm_schema::cw_serde;
 use cosmwasm_std::{Addr, Uint128};
-use cw_storage_plus::Item;
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex, SnapshotItem, SnapshotMap, Strategy};
 pub const CONFIG: Item<Config> = Item::new("config");
+/// Native tokens each operator has bonded. Counted in their power
+pub const BONDS: Map<&Addr, Uint128> = Map::new("bonds");
+pub struct DelegationIndexes<'a> {
+pub operator: MultiIndex<'a, Addr, Delegation, (Addr, Addr)>,
+}
+impl<'a> IndexList<Delegation> for DelegationIndexes<'a> {
+fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Delegation>> + '_> {
+Box::new(std::iter::once(&self.operator as &dyn Index<Delegation>))
+}
+}
+/// Tokens delegated to operators, by delegator and operator. Counted in the operator's power
+pub const DELEGATIONS: IndexedMap<(&Addr, &Addr), Delegation, DelegationIndexes<'static>> = IndexedMap::new(
+"delegations",
+DelegationIndexes {
+operator: MultiIndex::new(|_, d: &Delegation| d.operator.clone(), "delegations", "delegations_operator"),
+},
+);
+pub struct UnbondingIndexes<'a> {
+pub operator: MultiIndex<'a, Addr, Unbonding, (Addr, Addr, u64)>,
+}
+impl<'a> IndexList<Unbonding> for UnbondingIndexes<'a> {
+fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Unbonding>> + '_> {
+Box::new(std::iter::once(&self.operator as &dyn Index<Unbonding>))
+}
+}
+/// Unbonded and undelegated tokens waiting for the unbonding period to pass, by owner,
+/// operator and release time. Not counted in power, but still slashable
+pub const UNBONDING: IndexedMap<(&Addr, &Addr, u64), Unbonding, UnbondingIndexes<'static>> = IndexedMap::new(
+"unbonding",
+UnbondingIndexes {
+operator: MultiIndex::new(|_, d: &Unbonding| d.operator.clone(), "unbonding", "unbonding_operator"),
+},
+);
+/// Power of every operator, queryable at past heights
+pub const OPERATORS: SnapshotMap<&Addr, Uint128> = SnapshotMap::new(
+"operators",
+"operators__checkpoints",
+"operators__changelog",
+Strategy::EveryBlock,
+);
+/// Sum of all operator power, queryable at past heights
+pub const TOTAL_POWER: SnapshotItem<Uint128> = SnapshotItem::new(
+"total_power",
+"total_power__checkpoints",
+"total_power__changelog",
+Strategy::EveryBlock,
+);
 #[cw_serde]
 pub struct Config {
-pub operators: Vec<OpInfo>,
-pub total_power: Uint128,
-/// The address of the Oracle Verifier contract
-pub oracle_verifier: Addr,
+/// May link the Oracle Verifier contract, manage operators and hand over the role
+pub admin: Addr,
+/// Proposed by the admin, takes over once it accepts
+pub pending_admin: Option<Addr>,
+/// May pause and unpause slashing, next to the admin. Set by the admin with `UpdateConfig`
+pub guardian: Option<Addr>,
+/// Nobody is slashed while set
+pub paused: bool,
+/// The address of the Oracle Verifier contract, the only one allowed to slash
+pub oracle_verifier: Option<Addr>,
+/// Denom operators bond to gain power
+pub bond_denom: String,
+/// Seconds between unbonding and being able to claim the tokens
+pub unbonding_period: u64,
+}
+#[cw_serde]
+pub struct Delegation {
+pub delegator: Addr,
+pub operator: Addr,
+pub amount: Uint128,
 }
 #[cw_serde]
-pub struct OpInfo {
-pub op: Addr,
-pub power: Uint128,
-}
\ No newline at end of file
+pub struct Unbonding {
+/// Who can claim the tokens
+pub owner: Addr,
+/// The operator the tokens were bonded or delegated to. Slashes of this operator hit them
+pub operator: Addr,
+pub amount: Uint128,
+/// UNIX seconds from which the tokens can be claimed
+pub release_at: u64,
+}
@@ -1,13 +1,16 @@
-use cosmwasm_std::Uint128;
+use cosmwasm_std::{coins, Addr, Decimal, Uint128};
 use cw_orch::environment::CwEnv;
 use cw_orch::prelude::*;
 
 use lavs_orch::{Addressable, AltSigner};
 
 use crate::interface::Contract;
-use crate::msg::{InstantiateMsg, InstantiateOperator, QueryMsgFns};
+use crate::msg::{ExecuteMsgFns, InstantiateMsg, InstantiateOperator, QueryMsgFns};
+use crate::ContractError;
 
 pub const BECH_PREFIX: &str = "layer";
+pub const DENOM: &str = "uslay";
+pub const UNBONDING_PERIOD: u64 = 1000;
 
 pub fn setup<Chain: CwEnv>(chain: Chain, msg: InstantiateMsg) -> Contract<Chain> {
     let contract = Contract::new(chain);
@@ -35,25 +38,34 @@ where
     ];
 
     // put real message here
-    let msg = InstantiateMsg { operators };
+    let msg = InstantiateMsg {
+        operators,
+        oracle_verifier: None,
+        bond_denom: DENOM.to_string(),
+        unbonding_period: UNBONDING_PERIOD,
+    };
     let contract = setup(chain.clone(), msg);
+    let instantiated = chain.block_info().unwrap().height;
+    chain.next_block().unwrap();
 
     // now query the total power
     let total_power = contract.total_power_at_height(None).unwrap();
     assert_eq!(total_power.power, Uint128::from(600u64));
     assert_ne!(total_power.height, 0u64);
 
-    // now query the total power
+    // power is only there after the block it was set in
     let total_power = contract.total_power_at_height(Some(173)).unwrap();
-    assert_eq!(total_power.power, Uint128::from(600u64));
+    assert_eq!(total_power.power, Uint128::zero());
     assert_eq!(total_power.height, 173u64);
+    let total_power = contract.total_power_at_height(Some(instantiated + 1)).unwrap();
+    assert_eq!(total_power.power, Uint128::from(600u64));
 
     // query the power of an operator
     let total_power = contract
-        .voting_power_at_height(op2.addr().into_string(), Some(287))
+        .voting_power_at_height(op2.addr().into_string(), Some(instantiated + 1))
         .unwrap();
     assert_eq!(total_power.power, Uint128::from(200u64));
-    assert_eq!(total_power.height, 287u64);
+    assert_eq!(total_power.height, instantiated + 1);
 
     // query the power of an operator with None height (should return the current height, just ensure it works)
     let total_power = contract
@@ -63,8 +75,496 @@ where
 
     // query the power of a non-operator
     let total_power = contract
-        .voting_power_at_height(noop.addr().into_string(), Some(287))
+        .voting_power_at_height(noop.addr().into_string(), Some(instantiated + 1))
         .unwrap();
     assert_eq!(total_power.power, Uint128::zero());
-    assert_eq!(total_power.height, 287u64);
+    assert_eq!(total_power.height, instantiated + 1);
+}
+
+pub fn slashing_reduces_power<C>(chain: C)
+where
+    C: CwEnv + AltSigner,
+    C::Sender: Addressable,
+{
+    let op1 = chain.alt_signer(1);
+    let op2 = chain.alt_signer(2);
+    let verifier = chain.alt_signer(5);
+
+    let operators = vec![
+        InstantiateOperator::new(op1.addr().to_string(), 100),
+        InstantiateOperator::new(op2.addr().to_string(), 200),
+    ];
+    let msg = InstantiateMsg {
+        operators,
+        oracle_verifier: None,
+        bond_denom: DENOM.to_string(),
+        unbonding_period: UNBONDING_PERIOD,
+    };
+    let contract = setup(chain.clone(), msg);
+
+    // nobody can slash before the verifier is linked
+    let err = contract
+        .call_as(&verifier)
+        .slash(op1.addr().to_string(), Decimal::percent(10))
+        .unwrap_err();
+    assert!(
+        err.root()
+            .to_string()
+            .contains(&ContractError::Unauthorized.to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+
+    // only the admin may link it
+    contract
+        .call_as(&op1)
+        .set_oracle_verifier(verifier.addr().to_string())
+        .unwrap_err();
+    contract
+        .set_oracle_verifier(verifier.addr().to_string())
+        .unwrap();
+
+    // operators cannot slash each other
+    contract
+        .call_as(&op1)
+        .slash(op2.addr().to_string(), Decimal::percent(10))
+        .unwrap_err();
+
+    contract
+        .call_as(&verifier)
+        .slash(op2.addr().to_string(), Decimal::percent(10))
+        .unwrap();
+    let power = contract
+        .voting_power_at_height(op2.addr().into_string(), None)
+        .unwrap();
+    assert_eq!(power.power, Uint128::from(180u64));
+    let total_power = contract.total_power_at_height(None).unwrap();
+    assert_eq!(total_power.power, Uint128::from(280u64));
+
+    // slashing someone who is not an operator is a no-op
+    contract
+        .call_as(&verifier)
+        .slash(verifier.addr().to_string(), Decimal::percent(10))
+        .unwrap();
+    let total_power = contract.total_power_at_height(None).unwrap();
+    assert_eq!(total_power.power, Uint128::from(280u64));
+
+    // the guardian can stop all slashing until it is lifted
+    let guardian = chain.alt_signer(6);
+    contract
+        .update_config(None, Some(guardian.addr().to_string()))
+        .unwrap();
+    contract.call_as(&op1).pause().unwrap_err();
+    contract.call_as(&guardian).pause().unwrap();
+    let err = contract
+        .call_as(&verifier)
+        .slash(op2.addr().to_string(), Decimal::percent(10))
+        .unwrap_err();
+    assert!(
+        err.root()
+            .to_string()
+            .contains(&ContractError::Paused.to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+    contract.call_as(&guardian).unpause().unwrap();
+    contract
+        .call_as(&verifier)
+        .slash(op2.addr().to_string(), Decimal::percent(10))
+        .unwrap();
+    let power = contract
+        .voting_power_at_height(op2.addr().into_string(), None)
+        .unwrap();
+    assert_eq!(power.power, Uint128::from(162u64));
+}
+
+pub fn power_is_snapshotted<C>(chain: C)
+where
+    C: CwEnv + AltSigner,
+    C::Sender: Addressable,
+{
+    let op1 = chain.alt_signer(1);
+    let op2 = chain.alt_signer(2);
+    let verifier = chain.alt_signer(5);
+
+    let operators = vec![
+        InstantiateOperator::new(op1.addr().to_string(), 100),
+        InstantiateOperator::new(op2.addr().to_string(), 200),
+    ];
+    let msg = InstantiateMsg {
+        operators,
+        oracle_verifier: Some(verifier.addr().to_string()),
+        bond_denom: DENOM.to_string(),
+        unbonding_period: UNBONDING_PERIOD,
+    };
+    let contract = setup(chain.clone(), msg);
+    chain.next_block().unwrap();
+    let before = chain.block_info().unwrap().height;
+
+    contract
+        .call_as(&verifier)
+        .slash(op1.addr().to_string(), Decimal::percent(50))
+        .unwrap();
+
+    // a slash in the current block is not visible at its start
+    let power = contract
+        .voting_power_at_height(op1.addr().into_string(), Some(before))
+        .unwrap();
+    assert_eq!(power.power, Uint128::from(100u64));
+    chain.next_block().unwrap();
+
+    // past heights keep the power that was in effect back then
+    let power = contract
+        .voting_power_at_height(op1.addr().into_string(), Some(before))
+        .unwrap();
+    assert_eq!(power.power, Uint128::from(100u64));
+    let total_power = contract.total_power_at_height(Some(before)).unwrap();
+    assert_eq!(total_power.power, Uint128::from(300u64));
+
+    let now = chain.block_info().unwrap().height;
+    let power = contract
+        .voting_power_at_height(op1.addr().into_string(), Some(now))
+        .unwrap();
+    assert_eq!(power.power, Uint128::from(50u64));
+    let total_power = contract.total_power_at_height(Some(now)).unwrap();
+    assert_eq!(total_power.power, Uint128::from(250u64));
+
+    // untouched operators are the same at every height
+    let power = contract
+        .voting_power_at_height(op2.addr().into_string(), Some(before))
+        .unwrap();
+    assert_eq!(power.power, Uint128::from(200u64));
+    let power = contract
+        .voting_power_at_height(op2.addr().into_string(), Some(now))
+        .unwrap();
+    assert_eq!(power.power, Uint128::from(200u64));
+}
+
+pub fn operators_can_be_rotated<C>(chain: C)
+where
+    C: CwEnv + AltSigner,
+    C::Sender: Addressable,
+{
+    let op1 = chain.alt_signer(1);
+    let op2 = chain.alt_signer(2);
+    let op3 = chain.alt_signer(3);
+    let new_admin = chain.alt_signer(6);
+
+    let operators = vec![
+        InstantiateOperator::new(op1.addr().to_string(), 100),
+        InstantiateOperator::new(op2.addr().to_string(), 200),
+    ];
+    let msg = InstantiateMsg {
+        operators,
+        oracle_verifier: None,
+        bond_denom: DENOM.to_string(),
+        unbonding_period: UNBONDING_PERIOD,
+    };
+    let contract = setup(chain.clone(), msg);
+    chain.next_block().unwrap();
+
+    // only the admin manages operators
+    let err = contract
+        .call_as(&op1)
+        .register_operator(op3.addr().to_string(), 50)
+        .unwrap_err();
+    assert!(
+        err.root()
+            .to_string()
+            .contains(&ContractError::Unauthorized.to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+
+    let res = contract
+        .register_operator(op3.addr().to_string(), 50)
+        .unwrap();
+    assert_eq!(res.event_attr_value("wasm", "total_power").unwrap(), "350");
+    let err = contract
+        .register_operator(op3.addr().to_string(), 50)
+        .unwrap_err();
+    assert!(
+        err.root()
+            .to_string()
+            .contains(&ContractError::OperatorAlreadyRegistered(op3.addr().to_string()).to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+
+    contract.update_power(op2.addr().to_string(), 150).unwrap();
+    let rotated = chain.block_info().unwrap().height;
+    contract.deregister_operator(op1.addr().to_string()).unwrap();
+    let err = contract
+        .deregister_operator(op1.addr().to_string())
+        .unwrap_err();
+    assert!(
+        err.root()
+            .to_string()
+            .contains(&ContractError::OperatorNotRegistered(op1.addr().to_string()).to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+    contract
+        .update_power(op1.addr().to_string(), 10)
+        .unwrap_err();
+    chain.next_block().unwrap();
+
+    let total_power = contract.total_power_at_height(None).unwrap();
+    assert_eq!(total_power.power, Uint128::from(200u64));
+    let voters = contract.all_voters().unwrap().voters;
+    assert_eq!(voters.len(), 2);
+    let power = contract
+        .voting_power_at_height(op1.addr().into_string(), None)
+        .unwrap();
+    assert_eq!(power.power, Uint128::zero());
+
+    // the old set is still visible at the height before the rotation
+    let power = contract
+        .voting_power_at_height(op1.addr().into_string(), Some(rotated))
+        .unwrap();
+    assert_eq!(power.power, Uint128::from(100u64));
+    let total_power = contract.total_power_at_height(Some(rotated)).unwrap();
+    assert_eq!(total_power.power, Uint128::from(300u64));
+
+    // the admin role moves to the new admin only once it accepts
+    contract
+        .transfer_admin(new_admin.addr().to_string())
+        .unwrap();
+    contract
+        .call_as(&new_admin)
+        .register_operator(op1.addr().to_string(), 100)
+        .unwrap_err();
+    contract.call_as(&op1).accept_admin().unwrap_err();
+    contract.call_as(&new_admin).accept_admin().unwrap();
+    let config = contract.config().unwrap();
+    assert_eq!(config.admin, new_admin.addr());
+    assert_eq!(config.pending_admin, None);
+    contract
+        .register_operator(op1.addr().to_string(), 100)
+        .unwrap_err();
+    contract
+        .call_as(&new_admin)
+        .register_operator(op1.addr().to_string(), 100)
+        .unwrap();
+}
+
+/// Expects `alt_signer(1)` and `alt_signer(2)` to hold at least 1000 `DENOM` each
+pub fn bonding_gives_power<C>(chain: C)
+where
+    C: CwEnv + AltSigner,
+    C::Sender: Addressable,
+{
+    let op1 = chain.alt_signer(1);
+    let op2 = chain.alt_signer(2);
+    let verifier = chain.alt_signer(5);
+
+    let operators = vec![InstantiateOperator::new(op1.addr().to_string(), 100)];
+    let msg = InstantiateMsg {
+        operators,
+        oracle_verifier: Some(verifier.addr().to_string()),
+        bond_denom: DENOM.to_string(),
+        unbonding_period: UNBONDING_PERIOD,
+    };
+    let contract = setup(chain.clone(), msg);
+
+    // bonding adds to the assigned power, and anyone can bond to become an operator
+    contract.call_as(&op1).bond(&coins(400, DENOM)).unwrap();
+    contract.call_as(&op2).bond(&coins(1000, DENOM)).unwrap();
+    let power = contract
+        .voting_power_at_height(op1.addr().into_string(), None)
+        .unwrap();
+    assert_eq!(power.power, Uint128::from(500u64));
+    let total_power = contract.total_power_at_height(None).unwrap();
+    assert_eq!(total_power.power, Uint128::from(1500u64));
+
+    // only the bond denom is accepted
+    contract
+        .call_as(&op1)
+        .bond(&coins(400, "uatom"))
+        .unwrap_err();
+
+    let err = contract
+        .call_as(&op2)
+        .unbond(Uint128::new(1001))
+        .unwrap_err();
+    assert!(
+        err.root()
+            .to_string()
+            .contains(&ContractError::InsufficientBond(Uint128::new(1000)).to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+    contract.call_as(&op2).unbond(Uint128::new(600)).unwrap();
+    let power = contract
+        .voting_power_at_height(op2.addr().into_string(), None)
+        .unwrap();
+    assert_eq!(power.power, Uint128::from(400u64));
+
+    // slashing hits the assigned power, the bond and what is still unbonding
+    let res = contract
+        .call_as(&verifier)
+        .slash(op2.addr().to_string(), Decimal::percent(10))
+        .unwrap();
+    assert_eq!(res.event_attr_value("wasm", "slashed_power").unwrap(), "40");
+    assert_eq!(res.event_attr_value("wasm", "slashed_tokens").unwrap(), "100");
+    let bond = contract.bond(op2.addr().into_string()).unwrap();
+    assert_eq!(bond.bonded, Uint128::new(360));
+    assert_eq!(bond.unbonding[0].amount, Uint128::new(540));
+    contract
+        .call_as(&verifier)
+        .slash(op1.addr().to_string(), Decimal::percent(10))
+        .unwrap();
+    let power = contract
+        .voting_power_at_height(op1.addr().into_string(), None)
+        .unwrap();
+    assert_eq!(power.power, Uint128::from(450u64));
+    let bond = contract.bond(op1.addr().into_string()).unwrap();
+    assert_eq!(bond.bonded, Uint128::new(360));
+
+    // unbonded tokens can only be claimed after the unbonding period
+    let err = contract.call_as(&op2).claim().unwrap_err();
+    assert!(
+        err.root()
+            .to_string()
+            .contains(&ContractError::NothingToClaim.to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+    chain.wait_seconds(UNBONDING_PERIOD).unwrap();
+    let res = contract.call_as(&op2).claim().unwrap();
+    assert_eq!(res.event_attr_value("wasm", "amount").unwrap(), "540");
+    let bond = contract.bond(op2.addr().into_string()).unwrap();
+    assert!(bond.unbonding.is_empty());
+    contract.call_as(&op2).claim().unwrap_err();
+
+    // unbonding everything removes an operator without assigned power
+    contract.call_as(&op2).unbond(Uint128::new(360)).unwrap();
+    let voters = contract.all_voters().unwrap().voters;
+    assert_eq!(voters.len(), 1);
+    assert_eq!(voters[0].address, op1.addr().to_string());
+}
+
+/// Expects `alt_signer(7)` and `alt_signer(8)` to hold at least 1000 `DENOM` each
+pub fn delegations_are_slashed_pro_rata<C>(chain: C)
+where
+    C: CwEnv + AltSigner,
+    C::Sender: Addressable,
+{
+    let op1 = chain.alt_signer(1);
+    let op2 = chain.alt_signer(2);
+    let verifier = chain.alt_signer(5);
+    let delegator1 = chain.alt_signer(7);
+    let delegator2 = chain.alt_signer(8);
+
+    let operators = vec![InstantiateOperator::new(op1.addr().to_string(), 100)];
+    let msg = InstantiateMsg {
+        operators,
+        oracle_verifier: Some(verifier.addr().to_string()),
+        bond_denom: DENOM.to_string(),
+        unbonding_period: UNBONDING_PERIOD,
+    };
+    let contract = setup(chain.clone(), msg);
+
+    // only registered operators can be delegated to
+    let err = contract
+        .call_as(&delegator1)
+        .delegate(op2.addr().to_string(), &coins(300, DENOM))
+        .unwrap_err();
+    assert!(
+        err.root()
+            .to_string()
+            .contains(&ContractError::OperatorNotRegistered(op2.addr().to_string()).to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+
+    contract
+        .call_as(&delegator1)
+        .delegate(op1.addr().to_string(), &coins(300, DENOM))
+        .unwrap();
+    contract
+        .call_as(&delegator2)
+        .delegate(op1.addr().to_string(), &coins(100, DENOM))
+        .unwrap();
+    let power = contract
+        .voting_power_at_height(op1.addr().into_string(), None)
+        .unwrap();
+    assert_eq!(power.power, Uint128::from(500u64));
+
+    let err = contract
+        .call_as(&delegator2)
+        .undelegate(op1.addr().to_string(), Uint128::new(101))
+        .unwrap_err();
+    assert!(
+        err.root()
+            .to_string()
+            .contains(&ContractError::InsufficientDelegation(Uint128::new(100)).to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+    contract
+        .call_as(&delegator2)
+        .undelegate(op1.addr().to_string(), Uint128::new(50))
+        .unwrap();
+    let power = contract
+        .voting_power_at_height(op1.addr().into_string(), None)
+        .unwrap();
+    assert_eq!(power.power, Uint128::from(450u64));
+
+    // every delegator loses the same share, including what is still undelegating
+    let res = contract
+        .call_as(&verifier)
+        .slash(op1.addr().to_string(), Decimal::percent(10))
+        .unwrap();
+    assert_eq!(res.event_attr_value("wasm", "slashed_power").unwrap(), "45");
+    assert_eq!(res.event_attr_value("wasm", "slashed_tokens").unwrap(), "40");
+    let power = contract
+        .voting_power_at_height(op1.addr().into_string(), None)
+        .unwrap();
+    assert_eq!(power.power, Uint128::from(405u64));
+
+    let delegations = contract
+        .delegations_by_operator(op1.addr().to_string(), None, None)
+        .unwrap()
+        .delegations;
+    assert_eq!(delegations.len(), 2);
+    let amount_of = |delegator: &Addr| {
+        delegations
+            .iter()
+            .find(|delegation| &delegation.delegator == delegator)
+            .unwrap()
+            .amount
+    };
+    assert_eq!(amount_of(&delegator1.addr()), Uint128::new(270));
+    assert_eq!(amount_of(&delegator2.addr()), Uint128::new(45));
+
+    let page = contract
+        .delegations_by_operator(op1.addr().to_string(), Some(delegations[0].delegator.to_string()), None)
+        .unwrap()
+        .delegations;
+    assert_eq!(page, vec![delegations[1].clone()]);
+
+    let delegations = contract
+        .delegations_by_delegator(delegator1.addr().to_string(), None, None)
+        .unwrap()
+        .delegations;
+    assert_eq!(delegations.len(), 1);
+    assert_eq!(delegations[0].operator, op1.addr());
+    assert_eq!(delegations[0].amount, Uint128::new(270));
+
+    let bond = contract.bond(delegator2.addr().into_string()).unwrap();
+    assert_eq!(bond.unbonding.len(), 1);
+    assert_eq!(bond.unbonding[0].operator, op1.addr());
+    assert_eq!(bond.unbonding[0].amount, Uint128::new(45));
+
+    // deregistering leaves the delegated power in place
+    contract.deregister_operator(op1.addr().to_string()).unwrap();
+    let power = contract
+        .voting_power_at_height(op1.addr().into_string(), None)
+        .unwrap();
+    assert_eq!(power.power, Uint128::from(315u64));
+
+    chain.wait_seconds(UNBONDING_PERIOD).unwrap();
+    let res = contract.call_as(&delegator2).claim().unwrap();
+    assert_eq!(res.event_attr_value("wasm", "amount").unwrap(), "45");
 }
@@ -1,9 +1,180 @@
-use cw_orch::prelude::MockBech32;
+use cosmwasm_std::{coins, Addr, Decimal, Storage, Uint128};
+use cw_orch::prelude::*;
+use lavs_orch::{Addressable, AltSigner};
 
-use super::common::BECH_PREFIX;
+use super::common::{BECH_PREFIX, DENOM, UNBONDING_PERIOD};
+use crate::interface::Contract;
+use crate::migration::v1;
+use crate::msg::{ExecuteMsgFns, InstantiateMsg, MigrateMsg, QueryMsgFns};
+use crate::state::CONFIG;
+use crate::ContractError;
 
 #[test]
 fn happy_path_works() {
     let chain = MockBech32::new(BECH_PREFIX);
     super::common::happy_path(chain);
 }
+
+#[test]
+fn slashing_reduces_power() {
+    let chain = MockBech32::new(BECH_PREFIX);
+    super::common::slashing_reduces_power(chain);
+}
+
+#[test]
+fn power_is_snapshotted() {
+    let chain = MockBech32::new(BECH_PREFIX);
+    super::common::power_is_snapshotted(chain);
+}
+
+#[test]
+fn operators_can_be_rotated() {
+    let chain = MockBech32::new(BECH_PREFIX);
+    super::common::operators_can_be_rotated(chain);
+}
+
+#[test]
+fn bonding_gives_power() {
+    let chain = MockBech32::new(BECH_PREFIX);
+    for i in [1, 2] {
+        chain
+            .add_balance(&chain.alt_signer(i).addr(), coins(1000, DENOM))
+            .unwrap();
+    }
+    super::common::bonding_gives_power(chain);
+}
+
+#[test]
+fn delegations_are_slashed_pro_rata() {
+    let chain = MockBech32::new(BECH_PREFIX);
+    for i in [7, 8] {
+        chain
+            .add_balance(&chain.alt_signer(i).addr(), coins(1000, DENOM))
+            .unwrap();
+    }
+    super::common::delegations_are_slashed_pro_rata(chain);
+}
+
+/// Runs `f` on the contract's storage, to leave it the way an older release would have
+fn with_storage<T>(chain: &MockBech32, contract: &Addr, f: impl FnOnce(&mut dyn Storage) -> T) -> T {
+    let mut app = chain.app.borrow_mut();
+    f(app.contract_storage_mut(contract).as_mut())
+}
+
+fn deploy(chain: &MockBech32) -> Contract<MockBech32> {
+    let contract = Contract::new(chain.clone());
+    contract.upload().unwrap();
+    let msg = InstantiateMsg {
+        operators: vec![],
+        oracle_verifier: None,
+        bond_denom: DENOM.to_string(),
+        unbonding_period: UNBONDING_PERIOD,
+    };
+    contract.instantiate(&msg, Some(&chain.sender_addr()), &[]).unwrap();
+    contract
+}
+
+#[test]
+fn migrates_v1_state() {
+    let chain = MockBech32::new(BECH_PREFIX);
+    let op1 = chain.alt_signer(1);
+    let op2 = chain.alt_signer(2);
+    let verifier = chain.alt_signer(5);
+    let contract = deploy(&chain);
+
+    with_storage(&chain, &contract.address().unwrap(), |storage| {
+        cw2::set_contract_version(storage, "crates.io:mock-operators", "1.0.0").unwrap();
+        let config = v1::Config {
+            operators: vec![
+                v1::OpInfo {
+                    op: op1.addr(),
+                    power: Uint128::new(100),
+                },
+                v1::OpInfo {
+                    op: op2.addr(),
+                    power: Uint128::new(200),
+                },
+            ],
+            total_power: Uint128::new(300),
+            oracle_verifier: verifier.addr(),
+        };
+        v1::CONFIG.save(storage, &config).unwrap();
+    });
+    chain.next_block().unwrap();
+    let before = chain.block_info().unwrap().height;
+    chain.next_block().unwrap();
+
+    // 1.x had no bonding, so its settings must be given
+    let err = contract
+        .migrate(&MigrateMsg::Upgrade {}, contract.code_id().unwrap())
+        .unwrap_err();
+    assert!(
+        err.root()
+            .to_string()
+            .contains(&ContractError::WrongMigrateMsg("1.0.0".to_string()).to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+    let msg = MigrateMsg::FromV1 {
+        bond_denom: DENOM.to_string(),
+        unbonding_period: UNBONDING_PERIOD,
+    };
+    contract.migrate(&msg, contract.code_id().unwrap()).unwrap();
+
+    let config = with_storage(&chain, &contract.address().unwrap(), |storage| {
+        assert_eq!(cw2::get_contract_version(storage).unwrap().version, "2.0.0");
+        CONFIG.load(storage).unwrap()
+    });
+    assert_eq!(config.admin, chain.sender_addr());
+    assert_eq!(config.pending_admin, None);
+    assert_eq!(config.oracle_verifier, Some(verifier.addr()));
+    assert_eq!(config.bond_denom, DENOM);
+
+    // the operators keep their power, also at heights before the migration
+    let power = contract.voting_power_at_height(op1.addr().into_string(), None).unwrap();
+    assert_eq!(power.power, Uint128::new(100));
+    let power = contract
+        .voting_power_at_height(op2.addr().into_string(), Some(before))
+        .unwrap();
+    assert_eq!(power.power, Uint128::new(200));
+    let total_power = contract.total_power_at_height(Some(before)).unwrap();
+    assert_eq!(total_power.power, Uint128::new(300));
+    let voters = contract.all_voters().unwrap();
+    assert_eq!(voters.voters.len(), 2);
+
+    // and the verifier can slash them again
+    contract
+        .call_as(&verifier)
+        .slash(op1.addr().to_string(), Decimal::percent(50))
+        .unwrap();
+    chain.next_block().unwrap();
+    let total_power = contract.total_power_at_height(None).unwrap();
+    assert_eq!(total_power.power, Uint128::new(250));
+}
+
+#[test]
+fn migration_refuses_downgrades() {
+    let chain = MockBech32::new(BECH_PREFIX);
+    let contract = deploy(&chain);
+
+    // 2.x only needs new code
+    contract
+        .migrate(&MigrateMsg::Upgrade {}, contract.code_id().unwrap())
+        .unwrap();
+
+    with_storage(&chain, &contract.address().unwrap(), |storage| {
+        cw2::set_contract_version(storage, "crates.io:mock-operators", "3.0.0").unwrap();
+    });
+    let err = contract
+        .migrate(&MigrateMsg::Upgrade {}, contract.code_id().unwrap())
+        .unwrap_err();
+    let expected = ContractError::CannotDowngrade {
+        from: "3.0.0".to_string(),
+        to: "2.0.0".to_string(),
+    };
+    assert!(
+        err.root().to_string().contains(&expected.to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+}
@@ -1,18 +1,29 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Uint128, Decimal,
-    CosmosMsg, WasmMsg, Order,
+    to_json_binary, Addr, BankMsg, Binary, Coin, Deps, DepsMut, Env, HexBinary, MessageInfo, Response,
+    StdResult, Uint128, Decimal, CosmosMsg, WasmMsg, Order,
 };
-use cw2::set_contract_version;
+use cw2::{get_contract_version, set_contract_version};
+use semver::Version;
 
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
-use crate::state::{Config, OperatorVote, SLASHED_OPERATORS, CONFIG, VOTES, TASKS, TaskResponse};
-use lavs_apis::verifier_simple::{AllVotersResponse, TaskMetadata, TaskQueryMsg, VotingPowerResponse};
+use crate::migration::{self, V2Settings};
+use crate::msg::{vote_hash, ExecuteMsg, InstantiateMsg, MigrateMsg, PendingRewardsResponse, QueryMsg, SlashesResponse};
+use crate::state::{
+    Config, OperatorVote, SlashReason, SlashRecord, TaskInfo, TaskOption, VoteResult, ALLOCATED, COMMITS, CONFIG,
+    VOTES, REWARDS, SLASHES, TASKS, TaskResponse,
+};
+use lavs_apis::id::TaskId;
+use lavs_apis::verifier_simple::{AllVotersResponse, TotalPowerResponse, VotingPowerResponse};
+use lavs_mock_operators::msg::ExecuteMsg as OperatorsExecuteMsg;
+use lavs_task_queue::msg::{
+    ExecuteMsg as TaskExecuteMsg, QueryMsg as TaskQueueQueryMsg, TaskInfoResponse, TaskPayload,
+    VoteCountResponse, INVALID_OPTION,
+};
 
 const CONTRACT_NAME: &str = "crates.io:oracle-verifier";
-const CONTRACT_VERSION: &str = "1.0.0";
+const CONTRACT_VERSION: &str = "2.0.0";
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -28,8 +39,19 @@ pub fn instantiate(
         threshold_percent: msg.threshold_percent,
         allowed_spread: msg.allowed_spread,
         slashable_spread: msg.slashable_spread,
+        slash_fraction: msg.slash_fraction,
+        supermajority: msg.supermajority,
+        commit_window: msg.commit_window,
+        reveal_window: msg.reveal_window,
+        challenge_threshold: msg.challenge_threshold,
         operator_contract: operator_contract.clone(),
+        task_queue: None,
+        owner: info.sender,
+        pending_owner: None,
+        guardian: None,
+        paused: false,
     };
+    config.validate()?;
     CONFIG.save(deps.storage, &config)?;
 
     Ok(Response::new().add_attribute("action", "instantiate"))
@@ -43,225 +65,812 @@ pub fn execute(
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::SubmitVote { task_id, operator, result } => {
-            execute::submit_vote(deps, env, info, task_id, operator, result)
+        ExecuteMsg::CommitVote { task_queue, task_id, hash } => {
+            execute::commit_vote(deps, env, info, task_queue, task_id, hash)
+        }
+        ExecuteMsg::RevealVote { task_id, result, salt } => {
+            execute::reveal_vote(deps, env, info, task_id, result, salt)
         }
         ExecuteMsg::ProcessVotes { task_id } => {
             execute::process_votes(deps, env, info, task_id)
         }
-        ExecuteMsg::SlashOperators { task_id } => {
-            execute::slash_operators(deps, env, info, task_id)
+        ExecuteMsg::ClaimRewards {} => execute::claim_rewards(deps, info),
+        ExecuteMsg::WithdrawSurplus { denom } => execute::withdraw_surplus(deps, env, info, denom),
+        ExecuteMsg::UpdateConfig {
+            threshold_percent,
+            allowed_spread,
+            slashable_spread,
+            supermajority,
+            challenge_threshold,
+            guardian,
+            task_queue,
+        } => execute::update_config(
+            deps,
+            info,
+            threshold_percent,
+            allowed_spread,
+            slashable_spread,
+            supermajority,
+            challenge_threshold,
+            guardian,
+            task_queue,
+        ),
+        ExecuteMsg::TransferOwnership { owner } => execute::transfer_ownership(deps, info, owner),
+        ExecuteMsg::AcceptOwnership {} => execute::accept_ownership(deps, info),
+        ExecuteMsg::Pause {} => execute::set_paused(deps, info, true),
+        ExecuteMsg::Unpause {} => execute::set_paused(deps, info, false),
+    }
+}
+
+/// Upgrades the state of any older release of this contract. Downgrades are refused,
+/// as older code cannot read what newer releases store
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+    if stored.contract != CONTRACT_NAME {
+        return Err(ContractError::WrongContract(stored.contract));
+    }
+    let from: Version = stored.version.parse()?;
+    let to: Version = CONTRACT_VERSION.parse()?;
+    if from > to {
+        return Err(ContractError::CannotDowngrade {
+            from: stored.version,
+            to: CONTRACT_VERSION.to_string(),
+        });
+    }
+
+    match msg {
+        MigrateMsg::FromV1 {
+            slash_fraction,
+            supermajority,
+            commit_window,
+            reveal_window,
+            challenge_threshold,
+        } if from.major < 2 => {
+            let settings = V2Settings {
+                slash_fraction,
+                supermajority,
+                commit_window,
+                reveal_window,
+                challenge_threshold,
+            };
+            // only the contract admin can migrate, so it becomes the owner too
+            let owner = deps
+                .querier
+                .query_wasm_contract_info(&env.contract.address)?
+                .admin
+                .ok_or(ContractError::Unauthorized)?;
+            migration::v1_to_v2(deps.storage, settings, owner)?;
         }
+        MigrateMsg::Upgrade {} if from.major >= 2 => {}
+        _ => return Err(ContractError::WrongMigrateMsg(stored.version)),
+    }
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    Ok(Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("from", stored.version)
+        .add_attribute("to", CONTRACT_VERSION))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::VotingPowerAtHeight { address, height } => {
+            to_json_binary(&query::voting_power(deps, env, address, height)?)
+        }
+        QueryMsg::TotalPowerAtHeight { height } => to_json_binary(&query::total_power(deps, env, height)?),
+        QueryMsg::AllVoters {} => to_json_binary(&query::all_voters(deps, env)?),
+        QueryMsg::TaskInfo { task_contract, task_id } => {
+            to_json_binary(&query::task_info(deps, env, task_contract, task_id)?)
+        }
+        QueryMsg::VoteCount { task_contract, task_id } => {
+            to_json_binary(&query::vote_count(deps, task_contract, task_id)?)
+        }
+        QueryMsg::SlashesByTask { task_id } => to_json_binary(&query::slashes_by_task(deps, task_id)?),
+        QueryMsg::SlashesByOperator { operator, start_after, limit } => {
+            to_json_binary(&query::slashes_by_operator(deps, operator, start_after, limit)?)
+        }
+        QueryMsg::PendingRewards { operator } => to_json_binary(&query::pending_rewards(deps, operator)?),
+        QueryMsg::Config {} => to_json_binary(&CONFIG.load(deps.storage)?),
     }
 }
 
 pub mod execute {
     use super::*;
     use cosmwasm_std::Order;
-    use lavs_apis::interfaces::voting::VoterInfo;
+    use lavs_task_queue::msg::Status;
+    use serde_json::json;
+    use std::collections::BTreeMap;
 
-    pub fn submit_vote(
-        deps: DepsMut,
+    pub fn commit_vote(
+        mut deps: DepsMut,
         env: Env,
         info: MessageInfo,
+        task_queue: String,
         task_id: TaskId,
-        operator: String,
-        result: Decimal,
+        hash: HexBinary,
     ) -> Result<Response, ContractError> {
         let config = CONFIG.load(deps.storage)?;
-        let operator_addr = deps.api.addr_validate(&operator)?;
+        if config.paused {
+            return Err(ContractError::Paused);
+        }
+        let task = track_task(deps.branch(), &env, &config, &task_queue, task_id.clone())?;
+        if env.block.time.seconds() >= task.commit_ends {
+            return Err(ContractError::CommitPhaseClosed);
+        }
 
-        // Verify that the sender is a registered operator by querying the Mock Operators contract
-        let voting_power_query = VotingPowerResponse {};
-        let voting_power: VotingPowerResponse = deps.querier.query_wasm_smart(
-            &config.operator_contract,
-            &QueryMsg::VotingPowerAtHeight {
-                address: operator.clone(),
-                height: Some(env.block.height),
+        // Verify that the sender is a registered operator by querying the Mock Operators contract.
+        // Power is measured at the task's creation height, not the current block
+        let operator = info.sender;
+        let voting_power = voting_power_at(deps.as_ref(), &config, &operator, task.created_height)?;
+        if voting_power.is_zero() {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        // Check if the operator has already committed to this task
+        if COMMITS.has(deps.storage, (task_id.clone(), operator.clone())) {
+            return Err(ContractError::OperatorAlreadyVoted(operator.to_string()));
+        }
+        COMMITS.save(deps.storage, (task_id.clone(), operator.clone()), &hash)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "commit_vote")
+            .add_attribute("operator", operator)
+            .add_attribute("task_id", task_id.to_string())
+            .add_attribute("hash", hash.to_hex()))
+    }
+
+    pub fn reveal_vote(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        task_id: TaskId,
+        result: VoteResult,
+        salt: String,
+    ) -> Result<Response, ContractError> {
+        let task = TASKS
+            .may_load(deps.storage, task_id.clone())?
+            .ok_or(ContractError::NoCommit)?;
+        let now = env.block.time.seconds();
+        if now < task.commit_ends {
+            return Err(ContractError::RevealPhaseNotStarted);
+        }
+        if now >= task.reveal_ends {
+            return Err(ContractError::RevealPhaseClosed);
+        }
+
+        // Numeric tasks take numbers, categorical tasks take one of their options or "invalid".
+        // Scalars have to stay in their range, which may include zero, other numbers are prices
+        let range = task.kind.as_ref().and_then(TaskPayload::range);
+        match &result {
+            VoteResult::Numeric(_) if task.is_categorical() => return Err(ContractError::VoteKindMismatch),
+            VoteResult::Numeric(value) => match range {
+                Some((min, max)) if value < &min || value > &max => return Err(ContractError::OutOfRange(min, max)),
+                None if value.is_zero() => return Err(ContractError::ZeroPrice),
+                _ => {}
             },
-        )?;
+            VoteResult::Categorical(_) if !task.is_categorical() => return Err(ContractError::VoteKindMismatch),
+            VoteResult::Categorical(option) if option != INVALID_OPTION && !task.options.contains(option) => {
+                return Err(ContractError::UnknownOption(option.clone()))
+            }
+            _ => {}
+        }
 
-        if voting_power.power.is_zero() {
-            return Err(ContractError::Unauthorized {});
+        // The revealed vote must match what the operator committed to
+        let operator = info.sender;
+        let hash = COMMITS
+            .may_load(deps.storage, (task_id.clone(), operator.clone()))?
+            .ok_or(ContractError::NoCommit)?;
+        if hash != vote_hash(&operator, task_id.clone(), &result, &salt)? {
+            return Err(ContractError::CommitMismatch);
         }
 
-        // Check if the operator has already voted for this task
-        if VOTES.may_load(deps.storage, (task_id.clone(), operator_addr.clone()))?.is_some() {
-            return Err(ContractError::OperatorAlreadyVoted(operator.clone()));
+        // Check if the operator has already revealed for this task
+        if VOTES.has(deps.storage, (task_id.clone(), operator.clone())) {
+            return Err(ContractError::OperatorAlreadyVoted(operator.to_string()));
         }
 
         // Record the vote
         let vote = OperatorVote { result };
-        VOTES.save(deps.storage, (task_id.clone(), operator_addr.clone()), &vote)?;
+        VOTES.save(deps.storage, (task_id.clone(), operator.clone()), &vote)?;
 
         Ok(Response::new()
-            .add_attribute("action", "submit_vote")
+            .add_attribute("action", "reveal_vote")
             .add_attribute("operator", operator)
             .add_attribute("task_id", task_id.to_string())
-            .add_attribute("result", result.to_string()))
+            .add_attribute("result", vote.result.to_string()))
     }
 
     pub fn process_votes(
         deps: DepsMut,
         env: Env,
-        info: MessageInfo,
+        _info: MessageInfo,
         task_id: TaskId,
     ) -> Result<Response, ContractError> {
         let config = CONFIG.load(deps.storage)?;
+        // Reveals stay open while paused, so operators who committed are not slashed
+        // for a reveal window they could not use. Nothing is decided until we unpause
+        if config.paused {
+            return Err(ContractError::Paused);
+        }
+        let task = TASKS
+            .may_load(deps.storage, task_id.clone())?
+            .ok_or(ContractError::VoteProcessingFailed)?;
 
-        // Ensure that the caller is the Mock Operators contract
-        if info.sender != config.operator_contract {
-            return Err(ContractError::Unauthorized {});
+        // Wait for every operator to have had the chance to reveal
+        if env.block.time.seconds() < task.reveal_ends {
+            return Err(ContractError::RevealPhaseOpen);
+        }
+
+        // Once the task is challenged, only the votes of the second round count
+        let metadata: TaskInfoResponse = deps
+            .querier
+            .query_wasm_smart(&task.task_queue, &TaskQueueQueryMsg::TaskInfo { task_id: task_id.clone() })?;
+        if metadata.challenge.is_some() && task.disputed.is_none() {
+            return Err(ContractError::StaleRound);
         }
 
+        // Only revealed votes count
+        let unrevealed = COMMITS
+            .prefix(task_id.clone())
+            .keys(deps.storage, None, None, Order::Ascending)
+            .filter(|operator| match operator {
+                Ok(operator) => !VOTES.has(deps.storage, (task_id.clone(), operator.clone())),
+                Err(_) => true,
+            })
+            .collect::<StdResult<Vec<Addr>>>()?;
+
         // Fetch all votes for the task
         let votes: Vec<(Addr, OperatorVote)> = VOTES
-            .range(deps.storage, (Bound::Inclusive(task_id.clone()),), None, Order::Ascending)
+            .prefix(task_id.clone())
+            .range(deps.storage, None, None, Order::Ascending)
             .collect::<StdResult<Vec<_>>>()?;
 
         if votes.is_empty() {
             return Err(ContractError::VoteProcessingFailed);
         }
 
-        // Calculate the median result
-        let mut results: Vec<Decimal> = votes.iter().map(|(_, vote)| vote.result).collect();
-        results.sort();
-        let median = results[results.len() / 2];
+        // Weight every vote by the operator's power when the task was created
+        let weighted = votes
+            .into_iter()
+            .map(|(operator, vote)| {
+                let power = voting_power_at(deps.as_ref(), &config, &operator, task.created_height)?;
+                Ok((operator, vote.result, power))
+            })
+            .collect::<StdResult<Vec<(Addr, VoteResult, Uint128)>>>()?;
+
+        let total_power: TotalPowerResponse = deps.querier.query_wasm_smart(
+            &config.operator_contract,
+            &QueryMsg::TotalPowerAtHeight {
+                height: Some(task.created_height),
+            },
+        )?;
 
-        // Identify slashed operators
-        let mut slashed = Vec::new();
-        for (operator, vote) in votes.iter() {
-            if (vote.result - median).abs() > config.allowed_spread {
-                slashed.push(operator.clone());
+        let mut threshold = if task.is_categorical() {
+            config.supermajority
+        } else {
+            config.threshold_percent
+        };
+        // The second round of a challenged task needs a larger share of the power
+        if task.disputed.is_some() {
+            threshold = threshold.max(config.challenge_threshold);
+        }
+        let tally = if task.is_categorical() {
+            tally_categorical(weighted, total_power.power, threshold)
+        } else {
+            tally_numeric(&config, weighted, total_power.power, threshold)
+        };
+        let tally = match tally {
+            // Nobody settles a failed second round but the arbitrator
+            Err(ContractError::ThresholdNotMet) if task.disputed.is_some() => {
+                return escalate(&task, task_id);
             }
+            tally => tally?,
+        };
+        let reported = match &tally.outcome {
+            VoteResult::Numeric(median) => json!({ "price": median.to_string() }),
+            VoteResult::Categorical(winner) => json!({ "winner": winner }),
+        };
+
+        // Slash the operators, including those who committed but never revealed
+        let slashed_count = tally.slashed.len();
+        let unrevealed_count = unrevealed.len();
+        let mut slash_msgs = Vec::with_capacity(slashed_count + unrevealed_count);
+        let unrevealed = unrevealed.into_iter().map(|operator| (operator, SlashReason::Unrevealed));
+        for (operator, reason) in tally.slashed.into_iter().chain(unrevealed) {
+            let record = SlashRecord {
+                task_id: task_id.clone(),
+                operator: operator.clone(),
+                fraction: config.slash_fraction,
+                reason,
+            };
+            SLASHES.save(deps.storage, (task_id.clone(), operator.clone()), &record)?;
+            slash_msgs.push(WasmMsg::Execute {
+                contract_addr: config.operator_contract.to_string(),
+                msg: to_json_binary(&OperatorsExecuteMsg::Slash {
+                    operator: operator.to_string(),
+                    fraction: config.slash_fraction,
+                })?,
+                funds: vec![],
+            });
         }
 
-        // Slash the operators
-        for operator in slashed.iter() {
-            SLASHED_OPERATORS.save(deps.storage, operator, &true)?;
-            // Additional slashing logic can be implemented here (e.g., deducting tokens)
+        // Share the task's fee among the operators who agreed with the outcome, by power.
+        // Rounding leftovers stay with the verifier, for the owner to withdraw. A challenger's
+        // bond is only ours if the second round confirms the challenged result
+        let rewarded_power: Uint128 = tally.rewarded.iter().map(|(_, power)| *power).sum();
+        let fee = match &task.disputed {
+            Some(disputed) if disputed != &reported => None,
+            _ => task.fee.as_ref(),
+        };
+        if let Some(fee) = fee.filter(|_| !rewarded_power.is_zero()) {
+            let mut credited = Uint128::zero();
+            for (operator, power) in tally.rewarded.iter() {
+                let reward = fee.amount.multiply_ratio(*power, rewarded_power);
+                if !reward.is_zero() {
+                    REWARDS.update(deps.storage, (operator, &fee.denom), |pending| -> StdResult<_> {
+                        Ok(pending.unwrap_or_default() + reward)
+                    })?;
+                    credited += reward;
+                }
+            }
+            ALLOCATED.update(deps.storage, &fee.denom, |allocated| -> StdResult<_> {
+                Ok(allocated.unwrap_or_default() + credited)
+            })?;
         }
 
-        // Determine if the threshold is met
-        let total_power = deps.querier.query_wasm_smart(
-            &config.operator_contract,
-            &QueryMsg::TotalPowerAtHeight {
-                height: Some(env.block.height),
+        // Report the outcome to the Task Queue
+        let task_msg = match &tally.outcome {
+            VoteResult::Numeric(_) => TaskExecuteMsg::CompleteTask {
+                task_id: task_id.clone(),
+                result: reported,
             },
-        )?;
-        let required_power = total_power.power * config.threshold_percent;
+            VoteResult::Categorical(winner) if winner == INVALID_OPTION => TaskExecuteMsg::InvalidateTask {
+                task_id: task_id.clone(),
+                reason: "Operators voted the task invalid".to_string(),
+            },
+            VoteResult::Categorical(winner) => TaskExecuteMsg::ResolveOutcome {
+                task_id: task_id.clone(),
+                winner: winner.clone(),
+            },
+        };
+        let mark_complete_msg = CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: task.task_queue.to_string(),
+            msg: to_json_binary(&task_msg)?,
+            funds: vec![],
+        });
+
+        let outcome_key = match tally.outcome {
+            VoteResult::Numeric(_) => "median",
+            VoteResult::Categorical(_) => "winner",
+        };
+        Ok(Response::new()
+            .add_message(mark_complete_msg)
+            .add_messages(slash_msgs)
+            .add_attribute("action", "process_votes")
+            .add_attribute("task_id", task_id.to_string())
+            .add_attribute(outcome_key, tally.outcome.to_string())
+            .add_attribute("aggregated_power", tally.aggregated_power.to_string())
+            .add_attribute("required_power", tally.required_power.to_string())
+            .add_attribute("slashed_count", slashed_count.to_string())
+            .add_attribute("unrevealed_count", unrevealed_count.to_string()))
+    }
+
+    fn escalate(task: &TaskInfo, task_id: TaskId) -> Result<Response, ContractError> {
+        let escalate_msg = WasmMsg::Execute {
+            contract_addr: task.task_queue.to_string(),
+            msg: to_json_binary(&TaskExecuteMsg::Escalate { task_id: task_id.clone() })?,
+            funds: vec![],
+        };
+        Ok(Response::new()
+            .add_message(escalate_msg)
+            .add_attribute("action", "process_votes")
+            .add_attribute("task_id", task_id.to_string())
+            .add_attribute("escalated", "true"))
+    }
+
+    /// The result of counting the votes on a task
+    struct Tally {
+        outcome: VoteResult,
+        /// Power that agreed with the outcome
+        aggregated_power: Uint128,
+        /// Power needed for the outcome to be accepted
+        required_power: Uint128,
+        /// Operators to slash for disagreeing with the outcome
+        slashed: Vec<(Addr, SlashReason)>,
+        /// Operators who share the task's fee, with their power
+        rewarded: Vec<(Addr, Uint128)>,
+    }
+
+    /// Takes the stake-weighted median and sums the power of everyone within the allowed spread.
+    /// Only results beyond the slashable spread are slashed
+    fn tally_numeric(
+        config: &Config,
+        weighted: Vec<(Addr, VoteResult, Uint128)>,
+        total_power: Uint128,
+        threshold: Decimal,
+    ) -> Result<Tally, ContractError> {
+        let weighted = weighted
+            .into_iter()
+            .map(|(operator, result, power)| match result {
+                VoteResult::Numeric(result) => Ok((operator, result, power)),
+                VoteResult::Categorical(_) => Err(ContractError::VoteKindMismatch),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let median = weighted_median(weighted.iter().map(|(_, result, power)| (*result, *power)).collect())
+            .ok_or(ContractError::VoteProcessingFailed)?;
+
+        let mut aggregated_power = Uint128::zero();
+        let mut slashed = Vec::new();
+        let mut rewarded = Vec::new();
+        for (operator, result, power) in weighted {
+            if is_within_spread(result, median, config.allowed_spread) {
+                aggregated_power += power;
+                rewarded.push((operator, power));
+            } else if !is_within_spread(result, median, config.slashable_spread) {
+                slashed.push((operator, SlashReason::OutsideSpread));
+            }
+        }
+
+        let required_power = total_power.mul_ceil(threshold);
+        if aggregated_power < required_power {
+            return Err(ContractError::ThresholdNotMet);
+        }
+
+        Ok(Tally {
+            outcome: VoteResult::Numeric(median),
+            aggregated_power,
+            required_power,
+            slashed,
+            rewarded,
+        })
+    }
+
+    /// Sums the power behind each option. The option with the most power wins if it
+    /// holds the given share of the total power
+    fn tally_categorical(
+        weighted: Vec<(Addr, VoteResult, Uint128)>,
+        total_power: Uint128,
+        threshold: Decimal,
+    ) -> Result<Tally, ContractError> {
+        let mut options: BTreeMap<String, TaskOption> = BTreeMap::new();
+        for (_, result, power) in weighted.iter() {
+            match result {
+                VoteResult::Categorical(option) => {
+                    options
+                        .entry(option.clone())
+                        .or_insert(TaskOption { power: Uint128::zero() })
+                        .power += *power;
+                }
+                VoteResult::Numeric(_) => return Err(ContractError::VoteKindMismatch),
+            }
+        }
 
-        // Calculate the aggregated voting power in favor
-        let aggregated_power: Uint128 = votes
+        let (winner, winner_power) = options
             .iter()
-            .filter(|(_, vote)| vote.result >= median)
-            .map(|(_, vote)| vote.result.into())
-            .sum();
-
-        let threshold_met = aggregated_power >= required_power;
-
-        // Optionally, interact with the Task Queue to mark the task as complete
-        if threshold_met {
-            let task_info = TASKS
-                .load(deps.storage, task_id.clone())
-                .map_err(|_| ContractError::VoteProcessingFailed)?;
-
-            // Mark task as complete by sending a message to Task Queue
-            let mark_complete_msg = CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: task_info.task.task_contract.clone(),
-                msg: to_binary(&TaskQueryMsg::MarkComplete { task_id: task_id.clone() })?,
-                funds: vec![],
-            });
+            .max_by_key(|(_, option)| option.power)
+            .map(|(winner, option)| (winner.clone(), option.power))
+            .ok_or(ContractError::VoteProcessingFailed)?;
+        // A tie has no winner
+        if options.values().filter(|option| option.power == winner_power).count() > 1 {
+            return Err(ContractError::ThresholdNotMet);
+        }
 
-            Ok(Response::new()
-                .add_message(mark_complete_msg)
-                .add_attribute("action", "process_votes")
-                .add_attribute("task_id", task_id.to_string())
-                .add_attribute("median", median.to_string())
-                .add_attribute("threshold_met", threshold_met.to_string()))
-        } else {
-            Err(ContractError::ThresholdNotMet)
+        let required_power = total_power.mul_ceil(threshold);
+        if winner_power < required_power {
+            return Err(ContractError::ThresholdNotMet);
         }
+
+        let (winners, losers): (Vec<_>, Vec<_>) = weighted
+            .into_iter()
+            .partition(|(_, result, _)| result == &VoteResult::Categorical(winner.clone()));
+        let slashed = losers
+            .into_iter()
+            .map(|(operator, _, _)| (operator, SlashReason::WrongOption))
+            .collect();
+        let rewarded = winners
+            .into_iter()
+            .map(|(operator, _, power)| (operator, power))
+            .collect();
+
+        Ok(Tally {
+            outcome: VoteResult::Categorical(winner),
+            aggregated_power: winner_power,
+            required_power,
+            slashed,
+            rewarded,
+        })
     }
 
-    pub fn slash_operators(
+    pub fn claim_rewards(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+        let rewards = REWARDS
+            .prefix(&info.sender)
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| item.map(|(denom, amount)| Coin { denom, amount }))
+            .collect::<StdResult<Vec<_>>>()?;
+        if rewards.is_empty() {
+            return Err(ContractError::NoRewards);
+        }
+        for reward in rewards.iter() {
+            REWARDS.remove(deps.storage, (&info.sender, &reward.denom));
+            ALLOCATED.update(deps.storage, &reward.denom, |allocated| -> StdResult<_> {
+                Ok(allocated.unwrap_or_default().checked_sub(reward.amount)?)
+            })?;
+        }
+
+        let claimed = rewards.iter().map(|reward| reward.to_string()).collect::<Vec<_>>().join(",");
+        Ok(Response::new()
+            .add_message(BankMsg::Send {
+                to_address: info.sender.to_string(),
+                amount: rewards,
+            })
+            .add_attribute("action", "claim_rewards")
+            .add_attribute("operator", info.sender)
+            .add_attribute("rewards", claimed))
+    }
+
+    pub fn withdraw_surplus(
         deps: DepsMut,
         env: Env,
         info: MessageInfo,
-        task_id: TaskId,
+        denom: String,
     ) -> Result<Response, ContractError> {
-        let config = CONFIG.load(deps.storage)?;
-
-        // Ensure that the caller is the Mock Operators contract
-        if info.sender != config.operator_contract {
-            return Err(ContractError::Unauthorized {});
+        let config = assert_owner(deps.as_ref(), &info)?;
+        let held = deps.querier.query_balance(&env.contract.address, &denom)?.amount;
+        let allocated = ALLOCATED.may_load(deps.storage, &denom)?.unwrap_or_default();
+        let surplus = held.saturating_sub(allocated);
+        if surplus.is_zero() {
+            return Err(ContractError::NoSurplus);
         }
 
-        // Fetch all slashed operators for the task
-        let slashed_operators: Vec<Addr> = SLASHED_OPERATORS
-            .range(deps.storage, None, None, Order::Ascending)
-            .filter_map(|item| {
-                let (addr, slashed) = item.ok()?;
-                if slashed {
-                    Some(addr)
-                } else {
-                    None
-                }
+        Ok(Response::new()
+            .add_message(BankMsg::Send {
+                to_address: config.owner.to_string(),
+                amount: vec![Coin::new(surplus, denom)],
             })
-            .collect();
+            .add_attribute("action", "withdraw_surplus")
+            .add_attribute("amount", surplus.to_string()))
+    }
 
-        // Implement slashing logic (e.g., deducting tokens)
-        for operator in slashed_operators.iter() {
-            // Example: Create a message to slash tokens from the operator
-            // This is a placeholder and should be replaced with actual slashing implementation
-            // let slash_msg = CosmosMsg::Custom(...);
-            // messages.push(slash_msg);
+    /// The new settings apply to every tally from now on, including tasks already being voted on.
+    /// The task queue can only be set once
+    pub fn update_config(
+        deps: DepsMut,
+        info: MessageInfo,
+        threshold_percent: Option<Decimal>,
+        allowed_spread: Option<Decimal>,
+        slashable_spread: Option<Decimal>,
+        supermajority: Option<Decimal>,
+        challenge_threshold: Option<Decimal>,
+        guardian: Option<String>,
+        task_queue: Option<String>,
+    ) -> Result<Response, ContractError> {
+        let mut config = assert_owner(deps.as_ref(), &info)?;
+        config.threshold_percent = threshold_percent.unwrap_or(config.threshold_percent);
+        config.allowed_spread = allowed_spread.unwrap_or(config.allowed_spread);
+        config.slashable_spread = slashable_spread.unwrap_or(config.slashable_spread);
+        config.supermajority = supermajority.unwrap_or(config.supermajority);
+        config.challenge_threshold = challenge_threshold.unwrap_or(config.challenge_threshold);
+        if let Some(guardian) = guardian {
+            config.guardian = Some(deps.api.addr_validate(&guardian)?);
         }
+        // tasks are tracked by id alone, which only stays unique as long as the queue does
+        if let Some(task_queue) = task_queue {
+            if let Some(current) = &config.task_queue {
+                return Err(ContractError::TaskQueueAlreadySet(current.to_string()));
+            }
+            config.task_queue = Some(deps.api.addr_validate(&task_queue)?);
+        }
+        config.validate()?;
+        CONFIG.save(deps.storage, &config)?;
+
+        Ok(Response::new().add_attribute("action", "update_config"))
+    }
+
+    /// Only proposes the new owner, who has to accept. The current owner can replace
+    /// the proposal until then
+    pub fn transfer_ownership(
+        deps: DepsMut,
+        info: MessageInfo,
+        owner: String,
+    ) -> Result<Response, ContractError> {
+        let mut config = assert_owner(deps.as_ref(), &info)?;
+        let pending = deps.api.addr_validate(&owner)?;
+        config.pending_owner = Some(pending.clone());
+        CONFIG.save(deps.storage, &config)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "transfer_ownership")
+            .add_attribute("pending_owner", pending))
+    }
 
-        // Clear the slashed operators after slashing
-        for operator in slashed_operators.iter() {
-            SLASHED_OPERATORS.remove(deps.storage, operator);
+    pub fn accept_ownership(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+        let mut config = CONFIG.load(deps.storage)?;
+        if config.pending_owner.as_ref() != Some(&info.sender) {
+            return Err(ContractError::Unauthorized);
         }
+        config.owner = info.sender;
+        config.pending_owner = None;
+        CONFIG.save(deps.storage, &config)?;
 
-        // Optionally, interact with the Task Queue to mark the task as complete
-        let mark_complete_msg = CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: "task-queue-address".to_string(), // Replace with actual Task Queue address
-            msg: to_binary(&TaskQueryMsg::MarkComplete { task_id: task_id.clone() })?,
-            funds: vec![],
-        });
+        Ok(Response::new()
+            .add_attribute("action", "accept_ownership")
+            .add_attribute("owner", config.owner))
+    }
+
+    pub fn set_paused(deps: DepsMut, info: MessageInfo, paused: bool) -> Result<Response, ContractError> {
+        let mut config = CONFIG.load(deps.storage)?;
+        if info.sender != config.owner && config.guardian.as_ref() != Some(&info.sender) {
+            return Err(ContractError::Unauthorized);
+        }
+        config.paused = paused;
+        CONFIG.save(deps.storage, &config)?;
 
         Ok(Response::new()
-            .add_message(mark_complete_msg)
-            .add_attribute("action", "slash_operators")
-            .add_attribute("task_id", task_id.to_string())
-            .add_attribute("slashed_count", slashed_operators.len().to_string()))
+            .add_attribute("action", if paused { "pause" } else { "unpause" })
+            .add_attribute("sender", info.sender))
+    }
+
+    fn assert_owner(deps: Deps, info: &MessageInfo) -> Result<Config, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+        if info.sender != config.owner {
+            return Err(ContractError::Unauthorized);
+        }
+        Ok(config)
+    }
+
+    /// Loads the task from the trusted task queue and ensures it can still be voted on.
+    /// The first vote remembers the task queue and creation height, so later votes
+    /// and the final tally all refer to the same task. The first vote after a challenge
+    /// starts the second round, dropping the votes of the first.
+    fn track_task(
+        deps: DepsMut,
+        env: &Env,
+        config: &Config,
+        task_queue: &str,
+        task_id: TaskId,
+    ) -> Result<TaskInfo, ContractError> {
+        let task_queue = deps.api.addr_validate(task_queue)?;
+        if config.task_queue.as_ref() != Some(&task_queue) {
+            return Err(ContractError::UntrustedTaskQueue(task_queue.to_string()));
+        }
+        let known = TASKS.may_load(deps.storage, task_id.clone())?;
+
+        let metadata: TaskInfoResponse = deps
+            .querier
+            .query_wasm_smart(&task_queue, &TaskQueueQueryMsg::TaskInfo { task_id: task_id.clone() })?;
+        match metadata.status {
+            Status::Open {} if metadata.timing.expires_at > env.block.time.seconds() => {}
+            Status::Open {} | Status::Expired {} => return Err(ContractError::TaskExpired),
+            Status::Pending { .. } => return Err(ContractError::TaskPending),
+            Status::Arbitration { .. } => return Err(ContractError::TaskInArbitration),
+            Status::Completed { .. } => return Err(ContractError::TaskAlreadyCompleted),
+            Status::Invalid { .. } => return Err(ContractError::TaskInvalid),
+            Status::Cancelled {} => return Err(ContractError::TaskCancelled),
+        }
+        if let Some(close_time) = metadata.kind.as_ref().and_then(TaskPayload::close_time) {
+            if env.block.time.seconds() < close_time {
+                return Err(ContractError::EventNotClosed(close_time));
+            }
+        }
+
+        match (known, metadata.challenge.is_some()) {
+            (Some(known), true) if known.disputed.is_none() => {
+                let operators = COMMITS
+                    .prefix(task_id.clone())
+                    .keys(deps.storage, None, None, Order::Ascending)
+                    .collect::<StdResult<Vec<Addr>>>()?;
+                for operator in operators {
+                    COMMITS.remove(deps.storage, (task_id.clone(), operator.clone()));
+                    VOTES.remove(deps.storage, (task_id.clone(), operator));
+                }
+                start_round(deps, config, task_queue, task_id, metadata)
+            }
+            (Some(known), _) => Ok(known),
+            (None, _) => start_round(deps, config, task_queue, task_id, metadata),
+        }
+    }
+
+    /// The commit and reveal windows are carved out of the time the task has left,
+    /// leaving the rest for processing before the task expires. For events that is the time
+    /// left once the event closed
+    fn start_round(
+        deps: DepsMut,
+        config: &Config,
+        task_queue: Addr,
+        task_id: TaskId,
+        metadata: TaskInfoResponse,
+    ) -> Result<TaskInfo, ContractError> {
+        let (started, fee, disputed) = match metadata.challenge {
+            Some(challenge) => (challenge.challenged_at, Some(challenge.bond), Some(challenge.disputed)),
+            None => {
+                let close_time = metadata.kind.as_ref().and_then(TaskPayload::close_time);
+                (metadata.timing.created_at.max(close_time.unwrap_or_default()), metadata.payment, None)
+            }
+        };
+        let timeout = Uint128::from(metadata.timing.expires_at - started);
+        let commit_ends = started + timeout.mul_floor(config.commit_window).u128() as u64;
+        let reveal_ends = commit_ends + timeout.mul_floor(config.reveal_window).u128() as u64;
+        let task = TaskInfo {
+            task_queue,
+            created_height: metadata.timing.created_height,
+            options: metadata.options,
+            commit_ends,
+            reveal_ends,
+            fee,
+            disputed,
+            kind: metadata.kind,
+        };
+        TASKS.save(deps.storage, task_id, &task)?;
+        Ok(task)
+    }
+
+    fn voting_power_at(
+        deps: Deps,
+        config: &Config,
+        operator: &Addr,
+        height: u64,
+    ) -> StdResult<Uint128> {
+        let res: VotingPowerResponse = deps.querier.query_wasm_smart(
+            &config.operator_contract,
+            &QueryMsg::VotingPowerAtHeight {
+                address: operator.to_string(),
+                height: Some(height),
+            },
+        )?;
+        Ok(res.power)
+    }
+
+    /// Stake-weighted median: the lowest result at which the voters at or below it
+    /// hold at least half of the power that voted.
+    pub(crate) fn weighted_median(mut votes: Vec<(Decimal, Uint128)>) -> Option<Decimal> {
+        votes.sort_by(|a, b| a.0.cmp(&b.0));
+        let total: Uint128 = votes.iter().map(|(_, power)| *power).sum();
+        if total.is_zero() {
+            return None;
+        }
+
+        let mut cumulative = Uint128::zero();
+        for (result, power) in votes {
+            cumulative += power;
+            if cumulative + cumulative >= total {
+                return Some(result);
+            }
+        }
+        None
+    }
+
+    /// Spreads are relative to the median, so 10% allows 90-110 around a median of 100
+    pub(crate) fn is_within_spread(result: Decimal, median: Decimal, spread: Decimal) -> bool {
+        result.abs_diff(median) <= median * spread
     }
 }
 
 mod query {
     use super::*;
+    use cw_storage_plus::Bound;
 
     pub fn voting_power(
         deps: Deps,
-        env: Env,
+        _env: Env,
         address: String,
         height: Option<u64>,
     ) -> StdResult<VotingPowerResponse> {
-        let height = height.unwrap_or(env.block.height);
         let config = CONFIG.load(deps.storage)?;
-        let query_msg = QueryMsg::VotingPowerAtHeight { address: address.clone(), height: Some(height) };
+        let query_msg = QueryMsg::VotingPowerAtHeight { address, height };
         let res: VotingPowerResponse = deps.querier.query_wasm_smart(&config.operator_contract, &query_msg)?;
         Ok(res)
     }
 
     pub fn total_power(
         deps: Deps,
-        env: Env,
+        _env: Env,
         height: Option<u64>,
     ) -> StdResult<TotalPowerResponse> {
-        let height = height.unwrap_or(env.block.height);
         let config = CONFIG.load(deps.storage)?;
-        let query_msg = QueryMsg::TotalPowerAtHeight { height: Some(height) };
+        let query_msg = QueryMsg::TotalPowerAtHeight { height };
         let res: TotalPowerResponse = deps.querier.query_wasm_smart(&config.operator_contract, &query_msg)?;
         Ok(res)
     }
@@ -278,13 +887,68 @@ mod query {
 
     pub fn task_info(
         deps: Deps,
-        env: Env,
+        _env: Env,
         task_contract: String,
         task_id: TaskId,
-    ) -> Result<TaskResponse, ContractError> {
+    ) -> StdResult<TaskResponse> {
         let task_contract_addr = deps.api.addr_validate(&task_contract)?;
-        let query_msg = TaskQueryMsg::Task { id: task_id.clone() };
-        let res: TaskMetadata = deps.querier.query_wasm_smart(&task_contract_addr, &query_msg)?;
+        let query_msg = TaskQueueQueryMsg::TaskInfo { task_id };
+        let res: TaskInfoResponse = deps.querier.query_wasm_smart(&task_contract_addr, &query_msg)?;
         Ok(TaskResponse { task: res })
     }
+
+    pub fn pending_rewards(deps: Deps, operator: String) -> StdResult<PendingRewardsResponse> {
+        let operator = deps.api.addr_validate(&operator)?;
+        let rewards = REWARDS
+            .prefix(&operator)
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| item.map(|(denom, amount)| Coin { denom, amount }))
+            .collect::<StdResult<Vec<_>>>()?;
+        Ok(PendingRewardsResponse { rewards })
+    }
+
+    /// Counts commits, revealed or not, of the current round. Tasks of another task queue have none
+    pub fn vote_count(deps: Deps, task_contract: String, task_id: TaskId) -> StdResult<VoteCountResponse> {
+        let task_contract = deps.api.addr_validate(&task_contract)?;
+        let tracked = TASKS
+            .may_load(deps.storage, task_id.clone())?
+            .is_some_and(|task| task.task_queue == task_contract);
+        if !tracked {
+            return Ok(VoteCountResponse { count: 0 });
+        }
+        let count = COMMITS
+            .prefix(task_id)
+            .keys(deps.storage, None, None, Order::Ascending)
+            .count();
+        Ok(VoteCountResponse { count: count as u32 })
+    }
+
+    pub fn slashes_by_task(deps: Deps, task_id: TaskId) -> StdResult<SlashesResponse> {
+        let slashes = SLASHES
+            .prefix(task_id)
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| item.map(|(_, record)| record))
+            .collect::<StdResult<Vec<_>>>()?;
+        Ok(SlashesResponse { slashes })
+    }
+
+    pub fn slashes_by_operator(
+        deps: Deps,
+        operator: String,
+        start_after: Option<TaskId>,
+        limit: Option<u32>,
+    ) -> StdResult<SlashesResponse> {
+        let operator = deps.api.addr_validate(&operator)?;
+        let limit = limit.unwrap_or(10) as usize;
+        let start = start_after.map(|task_id| Bound::exclusive((task_id, operator.clone())));
+        let slashes = SLASHES
+            .idx
+            .operator
+            .prefix(operator)
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| item.map(|(_, record)| record))
+            .collect::<StdResult<Vec<_>>>()?;
+        Ok(SlashesResponse { slashes })
+    }
 }
@@ -24,6 +24,9 @@ pub enum ContractError {
     #[error("Zero price submitted")]
     ZeroPrice,
 
+    #[error("Result must be between {0} and {1}")]
+    OutOfRange(Decimal, Decimal),
+
     #[error("Operator tried to vote twice: {0}")]
     OperatorAlreadyVoted(String),
 
@@ -33,9 +36,72 @@ pub enum ContractError {
     #[error("Task expired. Cannot vote on it")]
     TaskExpired,
 
+    #[error("Task result is pending its dispute window. Cannot vote on it")]
+    TaskPending,
+
+    #[error("Task was challenged, the votes of its first round no longer count")]
+    StaleRound,
+
+    #[error("Task is waiting for the arbitrator's ruling. Cannot vote on it")]
+    TaskInArbitration,
+
+    #[error("Task was marked invalid. Cannot vote on it")]
+    TaskInvalid,
+
+    #[error("Task was cancelled. Cannot vote on it")]
+    TaskCancelled,
+
+    #[error("The task queue is already set to {0} and cannot be changed")]
+    TaskQueueAlreadySet(String),
+
+    #[error("Not the task queue this verifier serves: {0}")]
+    UntrustedTaskQueue(String),
+
+    #[error("Vote does not match the task kind, numeric tasks take a number and categorical tasks an option")]
+    VoteKindMismatch,
+
+    #[error("Not one of the task options: {0}")]
+    UnknownOption(String),
+
+    #[error("Supermajority must be greater than 0 and at most 1, got {0}")]
+    InvalidSupermajority(Decimal),
+
+    #[error("Invalid voting windows. Commit: {0}. Reveal: {1}. Both must be positive and sum to less than 1")]
+    InvalidVotingWindows(Decimal, Decimal),
+
+    #[error("Commit phase is over for this task")]
+    CommitPhaseClosed,
+
+    #[error("Votes cannot be revealed before the commit phase ends")]
+    RevealPhaseNotStarted,
+
+    #[error("Reveal phase is over for this task")]
+    RevealPhaseClosed,
+
+    #[error("Votes cannot be processed before the reveal phase ends")]
+    RevealPhaseOpen,
+
+    #[error("No vote committed for this task")]
+    NoCommit,
+
+    #[error("Revealed vote does not match the commitment")]
+    CommitMismatch,
+
+    #[error("No rewards to claim")]
+    NoRewards,
+
+    #[error("Nothing held beyond the operators' rewards")]
+    NoSurplus,
+
     #[error("Invalid spread configuration. Slashable: {0}. Allowed: {1}.")]
     InvalidSpread(Decimal, Decimal),
 
+    #[error("Slash fraction must be greater than 0 and at most 1, got {0}")]
+    InvalidSlashFraction(Decimal),
+
+    #[error("Challenge threshold must be greater than 0 and at most 1, got {0}")]
+    InvalidChallengeThreshold(Decimal),
+
     #[error("{0}")]
     Verifier(#[from] VerifierError),
 
@@ -50,4 +116,28 @@ pub enum ContractError {
 
     #[error("Slashing Failed")]
     SlashingFailed,
+
+    #[error("Voting is paused")]
+    Paused,
+
+    #[error("The event only closes at {0}. Cannot vote on it before")]
+    EventNotClosed(u64),
+
+    #[error("Cannot migrate from a different contract: {0}")]
+    WrongContract(String),
+
+    #[error("Cannot migrate from {from} down to {to}")]
+    CannotDowngrade { from: String, to: String },
+
+    #[error("Migrating from {0} needs the other MigrateMsg variant")]
+    WrongMigrateMsg(String),
+
+    #[error("Invalid version: {0}")]
+    SemVer(String),
+}
+
+impl From<semver::Error> for ContractError {
+    fn from(err: semver::Error) -> Self {
+        ContractError::SemVer(err.to_string())
+    }
 }
\ No newline at end of file
@@ -0,0 +1,75 @@
+//! State upgrades from earlier releases. Each step reads the state as the older version
+//! stored it and rewrites it in the current layout.
+
+use cosmwasm_std::{Addr, Decimal, Storage};
+
+use crate::error::ContractError;
+use crate::state::{Config, CONFIG};
+
+/// The state of 1.x releases
+pub mod v1 {
+    use cosmwasm_schema::cw_serde;
+    use cosmwasm_std::{Addr, Decimal};
+    use cw_storage_plus::{Item, Map};
+    use lavs_apis::id::TaskId;
+    use lavs_apis::interfaces::tasks::TaskMetadata;
+
+    pub const CONFIG: Item<Config> = Item::new("config");
+    pub const VOTES: Map<(TaskId, Addr), OperatorVote> = Map::new("operator_votes");
+    pub const TASKS: Map<TaskId, TaskMetadata> = Map::new("tasks");
+    pub const SLASHED_OPERATORS: Map<Addr, bool> = Map::new("slashed_operators");
+
+    #[cw_serde]
+    pub struct Config {
+        pub threshold_percent: Decimal,
+        pub allowed_spread: Decimal,
+        pub slashable_spread: Decimal,
+        pub operator_contract: Addr,
+    }
+
+    #[cw_serde]
+    pub struct OperatorVote {
+        pub result: Decimal,
+    }
+}
+
+/// The settings 2.0 added, see `MigrateMsg::FromV1`
+pub struct V2Settings {
+    pub slash_fraction: Decimal,
+    pub supermajority: Decimal,
+    pub commit_window: Decimal,
+    pub reveal_window: Decimal,
+    pub challenge_threshold: Decimal,
+}
+
+/// Adds the new settings to the config and drops the rounds in flight.
+/// 1.x counted plain votes as they came in, which cannot carry over into commit-reveal rounds,
+/// and did not record which task queue a task came from. Operators vote on open tasks again,
+/// which tracks them anew. The slash flags go too: they took no power away and name no task.
+/// `owner` takes over the role nobody had in 1.x, and has to pick the task queue to serve
+pub fn v1_to_v2(storage: &mut dyn Storage, settings: V2Settings, owner: Addr) -> Result<(), ContractError> {
+    let old = v1::CONFIG.load(storage)?;
+    let config = Config {
+        threshold_percent: old.threshold_percent,
+        allowed_spread: old.allowed_spread,
+        slashable_spread: old.slashable_spread,
+        slash_fraction: settings.slash_fraction,
+        supermajority: settings.supermajority,
+        commit_window: settings.commit_window,
+        reveal_window: settings.reveal_window,
+        challenge_threshold: settings.challenge_threshold,
+        operator_contract: old.operator_contract,
+        task_queue: None,
+        owner,
+        pending_owner: None,
+        guardian: None,
+        paused: false,
+    };
+    config.validate()?;
+    CONFIG.save(storage, &config)?;
+
+    v1::TASKS.clear(storage);
+    v1::VOTES.clear(storage);
+    v1::SLASHED_OPERATORS.clear(storage);
+    Ok(())
+}
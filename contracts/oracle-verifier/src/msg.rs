@@ -1,52 +1,184 @@
-use cosmwasm_schema::cw_serde;
+use cosmwasm_schema::{cw_serde, QueryResponses};
 use lavs_apis::interfaces::tasks::TaskId;
-use lavs_apis::interfaces::voting::VotingPower;
-use cosmwasm_std::Decimal;
+use lavs_apis::verifier_simple::{AllVotersResponse, TotalPowerResponse, VotingPowerResponse};
+use cosmwasm_std::{to_json_vec, Addr, Coin, Decimal, HexBinary, StdResult};
+use lavs_task_queue::msg::VoteCountResponse;
+use sha2::{Digest, Sha256};
+
+pub use crate::state::{Config, SlashReason, SlashRecord, TaskResponse, VoteResult};
 
 #[cw_serde]
 pub struct InstantiateMsg {
     pub threshold_percent: Decimal,
     pub allowed_spread: Decimal,
+    /// Numeric results further than this from the median are slashed. Must be at least `allowed_spread`
     pub slashable_spread: Decimal,
+    /// Share of an operator's power taken away for each slash
+    pub slash_fraction: Decimal,
+    /// Share of the total power the winning option needs on categorical tasks
+    pub supermajority: Decimal,
+    /// Share of each task's timeout reserved for committing votes
+    pub commit_window: Decimal,
+    /// Share of each task's timeout reserved for revealing votes, after the commit window.
+    /// Both windows together must leave time to process the votes before the task expires
+    pub reveal_window: Decimal,
+    /// Share of the total power the second round of a challenged task needs, on either kind
+    /// of task. Applies if it is higher than the usual threshold
+    pub challenge_threshold: Decimal,
     pub operator_contract: String, // Address of the Mock Operators contract
 }
 
+#[cw_serde]
+pub enum MigrateMsg {
+    /// From 1.x, which had none of these settings yet. They mean the same as in `InstantiateMsg`
+    FromV1 {
+        slash_fraction: Decimal,
+        supermajority: Decimal,
+        commit_window: Decimal,
+        reveal_window: Decimal,
+        challenge_threshold: Decimal,
+    },
+    /// From any 2.x release
+    Upgrade {},
+}
+
 #[cw_serde]
 #[derive(cw_orch::ExecuteFns)]
 #[cw_orch(disable_fields_sorting)]
 pub enum ExecuteMsg {
-    /// Allows the Oracle Verifier to process votes for a specific task
+    /// Tallies the votes for a task by stake and reports the weighted median
+    /// to the task queue if enough power agrees. Challenged tasks are voted on again,
+    /// and that second round has to clear the challenge threshold, or the task is
+    /// escalated to the task queue's arbitrator. Operators beyond the slashable
+    /// spread, on a losing option or who never revealed are slashed. The task's fee is
+    /// shared by power among the operators within the allowed spread or on the winning
+    /// option. Anyone may call this
     ProcessVotes {
         task_id: TaskId,
     },
-    /// Slash operators who have deviated from the consensus
-    SlashOperators {
+    /// Commits to a vote without revealing it, see [`vote_hash`].
+    /// The sender must have voting power in the Mock Operators contract at the
+    /// task's creation height, and the task must be in its commit window
+    CommitVote {
+        /// Address of the task queue the task belongs to. Must be the configured one
+        task_queue: String,
         task_id: TaskId,
+        hash: HexBinary,
     },
-    /// Receives a vote from an operator
-    SubmitVote {
+    /// Reveals a committed vote during the reveal window. Numeric tasks take a
+    /// number, categorical tasks one of their options
+    RevealVote {
         task_id: TaskId,
-        operator: String,
-        result: Decimal,
+        result: VoteResult,
+        salt: String,
+    },
+    /// Sends the sender all rewards it earned from task fees
+    ClaimRewards {},
+    /// Sends the owner what the verifier holds of `denom` beyond the operators' rewards, e.g.
+    /// rounding leftovers or a forfeited bond of a task the arbitrator settled. Only callable by the owner
+    WithdrawSurplus {
+        denom: String,
     },
+    /// Replaces the given thresholds, spreads, guardian and task queue, leaving the others
+    /// as they are. Only callable by the owner
+    UpdateConfig {
+        threshold_percent: Option<Decimal>,
+        allowed_spread: Option<Decimal>,
+        slashable_spread: Option<Decimal>,
+        supermajority: Option<Decimal>,
+        challenge_threshold: Option<Decimal>,
+        guardian: Option<String>,
+        /// The task queue to vote on. Operators cannot vote on any task before it is set,
+        /// and it cannot be changed afterwards, as tasks are tracked by their id alone
+        task_queue: Option<String>,
+    },
+    /// Proposes a new owner, who takes over once it accepts. Only callable by the owner
+    TransferOwnership {
+        owner: String,
+    },
+    /// Makes the proposed owner the owner. Only callable by the proposed owner
+    AcceptOwnership {},
+    /// Stops committing and processing votes, so no task is resolved or operator slashed,
+    /// e.g. while a data source is compromised. Only callable by the guardian or the owner
+    Pause {},
+    /// Lets voting continue. Only callable by the guardian or the owner
+    Unpause {},
+}
+
+/// The hash an operator commits to: sha256 over the operator address, task id,
+/// JSON encoded result and salt. Binding the operator stops others from copying a commit
+pub fn vote_hash(
+    operator: &Addr,
+    task_id: TaskId,
+    result: &VoteResult,
+    salt: &str,
+) -> StdResult<HexBinary> {
+    let mut hasher = Sha256::new();
+    hasher.update(operator.as_bytes());
+    hasher.update(task_id.u64().to_be_bytes());
+    hasher.update(to_json_vec(result)?);
+    hasher.update(salt.as_bytes());
+    Ok(HexBinary::from(hasher.finalize().as_slice()))
 }
 
 #[cw_serde]
+#[derive(QueryResponses, cw_orch::QueryFns)]
+#[cw_orch(disable_fields_sorting)]
 pub enum QueryMsg {
     /// Query voting power of an operator at a specific height
+    #[returns(VotingPowerResponse)]
     VotingPowerAtHeight {
         address: String,
         height: Option<u64>,
     },
     /// Query total voting power at a specific height
+    #[returns(TotalPowerResponse)]
     TotalPowerAtHeight {
         height: Option<u64>,
     },
     /// Query all voters
+    #[returns(AllVotersResponse)]
     AllVoters {},
     /// Query task information
+    #[returns(TaskResponse)]
     TaskInfo {
         task_contract: String,
         task_id: TaskId,
     },
+    /// How many operators committed a vote on the task. The task queue checks this before cancelling
+    #[returns(VoteCountResponse)]
+    VoteCount {
+        task_contract: String,
+        task_id: TaskId,
+    },
+    /// All operators slashed when processing the task
+    #[returns(SlashesResponse)]
+    SlashesByTask {
+        task_id: TaskId,
+    },
+    /// All slashes of an operator, oldest task first
+    #[returns(SlashesResponse)]
+    SlashesByOperator {
+        operator: String,
+        start_after: Option<TaskId>,
+        limit: Option<u32>,
+    },
+    /// Rewards the operator can claim
+    #[returns(PendingRewardsResponse)]
+    PendingRewards {
+        operator: String,
+    },
+    /// The current settings, owner and proposed owner
+    #[returns(Config)]
+    Config {},
+}
+
+#[cw_serde]
+pub struct PendingRewardsResponse {
+    pub rewards: Vec<Coin>,
+}
+
+#[cw_serde]
+pub struct SlashesResponse {
+    pub slashes: Vec<SlashRecord>,
 }
\ No newline at end of file
@@ -1,25 +1,146 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Decimal, Uint128};
-use cw_storage_plus::{Item, Map};
+use cosmwasm_std::{Addr, Coin, Decimal, HexBinary, Uint128};
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex};
 use lavs_apis::id::TaskId;
-use lavs_apis::interfaces::tasks::TaskMetadata;
+use lavs_task_queue::msg::{ResponseType, TaskInfoResponse, TaskPayload};
+
+use crate::error::ContractError;
 
 pub const CONFIG: Item<Config> = Item::new("config");
+/// Hashed votes from the commit phase
+pub const COMMITS: Map<(TaskId, Addr), HexBinary> = Map::new("operator_commits");
+/// Votes revealed during the reveal phase. Only these are counted
 pub const VOTES: Map<(TaskId, Addr), OperatorVote> = Map::new("operator_votes");
-pub const TASKS: Map<TaskId, TaskMetadata> = Map::new("tasks");
-pub const SLASHED_OPERATORS: Map<Addr, bool> = Map::new("slashed_operators");
+pub const TASKS: Map<TaskId, TaskInfo> = Map::new("tasks");
+/// Rewards operators can claim, by operator and denom
+pub const REWARDS: Map<(&Addr, &str), Uint128> = Map::new("rewards");
+/// Sum of `REWARDS` by denom. Whatever the verifier holds beyond it belongs to no operator:
+/// rounding leftovers, and bonds or fees sent for tasks it did not resolve itself
+pub const ALLOCATED: Map<&str, Uint128> = Map::new("allocated_rewards");
+/// Every slash the verifier applied, by task and operator
+pub const SLASHES: IndexedMap<(TaskId, Addr), SlashRecord, SlashIndexes<'static>> = IndexedMap::new(
+    "slashes",
+    SlashIndexes {
+        operator: MultiIndex::new(|_, d: &SlashRecord| d.operator.clone(), "slashes", "slashes_operator"),
+    },
+);
+
+pub struct SlashIndexes<'a> {
+    pub operator: MultiIndex<'a, Addr, SlashRecord, (TaskId, Addr)>,
+}
+
+impl<'a> IndexList<SlashRecord> for SlashIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<SlashRecord>> + '_> {
+        Box::new(std::iter::once(&self.operator as &dyn Index<SlashRecord>))
+    }
+}
 
 #[cw_serde]
 pub struct Config {
     pub threshold_percent: Decimal,
     pub allowed_spread: Decimal,
+    /// Results further than this from the median are slashed.
+    /// Results between `allowed_spread` and this are neither counted nor slashed
     pub slashable_spread: Decimal,
+    /// Share of an operator's power taken away per slash
+    pub slash_fraction: Decimal,
+    /// Share of the total power the winning option needs in categorical mode
+    pub supermajority: Decimal,
+    /// Share of a task's timeout during which operators commit their hashed votes
+    pub commit_window: Decimal,
+    /// Share of a task's timeout, after the commit window, during which votes are revealed
+    pub reveal_window: Decimal,
+    /// Share of the total power the second round of a challenged task needs
+    pub challenge_threshold: Decimal,
     pub operator_contract: Addr,
+    /// The task queue whose tasks operators vote on. Set once by the owner with `UpdateConfig`,
+    /// as the task queue is instantiated with our address. Nothing is voted on until then.
+    /// Task ids are only unique per queue, and the fees we share out are only paid by a queue
+    /// that knows us, so no other queue's tasks are tracked
+    pub task_queue: Option<Addr>,
+    /// May update the thresholds and spreads, and hand over ownership
+    pub owner: Addr,
+    /// Proposed by the owner, takes over once it accepts
+    pub pending_owner: Option<Addr>,
+    /// May pause and unpause voting, next to the owner. Set by the owner with `UpdateConfig`
+    pub guardian: Option<Addr>,
+    /// No votes are committed or processed while set
+    pub paused: bool,
+}
+
+/// What the verifier remembers about a task once it received the first vote
+#[cw_serde]
+pub struct TaskInfo {
+    /// The task queue we report the result to
+    pub task_queue: Addr,
+    /// Block height the task was created at. All voting power is measured here
+    pub created_height: u64,
+    /// Options of a categorical task. Empty means operators vote on a number
+    pub options: Vec<String>,
+    /// UNIX seconds at which commits close and reveals open
+    pub commit_ends: u64,
+    /// UNIX seconds at which reveals close and the votes can be processed
+    pub reveal_ends: u64,
+    /// What the requestor paid. The task queue sends it to us on completion, to reward the operators.
+    /// In a second round, this is the challenger's bond, which we only get if the challenge fails
+    pub fee: Option<Coin>,
+    /// The challenged result, once the task is in its second round
+    pub disputed: Option<ResponseType>,
+    /// What kind of task the payload describes, if the task queue knows it
+    pub kind: Option<TaskPayload>,
+}
+
+impl Config {
+    pub fn validate(&self) -> Result<(), ContractError> {
+        if self.supermajority.is_zero() || self.supermajority > Decimal::one() {
+            return Err(ContractError::InvalidSupermajority(self.supermajority));
+        }
+        if self.commit_window.is_zero()
+            || self.reveal_window.is_zero()
+            || self.commit_window + self.reveal_window >= Decimal::one()
+        {
+            return Err(ContractError::InvalidVotingWindows(self.commit_window, self.reveal_window));
+        }
+        if self.slashable_spread < self.allowed_spread {
+            return Err(ContractError::InvalidSpread(self.slashable_spread, self.allowed_spread));
+        }
+        if self.slash_fraction.is_zero() || self.slash_fraction > Decimal::one() {
+            return Err(ContractError::InvalidSlashFraction(self.slash_fraction));
+        }
+        if self.challenge_threshold.is_zero() || self.challenge_threshold > Decimal::one() {
+            return Err(ContractError::InvalidChallengeThreshold(self.challenge_threshold));
+        }
+        Ok(())
+    }
+}
+
+impl TaskInfo {
+    pub fn is_categorical(&self) -> bool {
+        !self.options.is_empty()
+    }
 }
 
 #[cw_serde]
 pub struct OperatorVote {
-    pub result: Decimal,
+    pub result: VoteResult,
+}
+
+/// What an operator voted for
+#[cw_serde]
+pub enum VoteResult {
+    /// A numeric observation, aggregated by stake-weighted median
+    Numeric(Decimal),
+    /// One of the task's options, aggregated by power per option
+    Categorical(String),
+}
+
+impl std::fmt::Display for VoteResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VoteResult::Numeric(result) => write!(f, "{}", result),
+            VoteResult::Categorical(option) => write!(f, "{}", option),
+        }
+    }
 }
 
 #[cw_serde]
@@ -27,7 +148,36 @@ pub struct TaskOption {
     pub power: Uint128,
 }
 
+#[cw_serde]
+pub struct SlashRecord {
+    pub task_id: TaskId,
+    pub operator: Addr,
+    /// Share of the operator's power that was taken away
+    pub fraction: Decimal,
+    pub reason: SlashReason,
+}
+
+#[cw_serde]
+pub enum SlashReason {
+    /// A numeric result beyond the slashable spread
+    OutsideSpread,
+    /// A categorical vote for an option that lost
+    WrongOption,
+    /// Committed a vote but never revealed it
+    Unrevealed,
+}
+
+impl std::fmt::Display for SlashReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SlashReason::OutsideSpread => write!(f, "outside_spread"),
+            SlashReason::WrongOption => write!(f, "wrong_option"),
+            SlashReason::Unrevealed => write!(f, "unrevealed"),
+        }
+    }
+}
+
 #[cw_serde]
 pub struct TaskResponse {
-    pub task: TaskMetadata,
+    pub task: TaskInfoResponse,
 }
\ No newline at end of file
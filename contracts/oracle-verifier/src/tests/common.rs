@@ -1,27 +1,30 @@
-use cosmwasm_std::Decimal;
+use cosmwasm_std::{coin, coins, Decimal};
 use cw_orch::environment::{ChainState, CwEnv};
 use cw_orch::prelude::*;
 
 use lavs_apis::id::TaskId;
-use lavs_apis::tasks::{Requestor, Status, TimeoutInfo};
+use lavs_apis::tasks::{Requestor, TimeoutInfo};
 use lavs_orch::{Addressable, AltSigner};
 use serde_json::json;
 
 use lavs_task_queue::{
     interface::Contract as TasksContract,
     msg::{
-        CustomExecuteMsgFns as TasksExecuteMsgFns, CustomQueryMsgFns as TasksQueryMsgFns,
-        InstantiateMsg as TasksInstantiateMsg,
+        DisputeConfig, ExecuteMsgFns as TasksExecuteMsgFns, InstantiateMsg as TasksInstantiateMsg,
+        QueryMsgFns as TasksQueryMsgFns, Status, INVALID_OPTION,
     },
 };
 
 use lavs_mock_operators::interface::Contract as MockOperatorsContract;
 use lavs_mock_operators::msg::{
-    InstantiateMsg as MockOperatorsInstantiateMsg, InstantiateOperator,
+    ExecuteMsgFns as MockOperatorsExecuteMsgFns, InstantiateMsg as MockOperatorsInstantiateMsg,
+    InstantiateOperator,
 };
 
 use crate::interface::Contract;
-use crate::msg::{ExecuteMsgFns, InstantiateMsg, QueryMsgFns};
+use crate::msg::{vote_hash, ExecuteMsgFns, InstantiateMsg, QueryMsgFns, SlashReason, VoteResult};
+use crate::ContractError;
+use lavs_task_queue::ContractError as TasksContractError;
 
 pub const BECH_PREFIX: &str = "slay3r";
 
@@ -32,83 +35,226 @@ pub fn setup<Chain: CwEnv>(chain: Chain, msg: InstantiateMsg) -> Contract<Chain>
     contract
 }
 
+/// Instantiates the verifier and allows it to slash on the mock operators contract
+pub fn setup_verifier<Chain: CwEnv>(
+    chain: Chain,
+    mock_operators: &MockOperatorsContract<Chain>,
+    msg: InstantiateMsg,
+) -> Contract<Chain> {
+    let verifier = setup(chain, msg);
+    mock_operators
+        .set_oracle_verifier(verifier.addr_str().unwrap())
+        .unwrap();
+    verifier
+}
+
 pub fn happy_path<C>(chain: C)
 where
     C: CwEnv + AltSigner,
     C::Sender: Addressable,
 {
-    // verifier setup
     let operator1 = chain.alt_signer(3);
     let operator2 = chain.alt_signer(4);
     let operator3 = chain.alt_signer(5);
 
     let operators = vec![
-        InstantiateOperator {
-            addr: operator1.addr().to_string(),
-            voting_power: 50u32,
-        },
-        InstantiateOperator {
-            addr: operator2.addr().to_string(),
-            voting_power: 30u32,
-        },
-        InstantiateOperator {
-            addr: operator3.addr().to_string(),
-            voting_power: 20u32,
-        },
+        InstantiateOperator::new(operator1.addr().to_string(), 50),
+        InstantiateOperator::new(operator2.addr().to_string(), 30),
+        InstantiateOperator::new(operator3.addr().to_string(), 20),
     ];
     let mock_operators = setup_mock_operators(chain.clone(), operators);
 
-    let msg = InstantiateMsg {
-        operator_contract: mock_operators.addr_str().unwrap(),
-        // we want all our 3 operators to submit their votes
-        threshold_percentage: Decimal::one(),
-        allowed_spread: Decimal::percent(10),
-        slashable_spread: Decimal::percent(20),
-        required_percentage: 100,
-    };
-    let oracle_verifier = setup(chain.clone(), msg);
-
-    // instantiate task queue
-    let tasker = setup_task_queue(chain.clone(), &oracle_verifier.addr_str().unwrap());
+    // we want all our 3 operators to agree
+    let verifier = setup_verifier(chain.clone(), &mock_operators, instantiate_msg(&mock_operators, 100, 10, 20));
+    let tasker = setup_task_queue(chain.clone(), &verifier);
 
     let payload = json!({"action": "get_price"});
     let task_id = make_task(&tasker, "Get Price Task", None, &payload);
 
-    let result1 = r#"{"price": "100"}"#.to_string();
-    oracle_verifier
-        .call_as(&operator1)
-        .executed_task(tasker.addr_str().unwrap(), task_id, result1)
-        .unwrap();
-
-    let result2 = r#"{"price": "102"}"#.to_string();
-    oracle_verifier
-        .call_as(&operator2)
-        .executed_task(tasker.addr_str().unwrap(), task_id, result2)
-        .unwrap();
-
-    let result3 = r#"{"price": "98"}"#.to_string();
-    oracle_verifier
-        .call_as(&operator3)
-        .executed_task(tasker.addr_str().unwrap(), task_id, result3)
-        .unwrap();
+    let votes = [(&operator1, price(100)), (&operator2, price(102)), (&operator3, price(98))];
+    for (operator, result) in votes.iter() {
+        commit(&verifier, &tasker, operator, task_id, result);
+    }
+    chain.wait_seconds(COMMIT_PHASE).unwrap();
+    for (operator, result) in votes {
+        reveal(&verifier, operator, task_id, result);
+    }
+    chain.wait_seconds(REVEAL_PHASE).unwrap();
+    verifier.process_votes(task_id).unwrap();
 
-    let status = tasker.task(task_id).unwrap();
+    let status = tasker.task_info(task_id).unwrap();
     assert_eq!(
         status.status,
         Status::Completed {
             completed: chain.block_info().unwrap().time.seconds()
         }
     );
+    assert_eq!(status.result.unwrap(), json!({"price": "100"}));
+    assert!(verifier.slashes_by_task(task_id).unwrap().slashes.is_empty());
+}
+
+pub fn threshold_not_met<C>(chain: C)
+where
+    C: CwEnv + AltSigner,
+    C::Sender: Addressable,
+{
+    let operator1 = chain.alt_signer(3);
+    let operator2 = chain.alt_signer(4);
+    let operator3 = chain.alt_signer(5);
+
+    let operators = vec![
+        InstantiateOperator::new(operator1.addr().to_string(), 50),
+        InstantiateOperator::new(operator2.addr().to_string(), 30),
+        InstantiateOperator::new(operator3.addr().to_string(), 20),
+    ];
+    let mock_operators = setup_mock_operators(chain.clone(), operators);
 
-    let median_price = Decimal::percent(10000);
-    let task_result = status.result.unwrap();
-    assert_eq!(task_result, json!({"price": median_price.to_string()}));
+    let verifier = setup_verifier(chain.clone(), &mock_operators, instantiate_msg(&mock_operators, 90, 5, 10));
+    let tasker = setup_task_queue(chain.clone(), &verifier);
 
-    let slashed_operators: Vec<Addr> = oracle_verifier.slashable_operators().unwrap();
-    assert!(slashed_operators.is_empty());
+    let payload = json!({"action": "get_price"});
+    let task_id = make_task(&tasker, "Get Price Task", None, &payload);
+
+    let votes = [(&operator1, price(100)), (&operator2, price(210))];
+    for (operator, result) in votes.iter() {
+        commit(&verifier, &tasker, operator, task_id, result);
+    }
+    chain.wait_seconds(COMMIT_PHASE).unwrap();
+    for (operator, result) in votes {
+        reveal(&verifier, operator, task_id, result);
+    }
+    chain.wait_seconds(REVEAL_PHASE).unwrap();
+
+    let err = verifier.process_votes(task_id).unwrap_err();
+    assert!(
+        err.root()
+            .to_string()
+            .contains(&ContractError::ThresholdNotMet.to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+    let status = tasker.task_info(task_id).unwrap();
+    assert_eq!(status.status, Status::Open {});
 }
 
-pub fn threshold_not_met<C>(chain: C)
+pub fn weighted_median_ignores_low_power_outliers<C>(chain: C)
+where
+    C: CwEnv + AltSigner,
+    C::Sender: Addressable,
+{
+    let operator1 = chain.alt_signer(3);
+    let operator2 = chain.alt_signer(4);
+    let operator3 = chain.alt_signer(5);
+
+    let operators = vec![
+        InstantiateOperator::new(operator1.addr().to_string(), 60),
+        InstantiateOperator::new(operator2.addr().to_string(), 20),
+        InstantiateOperator::new(operator3.addr().to_string(), 20),
+    ];
+    let mock_operators = setup_mock_operators(chain.clone(), operators);
+
+    let verifier = setup_verifier(chain.clone(), &mock_operators, instantiate_msg(&mock_operators, 50, 10, 20));
+    let tasker = setup_task_queue(chain.clone(), &verifier);
+
+    let payload = json!({"action": "get_price"});
+    let task_id = make_task(&tasker, "Get Price Task", None, &payload);
+
+    // two low power operators agree on an extreme value, an unweighted median would pick it
+    let votes = [(&operator1, price(100)), (&operator2, price(500)), (&operator3, price(510))];
+    for (operator, result) in votes.iter() {
+        commit(&verifier, &tasker, operator, task_id, result);
+    }
+
+    // cannot commit twice
+    let err = verifier
+        .call_as(&operator1)
+        .commit_vote(tasker.addr_str().unwrap(), task_id, vote_hash(&operator1.addr(), task_id, &price(100), SALT).unwrap())
+        .unwrap_err();
+    assert!(
+        err.root()
+            .to_string()
+            .contains(&ContractError::OperatorAlreadyVoted(operator1.addr().to_string()).to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+
+    chain.wait_seconds(COMMIT_PHASE).unwrap();
+    for (operator, result) in votes {
+        reveal(&verifier, operator, task_id, result);
+    }
+    chain.wait_seconds(REVEAL_PHASE).unwrap();
+
+    // only 60 of the 100 power agrees, which is enough for a 50% threshold
+    let res = verifier.process_votes(task_id).unwrap();
+    assert_eq!(res.event_attr_value("wasm", "median").unwrap(), "100");
+    assert_eq!(res.event_attr_value("wasm", "aggregated_power").unwrap(), "60");
+    assert_eq!(res.event_attr_value("wasm", "slashed_count").unwrap(), "2");
+
+    let status = tasker.task_info(task_id).unwrap();
+    assert!(matches!(status.status, Status::Completed { .. }));
+    assert_eq!(status.result.unwrap(), json!({"price": "100"}));
+}
+
+pub fn threshold_counts_power_not_results<C>(chain: C)
+where
+    C: CwEnv + AltSigner,
+    C::Sender: Addressable,
+{
+    let operator1 = chain.alt_signer(3);
+    let operator2 = chain.alt_signer(4);
+    let operator3 = chain.alt_signer(5);
+
+    let operators = vec![
+        InstantiateOperator::new(operator1.addr().to_string(), 50),
+        InstantiateOperator::new(operator2.addr().to_string(), 30),
+        InstantiateOperator::new(operator3.addr().to_string(), 20),
+    ];
+    let mock_operators = setup_mock_operators(chain.clone(), operators);
+
+    let verifier = setup_verifier(chain.clone(), &mock_operators, instantiate_msg(&mock_operators, 60, 5, 10));
+    let tasker = setup_task_queue(chain.clone(), &verifier);
+
+    let payload = json!({"action": "get_price"});
+    let first = make_task(&tasker, "Get Price Task", None, &payload);
+    let second = make_task(&tasker, "Get Price Task", None, &payload);
+
+    // on the first task, the median is 100, but only 50 of 100 power is within the spread.
+    // on the second task, a third operator agreeing brings it to 70
+    let first_votes = [(&operator1, price(100)), (&operator2, price(200))];
+    let second_votes = [(&operator1, price(100)), (&operator2, price(200)), (&operator3, price(101))];
+    for (operator, result) in first_votes.iter() {
+        commit(&verifier, &tasker, operator, first, result);
+    }
+    for (operator, result) in second_votes.iter() {
+        commit(&verifier, &tasker, operator, second, result);
+    }
+    chain.wait_seconds(COMMIT_PHASE).unwrap();
+    for (operator, result) in first_votes {
+        reveal(&verifier, operator, first, result);
+    }
+    for (operator, result) in second_votes {
+        reveal(&verifier, operator, second, result);
+    }
+    chain.wait_seconds(REVEAL_PHASE).unwrap();
+
+    let err = verifier.process_votes(first).unwrap_err();
+    assert!(
+        err.root()
+            .to_string()
+            .contains(&ContractError::ThresholdNotMet.to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+    let status = tasker.task_info(first).unwrap();
+    assert_eq!(status.status, Status::Open {});
+
+    verifier.process_votes(second).unwrap();
+    let status = tasker.task_info(second).unwrap();
+    assert!(matches!(status.status, Status::Completed { .. }));
+    assert_eq!(status.result.unwrap(), json!({"price": "100"}));
+}
+
+pub fn categorical_votes_resolve_by_supermajority<C>(chain: C)
 where
     C: CwEnv + AltSigner,
     C::Sender: Addressable,
@@ -118,50 +264,980 @@ where
     let operator3 = chain.alt_signer(5);
 
     let operators = vec![
-        InstantiateOperator {
-            addr: operator1.addr().to_string(),
-            voting_power: 50u32,
-        },
-        InstantiateOperator {
-            addr: operator2.addr().to_string(),
-            voting_power: 30u32,
-        },
-        InstantiateOperator {
-            addr: operator3.addr().to_string(),
-            voting_power: 20u32,
-        },
+        InstantiateOperator::new(operator1.addr().to_string(), 50),
+        InstantiateOperator::new(operator2.addr().to_string(), 30),
+        InstantiateOperator::new(operator3.addr().to_string(), 20),
     ];
     let mock_operators = setup_mock_operators(chain.clone(), operators);
 
     let msg = InstantiateMsg {
-        operator_contract: mock_operators.addr_str().unwrap(),
-        threshold_percentage: Decimal::percent(90),
-        allowed_spread: Decimal::percent(5),
-        slashable_spread: Decimal::percent(10),
-        required_percentage: 70,
+        supermajority: Decimal::percent(75),
+        ..instantiate_msg(&mock_operators, 50, 10, 20)
     };
-    let verifier = setup(chain.clone(), msg);
+    let verifier = setup_verifier(chain.clone(), &mock_operators, msg);
+    let tasker = setup_task_queue(chain.clone(), &verifier);
+
+    let options = vec!["Team A".to_string(), "Team B".to_string()];
+    let split = make_categorical_task(&tasker, "Will Team A win?", options.clone());
+    let agreed = make_categorical_task(&tasker, "Will Team A win?", options);
+
+    let team_a = VoteResult::Categorical("Team A".to_string());
+    let team_b = VoteResult::Categorical("Team B".to_string());
+    let team_c = VoteResult::Categorical("Team C".to_string());
+    let split_votes = [(&operator1, team_a.clone()), (&operator2, team_b.clone()), (&operator3, team_a)];
+    let agreed_votes = [(&operator1, team_b.clone()), (&operator2, team_b)];
+    for (operator, result) in split_votes.iter() {
+        commit(&verifier, &tasker, operator, split, result);
+    }
+    for (operator, result) in agreed_votes.iter() {
+        commit(&verifier, &tasker, operator, agreed, result);
+    }
+    chain.wait_seconds(COMMIT_PHASE).unwrap();
+
+    // numeric votes and unknown options are rejected on reveal
+    let err = verifier
+        .call_as(&operator1)
+        .reveal_vote(split, price(1), SALT.to_string())
+        .unwrap_err();
+    assert!(
+        err.root()
+            .to_string()
+            .contains(&ContractError::VoteKindMismatch.to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+    let err = verifier
+        .call_as(&operator1)
+        .reveal_vote(split, team_c, SALT.to_string())
+        .unwrap_err();
+    assert!(
+        err.root()
+            .to_string()
+            .contains(&ContractError::UnknownOption("Team C".to_string()).to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+
+    for (operator, result) in split_votes {
+        reveal(&verifier, operator, split, result);
+    }
+    for (operator, result) in agreed_votes {
+        reveal(&verifier, operator, agreed, result);
+    }
+    chain.wait_seconds(REVEAL_PHASE).unwrap();
+
+    // 50 + 20 of 100 is not a 75% supermajority
+    let err = verifier.process_votes(split).unwrap_err();
+    assert!(
+        err.root()
+            .to_string()
+            .contains(&ContractError::ThresholdNotMet.to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+
+    // with the winner holding 80 it is written back to the task queue
+    let res = verifier.process_votes(agreed).unwrap();
+    assert_eq!(res.event_attr_value("wasm", "winner").unwrap(), "Team B");
+    assert_eq!(res.event_attr_value("wasm", "aggregated_power").unwrap(), "80");
+
+    let status = tasker.task_info(agreed).unwrap();
+    assert!(matches!(status.status, Status::Completed { .. }));
+    assert_eq!(status.outcome, Some("Team B".to_string()));
+    assert_eq!(status.result.unwrap(), json!({"winner": "Team B"}));
+}
 
-    let tasker = setup_task_queue(chain.clone(), &verifier.addr_str().unwrap());
+pub fn commit_reveal_phases<C>(chain: C)
+where
+    C: CwEnv + AltSigner,
+    C::Sender: Addressable,
+{
+    let operator1 = chain.alt_signer(3);
+    let operator2 = chain.alt_signer(4);
+    let operator3 = chain.alt_signer(5);
+
+    let operators = vec![
+        InstantiateOperator::new(operator1.addr().to_string(), 50),
+        InstantiateOperator::new(operator2.addr().to_string(), 30),
+        InstantiateOperator::new(operator3.addr().to_string(), 20),
+    ];
+    let mock_operators = setup_mock_operators(chain.clone(), operators);
+
+    let verifier = setup_verifier(chain.clone(), &mock_operators, instantiate_msg(&mock_operators, 50, 10, 20));
+    let tasker = setup_task_queue(chain.clone(), &verifier);
 
     let payload = json!({"action": "get_price"});
     let task_id = make_task(&tasker, "Get Price Task", None, &payload);
 
-    let result = r#"{"price": "100"}"#.to_string();
-    verifier
+    commit(&verifier, &tasker, &operator1, task_id, &price(100));
+    commit(&verifier, &tasker, &operator2, task_id, &price(101));
+    commit(&verifier, &tasker, &operator3, task_id, &price(99));
+
+    // nothing can be revealed during the commit phase
+    let err = verifier
+        .call_as(&operator1)
+        .reveal_vote(task_id, price(100), SALT.to_string())
+        .unwrap_err();
+    assert!(
+        err.root()
+            .to_string()
+            .contains(&ContractError::RevealPhaseNotStarted.to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+
+    // and nothing can be committed after it
+    chain.wait_seconds(COMMIT_PHASE).unwrap();
+    let err = verifier
+        .call_as(&operator1)
+        .commit_vote(tasker.addr_str().unwrap(), task_id, vote_hash(&operator1.addr(), task_id, &price(100), SALT).unwrap())
+        .unwrap_err();
+    assert!(
+        err.root()
+            .to_string()
+            .contains(&ContractError::CommitPhaseClosed.to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+
+    // a reveal must match the commitment
+    let err = verifier
+        .call_as(&operator1)
+        .reveal_vote(task_id, price(105), SALT.to_string())
+        .unwrap_err();
+    assert!(
+        err.root()
+            .to_string()
+            .contains(&ContractError::CommitMismatch.to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+    reveal(&verifier, &operator1, task_id, price(100));
+    reveal(&verifier, &operator2, task_id, price(101));
+
+    // votes are processed only once the reveal phase is over
+    let err = verifier.process_votes(task_id).unwrap_err();
+    assert!(
+        err.root()
+            .to_string()
+            .contains(&ContractError::RevealPhaseOpen.to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+    chain.wait_seconds(REVEAL_PHASE).unwrap();
+
+    // the operator who never revealed is penalized, and its vote is not counted
+    let res = verifier.process_votes(task_id).unwrap();
+    assert_eq!(res.event_attr_value("wasm", "aggregated_power").unwrap(), "80");
+    assert_eq!(res.event_attr_value("wasm", "unrevealed_count").unwrap(), "1");
+}
+
+pub fn slashing_reduces_power<C>(chain: C)
+where
+    C: CwEnv + AltSigner,
+    C::Sender: Addressable,
+{
+    let operator1 = chain.alt_signer(3);
+    let operator2 = chain.alt_signer(4);
+    let operator3 = chain.alt_signer(5);
+
+    let operators = vec![
+        InstantiateOperator::new(operator1.addr().to_string(), 50),
+        InstantiateOperator::new(operator2.addr().to_string(), 30),
+        InstantiateOperator::new(operator3.addr().to_string(), 20),
+    ];
+    let mock_operators = setup_mock_operators(chain.clone(), operators);
+
+    // slashable spread must not be below the allowed spread
+    let rejected = Contract::new(chain.clone());
+    rejected.upload().unwrap();
+    let err = rejected
+        .instantiate(&instantiate_msg(&mock_operators, 50, 20, 10), None, &[])
+        .unwrap_err();
+    assert!(
+        err.root()
+            .to_string()
+            .contains(&ContractError::InvalidSpread(Decimal::percent(10), Decimal::percent(20)).to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+
+    let verifier = setup_verifier(chain.clone(), &mock_operators, instantiate_msg(&mock_operators, 50, 10, 20));
+    let tasker = setup_task_queue(chain.clone(), &verifier);
+
+    let payload = json!({"action": "get_price"});
+    let first = make_task(&tasker, "Get Price Task", None, &payload);
+    let second = make_task(&tasker, "Get Price Task", None, &payload);
+
+    // operator2 is outside the allowed spread but within the slashable one,
+    // operator3 is beyond it. On the second task operator3 never reveals
+    let first_votes = [(&operator1, price(100)), (&operator2, price(115)), (&operator3, price(150))];
+    let second_votes = [(&operator1, price(100)), (&operator2, price(100))];
+    for (operator, result) in first_votes.iter() {
+        commit(&verifier, &tasker, operator, first, result);
+    }
+    for (operator, result) in second_votes.iter() {
+        commit(&verifier, &tasker, operator, second, result);
+    }
+    commit(&verifier, &tasker, &operator3, second, &price(100));
+    chain.wait_seconds(COMMIT_PHASE).unwrap();
+    for (operator, result) in first_votes {
+        reveal(&verifier, operator, first, result);
+    }
+    for (operator, result) in second_votes {
+        reveal(&verifier, operator, second, result);
+    }
+    chain.wait_seconds(REVEAL_PHASE).unwrap();
+
+    let res = verifier.process_votes(first).unwrap();
+    assert_eq!(res.event_attr_value("wasm", "aggregated_power").unwrap(), "50");
+    assert_eq!(res.event_attr_value("wasm", "slashed_count").unwrap(), "1");
+
+    // 10% of operator3's power is gone, operator2 keeps everything
+    let power = verifier.voting_power_at_height(operator3.addr().to_string(), None).unwrap();
+    assert_eq!(power.power.u128(), 18);
+    let power = verifier.voting_power_at_height(operator2.addr().to_string(), None).unwrap();
+    assert_eq!(power.power.u128(), 30);
+    let total = verifier.total_power_at_height(None).unwrap();
+    assert_eq!(total.power.u128(), 98);
+
+    let slashes = verifier.slashes_by_task(first).unwrap().slashes;
+    assert_eq!(slashes.len(), 1);
+    assert_eq!(slashes[0].operator, operator3.addr());
+    assert_eq!(slashes[0].reason, SlashReason::OutsideSpread);
+
+    // the second task was created before the slash, so it still weighs the full 100 power
+    let res = verifier.process_votes(second).unwrap();
+    assert_eq!(res.event_attr_value("wasm", "required_power").unwrap(), "50");
+    assert_eq!(res.event_attr_value("wasm", "unrevealed_count").unwrap(), "1");
+    let power = verifier.voting_power_at_height(operator3.addr().to_string(), None).unwrap();
+    assert_eq!(power.power.u128(), 17);
+
+    // both slashes show up for the operator, and can be paged through
+    let slashes = verifier
+        .slashes_by_operator(operator3.addr().to_string(), None, None)
+        .unwrap()
+        .slashes;
+    assert_eq!(slashes.len(), 2);
+    assert_eq!(slashes[1].task_id, second);
+    assert_eq!(slashes[1].reason, SlashReason::Unrevealed);
+    let slashes = verifier
+        .slashes_by_operator(operator3.addr().to_string(), Some(first), None)
+        .unwrap()
+        .slashes;
+    assert_eq!(slashes.len(), 1);
+    assert_eq!(slashes[0].task_id, second);
+    let slashes = verifier
+        .slashes_by_operator(operator1.addr().to_string(), None, None)
+        .unwrap()
+        .slashes;
+    assert!(slashes.is_empty());
+}
+
+pub fn fees_are_paid_to_operators<C>(chain: C)
+where
+    C: CwEnv + AltSigner,
+    C::Sender: Addressable,
+{
+    let operator1 = chain.alt_signer(3);
+    let operator2 = chain.alt_signer(4);
+    let operator3 = chain.alt_signer(5);
+
+    let operators = vec![
+        InstantiateOperator::new(operator1.addr().to_string(), 50),
+        InstantiateOperator::new(operator2.addr().to_string(), 30),
+        InstantiateOperator::new(operator3.addr().to_string(), 20),
+    ];
+    let mock_operators = setup_mock_operators(chain.clone(), operators);
+    let verifier = setup_verifier(chain.clone(), &mock_operators, instantiate_msg(&mock_operators, 50, 10, 20));
+
+    // anyone can create tasks, for a fee
+    let msg = TasksInstantiateMsg {
+        requestor: Requestor::OpenPayment(coin(1001, FEE_DENOM)),
+        timeout: TimeoutInfo::new(600),
+        verifier: verifier.addr_str().unwrap(),
+        dispute: None,
+        arbitrator: None,
+        cancel_fee: None,
+        keeper_reward: None,
+    };
+    let tasker = TasksContract::new(chain.clone());
+    tasker.upload().unwrap();
+    tasker.instantiate(&msg, None, &[]).unwrap();
+    serve_task_queue(&verifier, &tasker);
+    let res = tasker
+        .create(
+            "Get Price Task".to_string(),
+            None,
+            json!({"action": "get_price"}),
+            vec![],
+            String::new(),
+            None,
+            &coins(1001, FEE_DENOM),
+        )
+        .unwrap();
+    let task_id = get_task_id(&res);
+
+    let votes = [(&operator1, price(100)), (&operator2, price(101)), (&operator3, price(150))];
+    for (operator, result) in votes.iter() {
+        commit(&verifier, &tasker, operator, task_id, result);
+    }
+    chain.wait_seconds(COMMIT_PHASE).unwrap();
+    for (operator, result) in votes {
+        reveal(&verifier, operator, task_id, result);
+    }
+    chain.wait_seconds(REVEAL_PHASE).unwrap();
+    verifier.process_votes(task_id).unwrap();
+
+    // operator1 and operator2 agreed and split the fee 50:30 rounded down, operator3 gets nothing
+    let rewards = verifier.pending_rewards(operator1.addr().to_string()).unwrap().rewards;
+    assert_eq!(rewards, coins(625, FEE_DENOM));
+    let rewards = verifier.pending_rewards(operator2.addr().to_string()).unwrap().rewards;
+    assert_eq!(rewards, coins(375, FEE_DENOM));
+    let rewards = verifier.pending_rewards(operator3.addr().to_string()).unwrap().rewards;
+    assert!(rewards.is_empty());
+
+    let res = verifier.call_as(&operator1).claim_rewards().unwrap();
+    assert_eq!(res.event_attr_value("wasm", "rewards").unwrap(), format!("625{}", FEE_DENOM));
+    let rewards = verifier.pending_rewards(operator1.addr().to_string()).unwrap().rewards;
+    assert!(rewards.is_empty());
+
+    let err = verifier.call_as(&operator1).claim_rewards().unwrap_err();
+    assert!(
+        err.root().to_string().contains(&ContractError::NoRewards.to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+    let err = verifier.call_as(&operator3).claim_rewards().unwrap_err();
+    assert!(
+        err.root().to_string().contains(&ContractError::NoRewards.to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+
+    // the owner takes the rounding leftover, but nothing operator2 can still claim
+    let err = verifier
+        .call_as(&operator1)
+        .withdraw_surplus(FEE_DENOM.to_string())
+        .unwrap_err();
+    assert!(
+        err.root().to_string().contains(&ContractError::Unauthorized.to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+    let res = verifier.withdraw_surplus(FEE_DENOM.to_string()).unwrap();
+    assert_eq!(res.event_attr_value("wasm", "amount").unwrap(), "1");
+    let err = verifier.withdraw_surplus(FEE_DENOM.to_string()).unwrap_err();
+    assert!(
+        err.root().to_string().contains(&ContractError::NoSurplus.to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+    verifier.call_as(&operator2).claim_rewards().unwrap();
+}
+
+pub fn challenged_tasks_vote_again<C>(chain: C)
+where
+    C: CwEnv + AltSigner,
+    C::Sender: Addressable,
+{
+    let operator1 = chain.alt_signer(3);
+    let operator2 = chain.alt_signer(4);
+    let operator3 = chain.alt_signer(5);
+
+    let operators = vec![
+        InstantiateOperator::new(operator1.addr().to_string(), 50),
+        InstantiateOperator::new(operator2.addr().to_string(), 30),
+        InstantiateOperator::new(operator3.addr().to_string(), 20),
+    ];
+    let mock_operators = setup_mock_operators(chain.clone(), operators);
+    let verifier = setup_verifier(chain.clone(), &mock_operators, instantiate_msg(&mock_operators, 50, 10, 20));
+
+    // results can be challenged for 100 seconds, by putting up a bond
+    let msg = TasksInstantiateMsg {
+        requestor: Requestor::Fixed(chain.sender_addr().into()),
+        timeout: TimeoutInfo::new(600),
+        verifier: verifier.addr_str().unwrap(),
+        dispute: Some(DisputeConfig {
+            window: 100,
+            bond: coin(1000, FEE_DENOM),
+        }),
+        arbitrator: None,
+        cancel_fee: None,
+        keeper_reward: None,
+    };
+    let tasker = TasksContract::new(chain.clone());
+    tasker.upload().unwrap();
+    tasker.instantiate(&msg, None, &[]).unwrap();
+    serve_task_queue(&verifier, &tasker);
+    let task_id = make_task(&tasker, "Get Price Task", None, &json!({"action": "get_price"}));
+
+    // 70% of the power agrees in the first round, which is enough
+    let votes = [(&operator1, price(100)), (&operator2, price(150)), (&operator3, price(100))];
+    for (operator, result) in votes.iter() {
+        commit(&verifier, &tasker, operator, task_id, result);
+    }
+    chain.wait_seconds(COMMIT_PHASE).unwrap();
+    for (operator, result) in votes {
+        reveal(&verifier, operator, task_id, result);
+    }
+    chain.wait_seconds(REVEAL_PHASE).unwrap();
+    verifier.process_votes(task_id).unwrap();
+    let task = tasker.task_info(task_id).unwrap();
+    assert!(matches!(task.status, Status::Pending { .. }));
+    assert_eq!(task.result, Some(json!({"price": "100"})));
+
+    // the challenge re-opens the task, and the first round's votes are done
+    tasker
+        .challenge(task_id, json!({"price": "150"}), &coins(1000, FEE_DENOM))
+        .unwrap();
+    assert_eq!(tasker.task_info(task_id).unwrap().status, Status::Open {});
+    let err = verifier.process_votes(task_id).unwrap_err();
+    assert!(
+        err.root().to_string().contains(&ContractError::StaleRound.to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+
+    // the second round needs 80% of the power
+    let votes = [(&operator1, price(100)), (&operator2, price(100))];
+    for (operator, result) in votes.iter() {
+        commit(&verifier, &tasker, operator, task_id, result);
+    }
+    chain.wait_seconds(COMMIT_PHASE).unwrap();
+    for (operator, result) in votes {
+        reveal(&verifier, operator, task_id, result);
+    }
+    chain.wait_seconds(REVEAL_PHASE).unwrap();
+    let res = verifier.process_votes(task_id).unwrap();
+    assert_eq!(res.event_attr_value("wasm", "required_power").unwrap(), "80");
+
+    // the result stands and is final, so the challenger's bond goes to the operators who confirmed it
+    let task = tasker.task_info(task_id).unwrap();
+    assert!(matches!(task.status, Status::Completed { .. }));
+    assert_eq!(task.result, Some(json!({"price": "100"})));
+    let rewards = verifier.pending_rewards(operator1.addr().to_string()).unwrap().rewards;
+    assert_eq!(rewards, coins(625, FEE_DENOM));
+    let rewards = verifier.pending_rewards(operator2.addr().to_string()).unwrap().rewards;
+    assert_eq!(rewards, coins(375, FEE_DENOM));
+}
+
+pub fn failed_second_rounds_escalate<C>(chain: C)
+where
+    C: CwEnv + AltSigner,
+    C::Sender: Addressable,
+{
+    let operator1 = chain.alt_signer(3);
+    let operator2 = chain.alt_signer(4);
+    let operator3 = chain.alt_signer(5);
+    let arbitrator = chain.alt_signer(8);
+
+    let operators = vec![
+        InstantiateOperator::new(operator1.addr().to_string(), 50),
+        InstantiateOperator::new(operator2.addr().to_string(), 30),
+        InstantiateOperator::new(operator3.addr().to_string(), 20),
+    ];
+    let mock_operators = setup_mock_operators(chain.clone(), operators);
+    let verifier = setup_verifier(chain.clone(), &mock_operators, instantiate_msg(&mock_operators, 50, 10, 20));
+
+    let msg = TasksInstantiateMsg {
+        requestor: Requestor::Fixed(chain.sender_addr().into()),
+        timeout: TimeoutInfo::new(600),
+        verifier: verifier.addr_str().unwrap(),
+        dispute: Some(DisputeConfig {
+            window: 100,
+            bond: coin(1000, FEE_DENOM),
+        }),
+        arbitrator: Some(arbitrator.addr().to_string()),
+        cancel_fee: None,
+        keeper_reward: None,
+    };
+    let tasker = TasksContract::new(chain.clone());
+    tasker.upload().unwrap();
+    tasker.instantiate(&msg, None, &[]).unwrap();
+    serve_task_queue(&verifier, &tasker);
+    let task_id = make_task(&tasker, "Get Price Task", None, &json!({"action": "get_price"}));
+
+    let votes = [(&operator1, price(100)), (&operator3, price(100))];
+    for (operator, result) in votes.iter() {
+        commit(&verifier, &tasker, operator, task_id, result);
+    }
+    chain.wait_seconds(COMMIT_PHASE).unwrap();
+    for (operator, result) in votes {
+        reveal(&verifier, operator, task_id, result);
+    }
+    chain.wait_seconds(REVEAL_PHASE).unwrap();
+    verifier.process_votes(task_id).unwrap();
+    tasker
+        .challenge(task_id, json!({"price": "150"}), &coins(1000, FEE_DENOM))
+        .unwrap();
+
+    // the same 70% are no longer enough in the second round
+    let votes = [(&operator1, price(100)), (&operator3, price(100))];
+    for (operator, result) in votes.iter() {
+        commit(&verifier, &tasker, operator, task_id, result);
+    }
+    chain.wait_seconds(COMMIT_PHASE).unwrap();
+    for (operator, result) in votes {
+        reveal(&verifier, operator, task_id, result);
+    }
+    chain.wait_seconds(REVEAL_PHASE).unwrap();
+    let res = verifier.process_votes(task_id).unwrap();
+    assert_eq!(res.event_attr_value("wasm", "escalated").unwrap(), "true");
+    assert!(matches!(tasker.task_info(task_id).unwrap().status, Status::Arbitration { .. }));
+
+    // the arbitrator has the final word
+    tasker
+        .call_as(&arbitrator)
+        .arbitrate(task_id, json!({"price": "150"}))
+        .unwrap();
+    let task = tasker.task_info(task_id).unwrap();
+    assert!(matches!(task.status, Status::Completed { .. }));
+    assert_eq!(task.result, Some(json!({"price": "150"})));
+}
+
+pub fn invalid_votes_invalidate_the_task<C>(chain: C)
+where
+    C: CwEnv + AltSigner,
+    C::Sender: Addressable,
+{
+    let operator1 = chain.alt_signer(3);
+    let operator2 = chain.alt_signer(4);
+    let operator3 = chain.alt_signer(5);
+
+    let operators = vec![
+        InstantiateOperator::new(operator1.addr().to_string(), 50),
+        InstantiateOperator::new(operator2.addr().to_string(), 30),
+        InstantiateOperator::new(operator3.addr().to_string(), 20),
+    ];
+    let mock_operators = setup_mock_operators(chain.clone(), operators);
+    let msg = InstantiateMsg {
+        supermajority: Decimal::percent(75),
+        ..instantiate_msg(&mock_operators, 50, 10, 20)
+    };
+    let verifier = setup_verifier(chain.clone(), &mock_operators, msg);
+    let tasker = setup_task_queue(chain.clone(), &verifier);
+
+    let options = vec!["Team A".to_string(), "Team B".to_string()];
+    let task_id = make_categorical_task(&tasker, "Will Team A win?", options);
+
+    // the match was cancelled, so most operators say the question cannot be answered
+    let invalid = VoteResult::Categorical(INVALID_OPTION.to_string());
+    let votes = [
+        (&operator1, invalid.clone()),
+        (&operator2, invalid),
+        (&operator3, VoteResult::Categorical("Team A".to_string())),
+    ];
+    for (operator, result) in votes.iter() {
+        commit(&verifier, &tasker, operator, task_id, result);
+    }
+    chain.wait_seconds(COMMIT_PHASE).unwrap();
+    for (operator, result) in votes {
+        reveal(&verifier, operator, task_id, result);
+    }
+    chain.wait_seconds(REVEAL_PHASE).unwrap();
+
+    let res = verifier.process_votes(task_id).unwrap();
+    assert_eq!(res.event_attr_value("wasm", "winner").unwrap(), INVALID_OPTION);
+    assert_eq!(verifier.slashes_by_task(task_id).unwrap().slashes.len(), 1);
+
+    let task = tasker.task_info(task_id).unwrap();
+    assert!(matches!(task.status, Status::Invalid { .. }));
+    assert_eq!(task.outcome, None);
+    assert_eq!(task.result, None);
+}
+
+pub fn tasks_with_votes_cannot_be_cancelled<C>(chain: C)
+where
+    C: CwEnv + AltSigner,
+    C::Sender: Addressable,
+{
+    let operator1 = chain.alt_signer(3);
+    let operators = vec![InstantiateOperator::new(operator1.addr().to_string(), 100)];
+    let mock_operators = setup_mock_operators(chain.clone(), operators);
+    let verifier = setup_verifier(chain.clone(), &mock_operators, instantiate_msg(&mock_operators, 50, 10, 20));
+    let tasker = setup_task_queue(chain.clone(), &verifier);
+
+    let untouched = make_task(&tasker, "Get Price Task", None, &json!({"action": "get_price"}));
+    let voted = make_task(&tasker, "Get Price Task", None, &json!({"action": "get_price"}));
+    commit(&verifier, &tasker, &operator1, voted, &price(100));
+
+    // the commit is hidden, but the verifier knows it is there
+    let count = verifier.vote_count(tasker.addr_str().unwrap(), voted).unwrap().count;
+    assert_eq!(count, 1);
+    let count = verifier.vote_count(tasker.addr_str().unwrap(), untouched).unwrap().count;
+    assert_eq!(count, 0);
+
+    let err = tasker.cancel_task(voted).unwrap_err();
+    assert!(
+        err.root()
+            .to_string()
+            .contains(&TasksContractError::TaskHasVotes.to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+    tasker.cancel_task(untouched).unwrap();
+    assert_eq!(tasker.task_info(untouched).unwrap().status, Status::Cancelled {});
+
+    // operators can no longer vote on a cancelled task
+    let err = verifier
         .call_as(&operator1)
-        .executed_task(tasker.addr_str().unwrap(), task_id, result.clone())
+        .commit_vote(
+            tasker.addr_str().unwrap(),
+            untouched,
+            vote_hash(&operator1.addr(), untouched, &price(100), SALT).unwrap(),
+        )
+        .unwrap_err();
+    assert!(
+        err.root()
+            .to_string()
+            .contains(&ContractError::TaskCancelled.to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+}
+
+pub fn config_is_updated_by_the_owner<C>(chain: C)
+where
+    C: CwEnv + AltSigner,
+    C::Sender: Addressable,
+{
+    let operator1 = chain.alt_signer(3);
+    let new_owner = chain.alt_signer(6);
+    let operators = vec![InstantiateOperator::new(operator1.addr().to_string(), 100)];
+    let mock_operators = setup_mock_operators(chain.clone(), operators);
+    let verifier = setup_verifier(chain.clone(), &mock_operators, instantiate_msg(&mock_operators, 50, 10, 20));
+
+    // the spreads are validated like on instantiation
+    let err = verifier
+        .update_config(None, Some(Decimal::percent(30)), None, None, None, None, None)
+        .unwrap_err();
+    assert!(
+        err.root()
+            .to_string()
+            .contains(&ContractError::InvalidSpread(Decimal::percent(20), Decimal::percent(30)).to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+    verifier
+        .update_config(Some(Decimal::percent(66)), None, Some(Decimal::percent(30)), None, None, None, None)
         .unwrap();
+    let config = verifier.config().unwrap();
+    assert_eq!(config.threshold_percent, Decimal::percent(66));
+    assert_eq!(config.allowed_spread, Decimal::percent(10));
+    assert_eq!(config.slashable_spread, Decimal::percent(30));
+    assert_eq!(config.owner, chain.sender_addr());
+
+    // ownership only moves once the new owner accepts
+    let err = verifier
+        .call_as(&operator1)
+        .update_config(None, None, None, None, None, None, None)
+        .unwrap_err();
+    assert!(
+        err.root().to_string().contains(&ContractError::Unauthorized.to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+    verifier.transfer_ownership(new_owner.addr().to_string()).unwrap();
+    verifier.update_config(None, None, None, None, None, None, None).unwrap();
+    verifier.call_as(&operator1).accept_ownership().unwrap_err();
+    verifier.call_as(&new_owner).accept_ownership().unwrap();
 
-    let result = r#"{"price": "210"}"#.to_string();
+    let config = verifier.config().unwrap();
+    assert_eq!(config.owner, new_owner.addr());
+    assert_eq!(config.pending_owner, None);
+    verifier.update_config(None, None, None, None, None, None, None).unwrap_err();
     verifier
+        .call_as(&new_owner)
+        .update_config(None, None, None, None, Some(Decimal::percent(90)), None, None)
+        .unwrap();
+}
+
+pub fn scalar_votes_stay_in_range<C>(chain: C)
+where
+    C: CwEnv + AltSigner,
+    C::Sender: Addressable,
+{
+    let operator1 = chain.alt_signer(3);
+    let operator2 = chain.alt_signer(4);
+    let operators = vec![
+        InstantiateOperator::new(operator1.addr().to_string(), 50),
+        InstantiateOperator::new(operator2.addr().to_string(), 50),
+    ];
+    let mock_operators = setup_mock_operators(chain.clone(), operators);
+    let verifier = setup_verifier(chain.clone(), &mock_operators, instantiate_msg(&mock_operators, 50, 10, 20));
+    let tasker = setup_task_queue(chain.clone(), &verifier);
+
+    let payload = json!({"scalar": {"min": "0", "max": "100", "unit": "mm"}});
+    let task_id = make_task(&tasker, "Rainfall", None, &payload);
+    commit(&verifier, &tasker, &operator1, task_id, &price(0));
+    commit(&verifier, &tasker, &operator2, task_id, &price(150));
+    chain.wait_seconds(COMMIT_PHASE).unwrap();
+
+    // zero is a valid scalar, unlike a price, but the range is enforced
+    reveal(&verifier, &operator1, task_id, price(0));
+    let err = verifier
+        .call_as(&operator2)
+        .reveal_vote(task_id, price(150), SALT.to_string())
+        .unwrap_err();
+    assert!(
+        err.root()
+            .to_string()
+            .contains(&ContractError::OutOfRange(Decimal::zero(), Decimal::from_ratio(100u128, 1u128)).to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+}
+
+pub fn events_are_voted_on_after_they_close<C>(chain: C)
+where
+    C: CwEnv + AltSigner,
+    C::Sender: Addressable,
+{
+    let operator1 = chain.alt_signer(3);
+    let operator2 = chain.alt_signer(4);
+    let operators = vec![
+        InstantiateOperator::new(operator1.addr().to_string(), 50),
+        InstantiateOperator::new(operator2.addr().to_string(), 50),
+    ];
+    let mock_operators = setup_mock_operators(chain.clone(), operators);
+    let verifier = setup_verifier(chain.clone(), &mock_operators, instantiate_msg(&mock_operators, 50, 10, 20));
+    let tasker = setup_task_queue(chain.clone(), &verifier);
+
+    // closes halfway through the 600 second timeout
+    let close_time = chain.block_info().unwrap().time.seconds() + 300;
+    let payload = json!({"binary_event": {
+        "question": "Will it rain?",
+        "resolution_source": "weather service",
+        "close_time": close_time,
+    }});
+    let task_id = make_task(&tasker, "Rain", None, &payload);
+    let yes = VoteResult::Categorical("yes".to_string());
+
+    let err = verifier
+        .call_as(&operator1)
+        .commit_vote(tasker.addr_str().unwrap(), task_id, vote_hash(&operator1.addr(), task_id, &yes, SALT).unwrap())
+        .unwrap_err();
+    assert!(
+        err.root()
+            .to_string()
+            .contains(&ContractError::EventNotClosed(close_time).to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+
+    // the commit phase is half of the 300 seconds left after the close
+    chain.wait_seconds(300).unwrap();
+    commit(&verifier, &tasker, &operator1, task_id, &yes);
+    chain.wait_seconds(150).unwrap();
+    let err = verifier
         .call_as(&operator2)
-        .executed_task(tasker.addr_str().unwrap(), task_id, result.clone())
+        .commit_vote(tasker.addr_str().unwrap(), task_id, vote_hash(&operator2.addr(), task_id, &yes, SALT).unwrap())
+        .unwrap_err();
+    assert!(
+        err.root()
+            .to_string()
+            .contains(&ContractError::CommitPhaseClosed.to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+    reveal(&verifier, &operator1, task_id, yes);
+}
+
+pub fn pausing_freezes_resolution<C>(chain: C)
+where
+    C: CwEnv + AltSigner,
+    C::Sender: Addressable,
+{
+    let operator1 = chain.alt_signer(3);
+    let operator2 = chain.alt_signer(4);
+    let guardian = chain.alt_signer(6);
+    let operators = vec![
+        InstantiateOperator::new(operator1.addr().to_string(), 50),
+        InstantiateOperator::new(operator2.addr().to_string(), 50),
+    ];
+    let mock_operators = setup_mock_operators(chain.clone(), operators);
+    let verifier = setup_verifier(chain.clone(), &mock_operators, instantiate_msg(&mock_operators, 50, 10, 20));
+    let tasker = setup_task_queue(chain.clone(), &verifier);
+    verifier
+        .update_config(None, None, None, None, None, Some(guardian.addr().to_string()), None)
         .unwrap();
 
-    let status = tasker.task(task_id).unwrap();
+    let payload = json!({"action": "get_price"});
+    let task_id = make_task(&tasker, "Get Price Task", None, &payload);
+    commit(&verifier, &tasker, &operator1, task_id, &price(100));
+
+    // only the guardian and the owner can pause
+    let err = verifier.call_as(&operator1).pause().unwrap_err();
+    assert!(
+        err.root().to_string().contains(&ContractError::Unauthorized.to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+    verifier.call_as(&guardian).pause().unwrap();
+    assert!(verifier.config().unwrap().paused);
+
+    let hash = vote_hash(&operator2.addr(), task_id, &price(100), SALT).unwrap();
+    let err = verifier
+        .call_as(&operator2)
+        .commit_vote(tasker.addr_str().unwrap(), task_id, hash)
+        .unwrap_err();
+    assert!(
+        err.root().to_string().contains(&ContractError::Paused.to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
 
+    // committed votes can still be revealed, but nothing is decided
+    chain.wait_seconds(COMMIT_PHASE).unwrap();
+    reveal(&verifier, &operator1, task_id, price(100));
+    chain.wait_seconds(REVEAL_PHASE).unwrap();
+    let err = verifier.process_votes(task_id).unwrap_err();
+    assert!(
+        err.root().to_string().contains(&ContractError::Paused.to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+    let status = tasker.task_info(task_id).unwrap();
     assert_eq!(status.status, Status::Open {});
+
+    verifier.call_as(&guardian).unpause().unwrap();
+    verifier.process_votes(task_id).unwrap();
+    let status = tasker.task_info(task_id).unwrap();
+    assert!(matches!(status.status, Status::Completed { .. }));
+}
+
+pub fn only_the_served_task_queue_is_voted_on<C>(chain: C)
+where
+    C: CwEnv + AltSigner,
+    C::Sender: Addressable,
+{
+    let operator1 = chain.alt_signer(3);
+    let operators = vec![InstantiateOperator::new(operator1.addr().to_string(), 100)];
+    let mock_operators = setup_mock_operators(chain.clone(), operators);
+    let verifier = setup_verifier(chain.clone(), &mock_operators, instantiate_msg(&mock_operators, 50, 10, 20));
+    let tasker = setup_task_queue(chain.clone(), &verifier);
+    assert_eq!(verifier.config().unwrap().task_queue, Some(tasker.address().unwrap()));
+    let task_id = make_task(&tasker, "Get Price Task", None, &json!({"action": "get_price"}));
+
+    // another contract cannot claim the task id, or have us share a fee it never pays
+    let untrusted = mock_operators.addr_str().unwrap();
+    let hash = vote_hash(&operator1.addr(), task_id, &price(100), SALT).unwrap();
+    let err = verifier
+        .call_as(&operator1)
+        .commit_vote(untrusted.clone(), task_id, hash)
+        .unwrap_err();
+    assert!(
+        err.root()
+            .to_string()
+            .contains(&ContractError::UntrustedTaskQueue(untrusted.clone()).to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+    commit(&verifier, &tasker, &operator1, task_id, &price(100));
+
+    // only the owner picks the task queue
+    let err = verifier
+        .call_as(&operator1)
+        .update_config(None, None, None, None, None, None, Some(untrusted.clone()))
+        .unwrap_err();
+    assert!(
+        err.root().to_string().contains(&ContractError::Unauthorized.to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+
+    // and only once, so task ids of another queue never meet the ones already tracked
+    let err = verifier
+        .update_config(None, None, None, None, None, None, Some(untrusted))
+        .unwrap_err();
+    assert!(
+        err.root()
+            .to_string()
+            .contains(&ContractError::TaskQueueAlreadySet(tasker.addr_str().unwrap()).to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+}
+
+/// Seconds until the commit phase of a task with the default timeout is over
+pub const COMMIT_PHASE: u64 = 300;
+/// Seconds from the end of the commit phase until the votes can be processed
+pub const REVEAL_PHASE: u64 = 180;
+pub const SALT: &str = "pepper";
+pub const FEE_DENOM: &str = "uslay";
+
+pub fn instantiate_msg<C: CwEnv>(
+    mock_operators: &MockOperatorsContract<C>,
+    threshold_percent: u64,
+    allowed_spread: u64,
+    slashable_spread: u64,
+) -> InstantiateMsg {
+    InstantiateMsg {
+        operator_contract: mock_operators.addr_str().unwrap(),
+        threshold_percent: Decimal::percent(threshold_percent),
+        allowed_spread: Decimal::percent(allowed_spread),
+        slashable_spread: Decimal::percent(slashable_spread),
+        slash_fraction: Decimal::percent(10),
+        supermajority: Decimal::percent(66),
+        // with a 600 second timeout: commit for 300 seconds, reveal for 180
+        commit_window: Decimal::percent(50),
+        reveal_window: Decimal::percent(30),
+        challenge_threshold: Decimal::percent(80),
+    }
+}
+
+pub fn price(value: u128) -> VoteResult {
+    VoteResult::Numeric(Decimal::from_ratio(value, 1u128))
+}
+
+#[track_caller]
+pub fn commit<C>(
+    verifier: &Contract<C>,
+    tasker: &TasksContract<C>,
+    operator: &C::Sender,
+    task_id: TaskId,
+    result: &VoteResult,
+) where
+    C: CwEnv,
+    C::Sender: Addressable,
+{
+    let hash = vote_hash(&operator.addr(), task_id, result, SALT).unwrap();
+    verifier
+        .call_as(operator)
+        .commit_vote(tasker.addr_str().unwrap(), task_id, hash)
+        .unwrap();
+}
+
+#[track_caller]
+pub fn reveal<C>(verifier: &Contract<C>, operator: &C::Sender, task_id: TaskId, result: VoteResult)
+where
+    C: CwEnv,
+{
+    verifier
+        .call_as(operator)
+        .reveal_vote(task_id, result, SALT.to_string())
+        .unwrap();
+}
+
+#[track_caller]
+pub fn make_categorical_task<C: ChainState + TxHandler>(
+    contract: &TasksContract<C>,
+    name: &str,
+    options: Vec<String>,
+) -> TaskId {
+    let res = contract
+        .create(
+            name.to_string(),
+            None,
+            json!({"question": name}),
+            options,
+            String::new(),
+            None,
+            &[],
+        )
+        .unwrap();
+    get_task_id(&res)
 }
 
 #[track_caller]
@@ -172,7 +1248,15 @@ pub fn make_task<C: ChainState + TxHandler>(
     payload: &serde_json::Value,
 ) -> TaskId {
     let res = contract
-        .create(name.to_string(), timeout.into(), payload.clone(), &[])
+        .create(
+            name.to_string(),
+            timeout.into(),
+            payload.clone(),
+            vec![],
+            String::new(),
+            None,
+            &[],
+        )
         .unwrap();
     get_task_id(&res)
 }
@@ -187,7 +1271,8 @@ pub fn get_task_id(res: &impl IndexResponse) -> TaskId {
     TaskId::new(id)
 }
 
-pub fn setup_task_queue<C>(chain: C, verifier_addr: &str) -> TasksContract<C>
+/// Instantiates a task queue and has the verifier serve it
+pub fn setup_task_queue<C>(chain: C, verifier: &Contract<C>) -> TasksContract<C>
 where
     C: CwEnv + AltSigner,
     C::Sender: Addressable,
@@ -195,14 +1280,26 @@ where
     let msg = TasksInstantiateMsg {
         requestor: Requestor::Fixed(chain.sender_addr().into()),
         timeout: TimeoutInfo::new(600),
-        verifier: verifier_addr.to_string(),
+        verifier: verifier.addr_str().unwrap(),
+        dispute: None,
+        arbitrator: None,
+        cancel_fee: None,
+        keeper_reward: None,
     };
     let tasker = TasksContract::new(chain);
     tasker.upload().unwrap();
     tasker.instantiate(&msg, None, &[]).unwrap();
+    serve_task_queue(verifier, &tasker);
     tasker
 }
 
+/// Makes the task queue the one operators vote on. The chain's sender must own the verifier
+pub fn serve_task_queue<C: CwEnv>(verifier: &Contract<C>, tasker: &TasksContract<C>) {
+    verifier
+        .update_config(None, None, None, None, None, None, Some(tasker.addr_str().unwrap()))
+        .unwrap();
+}
+
 pub fn setup_mock_operators<C>(
     chain: C,
     operators: Vec<InstantiateOperator>,
@@ -211,9 +1308,17 @@ where
     C: CwEnv + AltSigner,
     C::Sender: Addressable,
 {
-    let msg = MockOperatorsInstantiateMsg { operators };
-    let mock_operators = MockOperatorsContract::new(chain);
+    // the verifier is linked once it exists, see `setup_verifier`
+    let msg = MockOperatorsInstantiateMsg {
+        operators,
+        oracle_verifier: None,
+        bond_denom: "uslay".to_string(),
+        unbonding_period: 1000,
+    };
+    let mock_operators = MockOperatorsContract::new(chain.clone());
     mock_operators.upload().unwrap();
     mock_operators.instantiate(&msg, None, &[]).unwrap();
+    // power only counts from the block after it was set
+    chain.next_block().unwrap();
     mock_operators
 }
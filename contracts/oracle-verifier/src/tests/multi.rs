@@ -1,6 +1,19 @@
-use cw_orch::prelude::MockBech32;
+use cosmwasm_std::{coins, Decimal, Storage};
+use cw_orch::prelude::*;
+use lavs_mock_operators::msg::InstantiateOperator;
+use lavs_orch::{Addressable, AltSigner};
+use lavs_task_queue::msg::{ListOpenResponse, QueryMsg as TasksQueryMsg, QueryMsgFns as TasksQueryMsgFns, Status};
+use serde_json::json;
 
-use super::common::BECH_PREFIX;
+use super::common::{
+    commit, instantiate_msg, make_task, price, reveal, serve_task_queue, setup_mock_operators, setup_task_queue,
+    BECH_PREFIX, COMMIT_PHASE, FEE_DENOM, REVEAL_PHASE,
+};
+use crate::interface::Contract;
+use crate::migration::v1;
+use crate::msg::{ExecuteMsgFns, MigrateMsg};
+use crate::state::{CONFIG, TASKS, VOTES};
+use crate::ContractError;
 
 #[test]
 fn happy_path_works() {
@@ -13,3 +26,234 @@ fn threshold_not_met() {
     let chain = MockBech32::new(BECH_PREFIX);
     super::common::threshold_not_met(chain);
 }
+
+#[test]
+fn weighted_median_ignores_low_power_outliers() {
+    let chain = MockBech32::new(BECH_PREFIX);
+    super::common::weighted_median_ignores_low_power_outliers(chain);
+}
+
+#[test]
+fn threshold_counts_power_not_results() {
+    let chain = MockBech32::new(BECH_PREFIX);
+    super::common::threshold_counts_power_not_results(chain);
+}
+
+#[test]
+fn categorical_votes_resolve_by_supermajority() {
+    let chain = MockBech32::new(BECH_PREFIX);
+    super::common::categorical_votes_resolve_by_supermajority(chain);
+}
+
+#[test]
+fn commit_reveal_phases() {
+    let chain = MockBech32::new(BECH_PREFIX);
+    super::common::commit_reveal_phases(chain);
+}
+
+#[test]
+fn slashing_reduces_power() {
+    let chain = MockBech32::new(BECH_PREFIX);
+    super::common::slashing_reduces_power(chain);
+}
+
+#[test]
+fn fees_are_paid_to_operators() {
+    let chain = MockBech32::new(BECH_PREFIX);
+    chain
+        .add_balance(&chain.sender_addr(), coins(1001, FEE_DENOM))
+        .unwrap();
+    super::common::fees_are_paid_to_operators(chain);
+}
+
+#[test]
+fn challenged_tasks_vote_again() {
+    let chain = MockBech32::new(BECH_PREFIX);
+    chain
+        .add_balance(&chain.sender_addr(), coins(1000, FEE_DENOM))
+        .unwrap();
+    super::common::challenged_tasks_vote_again(chain);
+}
+
+#[test]
+fn failed_second_rounds_escalate() {
+    let chain = MockBech32::new(BECH_PREFIX);
+    chain
+        .add_balance(&chain.sender_addr(), coins(1000, FEE_DENOM))
+        .unwrap();
+    super::common::failed_second_rounds_escalate(chain);
+}
+
+#[test]
+fn invalid_votes_invalidate_the_task() {
+    let chain = MockBech32::new(BECH_PREFIX);
+    super::common::invalid_votes_invalidate_the_task(chain);
+}
+
+#[test]
+fn tasks_with_votes_cannot_be_cancelled() {
+    let chain = MockBech32::new(BECH_PREFIX);
+    super::common::tasks_with_votes_cannot_be_cancelled(chain);
+}
+
+#[test]
+fn config_is_updated_by_the_owner() {
+    let chain = MockBech32::new(BECH_PREFIX);
+    super::common::config_is_updated_by_the_owner(chain);
+}
+
+#[test]
+fn scalar_votes_stay_in_range() {
+    let chain = MockBech32::new(BECH_PREFIX);
+    super::common::scalar_votes_stay_in_range(chain);
+}
+
+#[test]
+fn events_are_voted_on_after_they_close() {
+    let chain = MockBech32::new(BECH_PREFIX);
+    super::common::events_are_voted_on_after_they_close(chain);
+}
+
+#[test]
+fn pausing_freezes_resolution() {
+    let chain = MockBech32::new(BECH_PREFIX);
+    super::common::pausing_freezes_resolution(chain);
+}
+
+#[test]
+fn only_the_served_task_queue_is_voted_on() {
+    let chain = MockBech32::new(BECH_PREFIX);
+    super::common::only_the_served_task_queue_is_voted_on(chain);
+}
+
+/// Runs `f` on the contract's storage, to leave it the way an older release would have
+fn with_storage<T>(chain: &MockBech32, contract: &Addr, f: impl FnOnce(&mut dyn Storage) -> T) -> T {
+    let mut app = chain.app.borrow_mut();
+    f(app.contract_storage_mut(contract).as_mut())
+}
+
+fn v1_settings() -> MigrateMsg {
+    MigrateMsg::FromV1 {
+        slash_fraction: Decimal::percent(10),
+        supermajority: Decimal::percent(66),
+        commit_window: Decimal::percent(50),
+        reveal_window: Decimal::percent(30),
+        challenge_threshold: Decimal::percent(80),
+    }
+}
+
+#[test]
+fn migrates_v1_state() {
+    let chain = MockBech32::new(BECH_PREFIX);
+    let operator = chain.alt_signer(3);
+    let mock_operators = setup_mock_operators(
+        chain.clone(),
+        vec![InstantiateOperator::new(operator.addr().to_string(), 100)],
+    );
+    let verifier = Contract::new(chain.clone());
+    verifier.upload().unwrap();
+    verifier
+        .instantiate(&instantiate_msg(&mock_operators, 50, 10, 20), Some(&chain.sender_addr()), &[])
+        .unwrap();
+    let tasker = setup_task_queue(chain.clone(), &verifier);
+    let task_id = make_task(&tasker, "Get Price Task", None, &json!({"action": "get_price"}));
+
+    // 1.x kept the task queue's metadata of every task it saw a vote on
+    let open: ListOpenResponse = tasker
+        .query(&TasksQueryMsg::ListOpen {
+            start_after: None,
+            limit: None,
+            order: None,
+        })
+        .unwrap();
+    with_storage(&chain, &verifier.address().unwrap(), |storage| {
+        cw2::set_contract_version(storage, "crates.io:oracle-verifier", "1.0.0").unwrap();
+        let config = v1::Config {
+            threshold_percent: Decimal::percent(50),
+            allowed_spread: Decimal::percent(10),
+            slashable_spread: Decimal::percent(20),
+            operator_contract: mock_operators.address().unwrap(),
+        };
+        v1::CONFIG.save(storage, &config).unwrap();
+        v1::TASKS.save(storage, task_id, &open.tasks[0]).unwrap();
+        let vote = v1::OperatorVote {
+            result: Decimal::percent(10000),
+        };
+        v1::VOTES.save(storage, (task_id, operator.addr()), &vote).unwrap();
+        v1::SLASHED_OPERATORS.save(storage, operator.addr(), &true).unwrap();
+    });
+
+    // 1.x has none of the new settings, so they must be given
+    let err = verifier
+        .migrate(&MigrateMsg::Upgrade {}, verifier.code_id().unwrap())
+        .unwrap_err();
+    assert!(
+        err.root()
+            .to_string()
+            .contains(&ContractError::WrongMigrateMsg("1.0.0".to_string()).to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+    verifier.migrate(&v1_settings(), verifier.code_id().unwrap()).unwrap();
+
+    with_storage(&chain, &verifier.address().unwrap(), |storage| {
+        assert_eq!(cw2::get_contract_version(storage).unwrap().version, "2.0.0");
+        let config = CONFIG.load(storage).unwrap();
+        assert_eq!(config.threshold_percent, Decimal::percent(50));
+        assert_eq!(config.slashable_spread, Decimal::percent(20));
+        assert_eq!(config.operator_contract, mock_operators.address().unwrap());
+        assert_eq!(config.commit_window, Decimal::percent(50));
+        assert_eq!(config.owner, chain.sender_addr());
+        assert_eq!(config.task_queue, None);
+        // the round in flight is dropped
+        assert!(TASKS.is_empty(storage));
+        assert!(VOTES.is_empty(storage));
+        assert!(v1::SLASHED_OPERATORS.is_empty(storage));
+    });
+
+    // and voted on again under commit-reveal, once the owner picked the task queue
+    serve_task_queue(&verifier, &tasker);
+    commit(&verifier, &tasker, &operator, task_id, &price(100));
+    chain.wait_seconds(COMMIT_PHASE).unwrap();
+    reveal(&verifier, &operator, task_id, price(100));
+    chain.wait_seconds(REVEAL_PHASE).unwrap();
+    verifier.process_votes(task_id).unwrap();
+    let task = tasker.task_info(task_id).unwrap();
+    assert!(matches!(task.status, Status::Completed { .. }));
+}
+
+#[test]
+fn migration_refuses_downgrades() {
+    let chain = MockBech32::new(BECH_PREFIX);
+    let operator = chain.alt_signer(3);
+    let mock_operators = setup_mock_operators(
+        chain.clone(),
+        vec![InstantiateOperator::new(operator.addr().to_string(), 100)],
+    );
+    let verifier = Contract::new(chain.clone());
+    verifier.upload().unwrap();
+    verifier
+        .instantiate(&instantiate_msg(&mock_operators, 50, 10, 20), Some(&chain.sender_addr()), &[])
+        .unwrap();
+
+    // 2.x only needs new code
+    verifier
+        .migrate(&MigrateMsg::Upgrade {}, verifier.code_id().unwrap())
+        .unwrap();
+
+    with_storage(&chain, &verifier.address().unwrap(), |storage| {
+        cw2::set_contract_version(storage, "crates.io:oracle-verifier", "3.0.0").unwrap();
+    });
+    let err = verifier
+        .migrate(&MigrateMsg::Upgrade {}, verifier.code_id().unwrap())
+        .unwrap_err();
+    let expected = ContractError::CannotDowngrade {
+        from: "3.0.0".to_string(),
+        to: "2.0.0".to_string(),
+    };
+    assert!(
+        err.root().to_string().contains(&expected.to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+}
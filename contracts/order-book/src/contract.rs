@@ -0,0 +1,364 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    coins, from_json, to_json_binary, Addr, BankMsg, Binary, CosmosMsg, Decimal, Deps, DepsMut, Empty, Env,
+    MessageInfo, Order as StorageOrder, Response, StdResult, Storage, Uint128, WasmMsg,
+};
+use cw2::set_contract_version;
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use cw_storage_plus::Bound;
+use cw_utils::{must_pay, nonpayable};
+use lavs_apis::id::TaskId;
+use lavs_conditional_tokens::msg::{
+    Config as ConditionalTokensConfig, ConditionResponse, Outcome, QueryMsg as ConditionalTokensQueryMsg,
+};
+use lavs_task_queue::msg::{MarketPhase, QueryMsg as TaskQueueQueryMsg, TaskInfoResponse};
+
+use crate::error::ContractError;
+use crate::msg::{ExecuteMsg, InstantiateMsg, OrderBookResponse, QueryMsg, ReceiveMsg};
+use crate::state::{book, price_key, Config, Order, Side, CONFIG, NEXT_ORDER_ID, ORDERS};
+
+// version info for migration info
+const CONTRACT_NAME: &str = "crates.io:order-book";
+const CONTRACT_VERSION: &str = "1.0.0";
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+/// Resting orders one new order may fill, so matching fits in a block's gas
+const MAX_FILLS: usize = 30;
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    let conditional_tokens = deps.api.addr_validate(&msg.conditional_tokens)?;
+    let conditional_config: ConditionalTokensConfig = deps
+        .querier
+        .query_wasm_smart(&conditional_tokens, &ConditionalTokensQueryMsg::Config {})?;
+    let config = Config {
+        conditional_tokens,
+        task_queue: conditional_config.task_queue,
+        denom: msg.denom,
+        min_shares: msg.min_shares,
+    };
+    CONFIG.save(deps.storage, &config)?;
+    NEXT_ORDER_ID.save(deps.storage, &1)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "instantiate")
+        .add_attribute("conditional_tokens", config.conditional_tokens))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::Bid { market, outcome, price, shares } => {
+            execute::bid(deps, env, info, market, outcome, price, shares)
+        }
+        ExecuteMsg::Receive(msg) => execute::receive(deps, env, info, msg),
+        ExecuteMsg::CancelOrder { order_id } => execute::cancel_order(deps, info, order_id),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => to_json_binary(&CONFIG.load(deps.storage)?),
+        QueryMsg::Order { order_id } => to_json_binary(&ORDERS.load(deps.storage, order_id)?),
+        QueryMsg::OrderBook { market, outcome, side, start_after, limit } => {
+            to_json_binary(&query::order_book(deps, market, outcome, side, start_after, limit)?)
+        }
+    }
+}
+
+mod execute {
+    use super::*;
+
+    pub fn bid(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        market: TaskId,
+        outcome: String,
+        price: Decimal,
+        shares: Uint128,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+        let paid = must_pay(&info, &config.denom)?;
+        validate_order(&config, price, shares)?;
+        let token = outcome_token(deps.as_ref(), &config, market, &outcome)?;
+        assert_trading(deps.as_ref(), &env, &config, market)?;
+
+        let locked = shares.mul_ceil(price);
+        if paid < locked {
+            return Err(ContractError::InsufficientFunds(locked));
+        }
+        let mut msgs = vec![];
+        if paid > locked {
+            msgs.push(bank_send(&info.sender, paid - locked, &config.denom));
+        }
+
+        let order = Order {
+            id: next_order_id(deps.storage)?,
+            owner: info.sender,
+            market,
+            outcome,
+            token,
+            side: Side::Bid,
+            price,
+            remaining: shares,
+            locked,
+        };
+        place(deps.storage, &config, order, msgs)
+    }
+
+    pub fn receive(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        wrapper: Cw20ReceiveMsg,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+        // only outcome tokens of the conditional tokens contract are accepted
+        let outcome: Outcome = deps.querier.query_wasm_smart(
+            &config.conditional_tokens,
+            &ConditionalTokensQueryMsg::Token {
+                address: info.sender.to_string(),
+            },
+        )?;
+        let owner = deps.api.addr_validate(&wrapper.sender)?;
+
+        match from_json(&wrapper.msg)? {
+            ReceiveMsg::Ask { price } => {
+                validate_order(&config, price, wrapper.amount)?;
+                assert_trading(deps.as_ref(), &env, &config, outcome.task_id)?;
+                let order = Order {
+                    id: next_order_id(deps.storage)?,
+                    owner,
+                    market: outcome.task_id,
+                    outcome: outcome.option,
+                    token: info.sender,
+                    side: Side::Ask,
+                    price,
+                    remaining: wrapper.amount,
+                    locked: Uint128::zero(),
+                };
+                place(deps.storage, &config, order, vec![])
+            }
+        }
+    }
+
+    pub fn cancel_order(deps: DepsMut, info: MessageInfo, order_id: u64) -> Result<Response, ContractError> {
+        nonpayable(&info)?;
+        let config = CONFIG.load(deps.storage)?;
+        let order = ORDERS
+            .may_load(deps.storage, order_id)?
+            .ok_or(ContractError::OrderNotFound(order_id))?;
+        if order.owner != info.sender {
+            return Err(ContractError::Unauthorized);
+        }
+        remove_order(deps.storage, &order);
+
+        let refund = match order.side {
+            Side::Bid => bank_send(&order.owner, order.locked, &config.denom),
+            Side::Ask => cw20_transfer(&order.token, &order.owner, order.remaining)?,
+        };
+        Ok(Response::new()
+            .add_message(refund)
+            .add_attribute("action", "cancel_order")
+            .add_attribute("order_id", order_id.to_string())
+            .add_attribute("owner", order.owner)
+            .add_attribute("remaining", order.remaining.to_string()))
+    }
+
+    /// Matches a new order against the other side of the book, at the resting orders' prices.
+    /// What is left rests on the book. After `MAX_FILLS` resting orders, a remainder that still
+    /// crosses the book is returned instead, as resting it would leave the book crossed
+    fn place(
+        storage: &mut dyn Storage,
+        config: &Config,
+        mut taker: Order,
+        mut msgs: Vec<CosmosMsg>,
+    ) -> Result<Response, ContractError> {
+        let side = taker.side;
+        let mut filled = Uint128::zero();
+        let mut fills = 0;
+        let mut capped = false;
+        // the best resting order is read again after each fill, as filled ones leave the book
+        while !taker.remaining.is_zero() {
+            let Some(maker_id) = best_order(storage, side.opposite(), taker.market, &taker.outcome)? else {
+                break;
+            };
+            let mut maker = ORDERS.load(storage, maker_id)?;
+            if !taker.crosses(maker.price) {
+                break;
+            }
+            if fills == MAX_FILLS {
+                capped = true;
+                break;
+            }
+            fills += 1;
+
+            let fill = taker.remaining.min(maker.remaining);
+            // sellers get the price rounded down, so a bid's locked funds always cover its fills
+            let payment = fill.mul_floor(maker.price);
+            let (buyer, seller) = match side {
+                Side::Bid => (&mut taker, &mut maker),
+                Side::Ask => (&mut maker, &mut taker),
+            };
+            buyer.locked -= payment;
+            msgs.push(cw20_transfer(&buyer.token, &buyer.owner, fill)?);
+            if !payment.is_zero() {
+                msgs.push(bank_send(&seller.owner, payment, &config.denom));
+            }
+            taker.remaining -= fill;
+            maker.remaining -= fill;
+            filled += fill;
+
+            if maker.remaining.is_zero() {
+                remove_order(storage, &maker);
+                if !maker.locked.is_zero() {
+                    msgs.push(bank_send(&maker.owner, maker.locked, &config.denom));
+                }
+            } else {
+                ORDERS.save(storage, maker.id, &maker)?;
+            }
+        }
+
+        let mut returned = Uint128::zero();
+        if taker.remaining.is_zero() || capped {
+            // bids filled below their price get the difference back
+            if !taker.locked.is_zero() {
+                msgs.push(bank_send(&taker.owner, taker.locked, &config.denom));
+            }
+            if capped && side == Side::Ask {
+                msgs.push(cw20_transfer(&taker.token, &taker.owner, taker.remaining)?);
+            }
+            returned = std::mem::take(&mut taker.remaining);
+        } else {
+            ORDERS.save(storage, taker.id, &taker)?;
+            book(taker.side).save(storage, taker.book_key(), &Empty {})?;
+        }
+
+        Ok(Response::new()
+            .add_messages(msgs)
+            .add_attribute("action", "place_order")
+            .add_attribute("order_id", taker.id.to_string())
+            .add_attribute("owner", taker.owner.to_string())
+            .add_attribute("filled", filled.to_string())
+            .add_attribute("remaining", taker.remaining.to_string())
+            .add_attribute("returned", returned.to_string()))
+    }
+
+    fn best_order(storage: &dyn Storage, side: Side, market: TaskId, outcome: &str) -> StdResult<Option<u64>> {
+        book(side)
+            .sub_prefix((market, outcome))
+            .keys(storage, None, None, StorageOrder::Ascending)
+            .next()
+            .transpose()
+            .map(|key| key.map(|(_, id)| id))
+    }
+
+    fn remove_order(storage: &mut dyn Storage, order: &Order) {
+        ORDERS.remove(storage, order.id);
+        book(order.side).remove(storage, order.book_key());
+    }
+
+    fn validate_order(config: &Config, price: Decimal, shares: Uint128) -> Result<(), ContractError> {
+        // a share never pays out more than one unit of collateral
+        if price.is_zero() || price > Decimal::one() {
+            return Err(ContractError::InvalidPrice(price));
+        }
+        if shares.is_zero() {
+            return Err(ContractError::ZeroShares);
+        }
+        if shares < config.min_shares {
+            return Err(ContractError::OrderTooSmall(config.min_shares));
+        }
+        Ok(())
+    }
+
+    fn next_order_id(storage: &mut dyn Storage) -> StdResult<u64> {
+        let id = NEXT_ORDER_ID.load(storage)?;
+        NEXT_ORDER_ID.save(storage, &(id + 1))?;
+        Ok(id)
+    }
+
+    fn outcome_token(deps: Deps, config: &Config, market: TaskId, outcome: &str) -> Result<Addr, ContractError> {
+        let condition: ConditionResponse = deps.querier.query_wasm_smart(
+            &config.conditional_tokens,
+            &ConditionalTokensQueryMsg::Condition { task_id: market },
+        )?;
+        condition
+            .outcomes
+            .into_iter()
+            .find(|token| token.option == outcome)
+            .map(|token| token.token)
+            .ok_or_else(|| ContractError::UnknownOutcome(outcome.to_string()))
+    }
+
+    /// Trading stops as soon as the task expires, leaves `Open` or is challenged
+    fn assert_trading(deps: Deps, env: &Env, config: &Config, market: TaskId) -> Result<(), ContractError> {
+        let task: TaskInfoResponse = deps
+            .querier
+            .query_wasm_smart(&config.task_queue, &TaskQueueQueryMsg::TaskInfo { task_id: market })?;
+        if task.market_phase(env) != MarketPhase::Open {
+            return Err(ContractError::TradingHalted);
+        }
+        Ok(())
+    }
+
+    fn bank_send(to: &Addr, amount: Uint128, denom: &str) -> CosmosMsg {
+        BankMsg::Send {
+            to_address: to.to_string(),
+            amount: coins(amount.u128(), denom),
+        }
+        .into()
+    }
+
+    fn cw20_transfer(token: &Addr, to: &Addr, amount: Uint128) -> StdResult<CosmosMsg> {
+        Ok(WasmMsg::Execute {
+            contract_addr: token.to_string(),
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: to.to_string(),
+                amount,
+            })?,
+            funds: vec![],
+        }
+        .into())
+    }
+}
+
+mod query {
+    use super::*;
+
+    pub fn order_book(
+        deps: Deps,
+        market: TaskId,
+        outcome: String,
+        side: Side,
+        start_after: Option<(Decimal, u64)>,
+        limit: Option<u32>,
+    ) -> StdResult<OrderBookResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let min = start_after.map(|(price, id)| Bound::exclusive((price_key(side, price), id)));
+
+        let orders = book(side)
+            .sub_prefix((market, outcome.as_str()))
+            .keys(deps.storage, min, None, StorageOrder::Ascending)
+            .take(limit)
+            .map(|key| ORDERS.load(deps.storage, key?.1))
+            .collect::<StdResult<Vec<_>>>()?;
+        Ok(OrderBookResponse { orders })
+    }
+}
@@ -0,0 +1,36 @@
+use cosmwasm_std::{Decimal, StdError, Uint128};
+use cw_utils::PaymentError;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("{0}")]
+    Payment(#[from] PaymentError),
+
+    #[error("Unauthorized")]
+    Unauthorized,
+
+    #[error("Price must be above 0 and at most 1, got {0}")]
+    InvalidPrice(Decimal),
+
+    #[error("Cannot trade zero shares")]
+    ZeroShares,
+
+    #[error("Orders must be for at least {0} shares")]
+    OrderTooSmall(Uint128),
+
+    #[error("Unknown outcome: {0}")]
+    UnknownOutcome(String),
+
+    #[error("Bid costs {0}, more than was sent")]
+    InsufficientFunds(Uint128),
+
+    #[error("Trading halted, the task is no longer open")]
+    TradingHalted,
+
+    #[error("Order not found: {0}")]
+    OrderNotFound(u64),
+}
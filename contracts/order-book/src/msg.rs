@@ -0,0 +1,74 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{Decimal, Uint128};
+use cw20::Cw20ReceiveMsg;
+use lavs_apis::id::TaskId;
+
+pub use crate::state::{Config, Order, Side};
+
+#[cw_serde]
+pub struct InstantiateMsg {
+    /// Conditional tokens contract whose outcome tokens are traded
+    pub conditional_tokens: String,
+    /// Native token shares are priced in
+    pub denom: String,
+    /// Smallest order accepted, in outcome token units
+    pub min_shares: Uint128,
+}
+
+#[cw_serde]
+#[derive(cw_orch::ExecuteFns)]
+#[cw_orch(disable_fields_sorting)]
+pub enum ExecuteMsg {
+    /// Buys up to `shares` outcome tokens at `price` or better. Must send `shares * price`,
+    /// rounded up. Whatever does not fill right away rests on the book, unless the fill cap
+    /// was reached while the book still crosses, in which case it is returned
+    #[cw_orch(payable)]
+    Bid {
+        market: TaskId,
+        outcome: String,
+        price: Decimal,
+        shares: Uint128,
+    },
+    /// Outcome tokens sent with a [`ReceiveMsg`]
+    Receive(Cw20ReceiveMsg),
+    /// Removes a resting order and returns what it still holds. Only the owner can cancel,
+    /// which also works once trading halted
+    CancelOrder {
+        order_id: u64,
+    },
+}
+
+#[cw_serde]
+pub enum ReceiveMsg {
+    /// Sells the sent outcome tokens at `price` or better.
+    /// Whatever does not fill right away rests on the book, or is returned as for bids
+    Ask { price: Decimal },
+}
+
+#[cw_serde]
+#[derive(QueryResponses, cw_orch::QueryFns)]
+#[cw_orch(disable_fields_sorting)]
+pub enum QueryMsg {
+    #[returns(Config)]
+    Config {},
+    #[returns(Order)]
+    Order {
+        order_id: u64,
+    },
+    /// Resting orders on one side of a market's outcome, best price first and oldest first
+    /// within a price. Pass the last order's price and id to get the next page, which works
+    /// even if that order was filled or cancelled in the meantime
+    #[returns(OrderBookResponse)]
+    OrderBook {
+        market: TaskId,
+        outcome: String,
+        side: Side,
+        start_after: Option<(Decimal, u64)>,
+        limit: Option<u32>,
+    },
+}
+
+#[cw_serde]
+pub struct OrderBookResponse {
+    pub orders: Vec<Order>,
+}
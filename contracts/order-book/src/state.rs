@@ -0,0 +1,94 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Decimal, Empty, Uint128};
+use cw_storage_plus::{Item, Map};
+use lavs_apis::id::TaskId;
+
+pub const CONFIG: Item<Config> = Item::new("config");
+pub const NEXT_ORDER_ID: Item<u64> = Item::new("next_order_id");
+pub const ORDERS: Map<u64, Order> = Map::new("orders");
+/// Resting bids by market and outcome, best first: highest price, then oldest
+pub const BIDS: Map<BookKey, Empty> = Map::new("bids");
+/// Resting asks by market and outcome, best first: lowest price, then oldest
+pub const ASKS: Map<BookKey, Empty> = Map::new("asks");
+
+/// ((task id, outcome), price key, order id)
+pub type BookKey<'a> = ((TaskId, &'a str), u128, u64);
+
+pub fn book(side: Side) -> Map<BookKey<'static>, Empty> {
+    match side {
+        Side::Bid => BIDS,
+        Side::Ask => ASKS,
+    }
+}
+
+/// Sorts a side of the book best price first
+pub fn price_key(side: Side, price: Decimal) -> u128 {
+    let atomics = price.atomics().u128();
+    match side {
+        Side::Bid => u128::MAX - atomics,
+        Side::Ask => atomics,
+    }
+}
+
+#[cw_serde]
+pub struct Config {
+    /// Where outcome tokens are looked up
+    pub conditional_tokens: Addr,
+    /// Task queue of the conditional tokens, which decides when trading halts
+    pub task_queue: Addr,
+    /// Native token shares are priced in
+    pub denom: String,
+    /// Smallest order accepted, so the book cannot be filled with dust
+    pub min_shares: Uint128,
+}
+
+#[cw_serde]
+#[derive(Copy)]
+pub enum Side {
+    /// Buys shares, paying in the native denom
+    Bid,
+    /// Sells shares for the native denom
+    Ask,
+}
+
+impl Side {
+    pub fn opposite(self) -> Side {
+        match self {
+            Side::Bid => Side::Ask,
+            Side::Ask => Side::Bid,
+        }
+    }
+}
+
+#[cw_serde]
+pub struct Order {
+    pub id: u64,
+    pub owner: Addr,
+    /// The task the outcome token is about
+    pub market: TaskId,
+    pub outcome: String,
+    /// The cw20 outcome token traded
+    pub token: Addr,
+    pub side: Side,
+    /// Native tokens per share
+    pub price: Decimal,
+    /// Shares not filled yet
+    pub remaining: Uint128,
+    /// Native tokens a bid still holds, refunded once it is filled or cancelled. Zero for asks
+    pub locked: Uint128,
+}
+
+impl Order {
+    /// Orders a side of the book by price, best first, and then by id, oldest first
+    pub fn book_key(&self) -> BookKey<'_> {
+        ((self.market, &self.outcome), price_key(self.side, self.price), self.id)
+    }
+
+    /// Whether this order trades with a resting order at `price`
+    pub fn crosses(&self, price: Decimal) -> bool {
+        match self.side {
+            Side::Bid => price <= self.price,
+            Side::Ask => price >= self.price,
+        }
+    }
+}
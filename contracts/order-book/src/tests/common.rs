@@ -0,0 +1,326 @@
+use cosmwasm_std::{coins, to_json_binary, Addr, Decimal, Empty, Uint128};
+use cw20::BalanceResponse;
+use cw20_base::msg::{ExecuteMsg as Cw20ExecuteMsg, InstantiateMsg as Cw20InstantiateMsg, QueryMsg as Cw20QueryMsg};
+use cw_orch::environment::{ChainState, CwEnv};
+use cw_orch::{interface, prelude::*};
+
+use lavs_apis::id::TaskId;
+use lavs_apis::tasks::{Requestor, TimeoutInfo};
+use lavs_orch::{Addressable, AltSigner};
+use serde_json::json;
+
+use lavs_conditional_tokens::{
+    interface::Contract as ConditionalTokensContract,
+    msg::{
+        ExecuteMsgFns as ConditionalTokensExecuteMsgFns, InstantiateMsg as ConditionalTokensInstantiateMsg,
+        QueryMsgFns as ConditionalTokensQueryMsgFns,
+    },
+};
+use lavs_task_queue::{
+    interface::Contract as TasksContract,
+    msg::{ExecuteMsgFns as TasksExecuteMsgFns, InstantiateMsg as TasksInstantiateMsg},
+};
+
+use crate::interface::Contract;
+use crate::msg::{ExecuteMsgFns, InstantiateMsg, QueryMsgFns, ReceiveMsg, Side};
+use crate::ContractError;
+
+pub const BECH_PREFIX: &str = "slay3r";
+pub const DENOM: &str = "uslay";
+/// Signer that resolves tasks in place of a real verifier
+const VERIFIER_INDEX: u32 = 9;
+const TIMEOUT: u64 = 600;
+const MIN_SHARES: u128 = 10;
+
+/// cw20-base, to upload it and interact with the outcome tokens
+#[interface(Cw20InstantiateMsg, Cw20ExecuteMsg, Cw20QueryMsg, Empty)]
+pub struct Cw20;
+
+impl<Chain> Uploadable for Cw20<Chain> {
+    fn wrapper() -> Box<dyn MockContract<Empty>> {
+        Box::new(ContractWrapper::new_with_empty(
+            cw20_base::contract::execute,
+            cw20_base::contract::instantiate,
+            cw20_base::contract::query,
+        ))
+    }
+}
+
+/// The task queue, conditional tokens and order book, wired together
+pub struct Setup<C: CwEnv> {
+    pub tasker: TasksContract<C>,
+    pub conditional: ConditionalTokensContract<C>,
+    pub order_book: Contract<C>,
+}
+
+pub fn setup<C>(chain: C) -> Setup<C>
+where
+    C: CwEnv + AltSigner,
+    C::Sender: Addressable,
+{
+    let msg = TasksInstantiateMsg {
+        requestor: Requestor::Fixed(chain.sender_addr().into()),
+        timeout: TimeoutInfo::new(TIMEOUT),
+        verifier: chain.alt_signer(VERIFIER_INDEX).addr().to_string(),
+        dispute: None,
+        arbitrator: None,
+        cancel_fee: None,
+        keeper_reward: None,
+    };
+    let tasker = TasksContract::new(chain.clone());
+    tasker.upload().unwrap();
+    tasker.instantiate(&msg, None, &[]).unwrap();
+
+    let cw20 = Cw20::new("cw20_base", chain.clone());
+    cw20.upload().unwrap();
+    let msg = ConditionalTokensInstantiateMsg {
+        task_queue: tasker.addr_str().unwrap(),
+        collateral_denom: DENOM.to_string(),
+        cw20_code_id: cw20.code_id().unwrap(),
+    };
+    let conditional = ConditionalTokensContract::new(chain.clone());
+    conditional.upload().unwrap();
+    conditional.instantiate(&msg, None, &[]).unwrap();
+
+    let msg = InstantiateMsg {
+        conditional_tokens: conditional.addr_str().unwrap(),
+        denom: DENOM.to_string(),
+        min_shares: Uint128::new(MIN_SHARES),
+    };
+    let order_book = Contract::new(chain);
+    order_book.upload().unwrap();
+    order_book.instantiate(&msg, None, &[]).unwrap();
+
+    Setup {
+        tasker,
+        conditional,
+        order_book,
+    }
+}
+
+pub fn orders_match_by_price_and_time<C>(chain: C)
+where
+    C: CwEnv + AltSigner,
+    C::Sender: Addressable,
+{
+    let seller = chain.alt_signer(1);
+    let buyer1 = chain.alt_signer(2);
+    let buyer2 = chain.alt_signer(3);
+    let verifier = chain.alt_signer(VERIFIER_INDEX);
+
+    let Setup {
+        tasker,
+        conditional,
+        order_book,
+    } = setup(chain.clone());
+    let market = make_task(&tasker);
+    conditional.prepare(market).unwrap();
+    let yes = token_at(&chain, &conditional.condition(market).unwrap().outcomes[0].token);
+    conditional.call_as(&seller).split(market, &coins(100, DENOM)).unwrap();
+
+    // two asks, the cheaper one placed last
+    ask(&order_book, &yes, &seller, 30, "0.6").unwrap();
+    ask(&order_book, &yes, &seller, 30, "0.5").unwrap();
+    let asks = order_book
+        .order_book(market, "yes".to_string(), Side::Ask, None, None)
+        .unwrap()
+        .orders;
+    assert_eq!(asks.len(), 2);
+    assert_eq!(asks[0].price, price("0.5"));
+
+    let err = order_book
+        .call_as(&buyer1)
+        .bid(market, "yes".to_string(), price("1.5"), Uint128::new(10), &coins(15, DENOM))
+        .unwrap_err();
+    assert!(
+        err.root()
+            .to_string()
+            .contains(&ContractError::InvalidPrice(price("1.5")).to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+
+    let err = order_book
+        .call_as(&buyer1)
+        .bid(market, "yes".to_string(), price("0.5"), Uint128::new(5), &coins(3, DENOM))
+        .unwrap_err();
+    assert!(
+        err.root()
+            .to_string()
+            .contains(&ContractError::OrderTooSmall(Uint128::new(MIN_SHARES)).to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+
+    // the bid takes all of the 0.5 ask, the rest rests on the book at 0.55
+    let res = order_book
+        .call_as(&buyer1)
+        .bid(market, "yes".to_string(), price("0.55"), Uint128::new(40), &coins(22, DENOM))
+        .unwrap();
+    assert_eq!(res.event_attr_value("wasm", "filled").unwrap(), "30");
+    assert_eq!(balance(&yes, &buyer1.addr()), 30);
+    let bids = order_book
+        .order_book(market, "yes".to_string(), Side::Bid, None, None)
+        .unwrap()
+        .orders;
+    assert_eq!(bids.len(), 1);
+    assert_eq!(bids[0].remaining, Uint128::new(10));
+    // 15 of the 22 paid went to the seller
+    assert_eq!(bids[0].locked, Uint128::new(7));
+
+    order_book
+        .call_as(&buyer2)
+        .bid(market, "yes".to_string(), price("0.4"), Uint128::new(10), &coins(4, DENOM))
+        .unwrap();
+
+    // bids are sorted best first, and can be paged through
+    let page = order_book
+        .order_book(market, "yes".to_string(), Side::Bid, None, Some(1))
+        .unwrap()
+        .orders;
+    assert_eq!(page.len(), 1);
+    assert_eq!(page[0].owner, buyer1.addr());
+    let page = order_book
+        .order_book(market, "yes".to_string(), Side::Bid, Some((page[0].price, page[0].id)), None)
+        .unwrap()
+        .orders;
+    assert_eq!(page.len(), 1);
+    assert_eq!(page[0].owner, buyer2.addr());
+    let buyer2_order = page[0].id;
+
+    // an ask at 0.4 fills the best bid first and partially fills the next one
+    let res = ask(&order_book, &yes, &seller, 15, "0.4").unwrap();
+    assert_eq!(res.event_attr_value("wasm", "filled").unwrap(), "15");
+    assert_eq!(balance(&yes, &buyer1.addr()), 40);
+    assert_eq!(balance(&yes, &buyer2.addr()), 5);
+    let order = order_book.order(buyer2_order).unwrap();
+    assert_eq!(order.remaining, Uint128::new(5));
+
+    let err = order_book.call_as(&buyer1).cancel_order(buyer2_order).unwrap_err();
+    assert!(
+        err.root().to_string().contains(&ContractError::Unauthorized.to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+    order_book.call_as(&buyer2).cancel_order(buyer2_order).unwrap();
+    let bids = order_book
+        .order_book(market, "yes".to_string(), Side::Bid, None, None)
+        .unwrap()
+        .orders;
+    assert!(bids.is_empty());
+    // paging on from an order that left the book still works
+    let bids = order_book
+        .order_book(market, "yes".to_string(), Side::Bid, Some((price("0.4"), buyer2_order)), None)
+        .unwrap()
+        .orders;
+    assert!(bids.is_empty());
+
+    // once the task resolves, trading halts but orders can still be cancelled
+    tasker
+        .call_as(&verifier)
+        .resolve_outcome(market, "yes".to_string())
+        .unwrap();
+    let err = order_book
+        .call_as(&buyer1)
+        .bid(market, "yes".to_string(), price("0.9"), Uint128::new(10), &coins(9, DENOM))
+        .unwrap_err();
+    assert!(
+        err.root().to_string().contains(&ContractError::TradingHalted.to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+    let asks = order_book
+        .order_book(market, "yes".to_string(), Side::Ask, None, None)
+        .unwrap()
+        .orders;
+    assert_eq!(asks.len(), 1);
+    order_book.call_as(&seller).cancel_order(asks[0].id).unwrap();
+    assert_eq!(balance(&yes, &seller.addr()), 100 - 30 - 15);
+}
+
+pub fn trading_halts_at_expiry<C>(chain: C)
+where
+    C: CwEnv + AltSigner,
+    C::Sender: Addressable,
+{
+    let seller = chain.alt_signer(1);
+
+    let Setup {
+        tasker,
+        conditional,
+        order_book,
+    } = setup(chain.clone());
+    let market = make_task(&tasker);
+    conditional.prepare(market).unwrap();
+    let yes = token_at(&chain, &conditional.condition(market).unwrap().outcomes[0].token);
+    conditional.call_as(&seller).split(market, &coins(100, DENOM)).unwrap();
+
+    chain.wait_seconds(TIMEOUT + 1).unwrap();
+    let err = ask(&order_book, &yes, &seller, 30, "0.5").unwrap_err();
+    assert!(
+        err.root().to_string().contains(&ContractError::TradingHalted.to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+}
+
+/// Sends outcome tokens to the order book to sell them
+pub fn ask<C>(
+    order_book: &Contract<C>,
+    token: &Cw20<C>,
+    owner: &C::Sender,
+    amount: u128,
+    limit: &str,
+) -> Result<C::Response, CwOrchError>
+where
+    C: CwEnv,
+{
+    token.call_as(owner).execute(
+        &Cw20ExecuteMsg::Send {
+            contract: order_book.addr_str().unwrap(),
+            amount: Uint128::new(amount),
+            msg: to_json_binary(&ReceiveMsg::Ask { price: price(limit) }).unwrap(),
+        },
+        &[],
+    )
+}
+
+pub fn price(value: &str) -> Decimal {
+    value.parse().unwrap()
+}
+
+pub fn token_at<C: CwEnv>(chain: &C, address: &Addr) -> Cw20<C> {
+    let token = Cw20::new(address.to_string(), chain.clone());
+    token.set_address(address);
+    token
+}
+
+pub fn balance<C: CwEnv>(token: &Cw20<C>, owner: &Addr) -> u128 {
+    let res: BalanceResponse = token
+        .query(&Cw20QueryMsg::Balance {
+            address: owner.to_string(),
+        })
+        .unwrap();
+    res.balance.u128()
+}
+
+#[track_caller]
+pub fn make_task<C: ChainState + TxHandler>(tasker: &TasksContract<C>) -> TaskId {
+    let res = tasker
+        .create(
+            "Will it rain?".to_string(),
+            None,
+            json!({"question": "Will it rain?"}),
+            vec!["yes".to_string(), "no".to_string()],
+            String::new(),
+            None,
+            &[],
+        )
+        .unwrap();
+    let id = res
+        .event_attr_value("wasm", "task_id")
+        .unwrap()
+        .parse()
+        .unwrap();
+    TaskId::new(id)
+}
@@ -0,0 +1,25 @@
+use cosmwasm_std::coins;
+use cw_orch::prelude::MockBech32;
+use lavs_orch::{Addressable, AltSigner};
+
+use super::common::{BECH_PREFIX, DENOM};
+
+fn funded_chain() -> MockBech32 {
+    let chain = MockBech32::new(BECH_PREFIX);
+    for i in [1, 2, 3] {
+        chain
+            .add_balance(&chain.alt_signer(i).addr(), coins(1000, DENOM))
+            .unwrap();
+    }
+    chain
+}
+
+#[test]
+fn orders_match_by_price_and_time() {
+    super::common::orders_match_by_price_and_time(funded_chain());
+}
+
+#[test]
+fn trading_halts_at_expiry() {
+    super::common::trading_halts_at_expiry(funded_chain());
+}
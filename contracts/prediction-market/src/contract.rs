@@ -0,0 +1,281 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use std::str::FromStr;
+
+use cosmwasm_std::{
+    coins, to_json_binary, BankMsg, Binary, Decimal, Deps, DepsMut, Env, MessageInfo, Order, Response, StdResult,
+    Uint128,
+};
+use cw2::set_contract_version;
+use cw_utils::{must_pay, nonpayable};
+use lavs_apis::id::TaskId;
+use lavs_task_queue::msg::{MarketPhase, QueryMsg as TaskQueueQueryMsg, Status, TaskInfoResponse, TaskPayload};
+
+use crate::error::ContractError;
+use crate::msg::{BetsResponse, ExecuteMsg, InstantiateMsg, MarketResponse, OptionStake, QueryMsg};
+use crate::state::{Config, Market, ScalarRange, BETS, CONFIG, LONG, MARKETS, POOLS, SHORT};
+
+// version info for migration info
+const CONTRACT_NAME: &str = "crates.io:prediction-market";
+const CONTRACT_VERSION: &str = "1.0.0";
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    let config = Config {
+        task_queue: deps.api.addr_validate(&msg.task_queue)?,
+        denom: msg.denom,
+    };
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "instantiate")
+        .add_attribute("task_queue", config.task_queue))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::CreateMarket { task_id } => execute::create_market(deps, env, info, task_id),
+        ExecuteMsg::Bet { task_id, option } => execute::bet(deps, env, info, task_id, option),
+        ExecuteMsg::Claim { task_id } => execute::claim(deps, env, info, task_id),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => to_json_binary(&CONFIG.load(deps.storage)?),
+        QueryMsg::Market { task_id } => to_json_binary(&query::market(deps, task_id)?),
+        QueryMsg::Bets { task_id, bettor } => to_json_binary(&query::bets(deps, task_id, bettor)?),
+    }
+}
+
+mod execute {
+    use super::*;
+
+    pub fn create_market(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        task_id: TaskId,
+    ) -> Result<Response, ContractError> {
+        nonpayable(&info)?;
+        if MARKETS.has(deps.storage, task_id) {
+            return Err(ContractError::MarketExists(task_id.u64()));
+        }
+
+        let task = load_task(deps.as_ref(), task_id)?;
+        // scalar tasks have no options, their markets bet on which end of the range is closer
+        let (options, scalar) = match task.kind {
+            Some(TaskPayload::Scalar { min, max, .. }) => {
+                (vec![LONG.to_string(), SHORT.to_string()], Some(ScalarRange { min, max }))
+            }
+            _ if task.options.is_empty() => return Err(ContractError::NotCategorical),
+            _ => (task.options, None),
+        };
+        if task.market_phase(&env) != MarketPhase::Open {
+            return Err(ContractError::BettingClosed);
+        }
+
+        let market = Market {
+            creator: info.sender,
+            options,
+            total: Uint128::zero(),
+            scalar,
+        };
+        MARKETS.save(deps.storage, task_id, &market)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "create_market")
+            .add_attribute("task_id", task_id.to_string())
+            .add_attribute("creator", market.creator))
+    }
+
+    pub fn bet(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        task_id: TaskId,
+        option: String,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+        let amount = must_pay(&info, &config.denom)?;
+        let mut market = MARKETS
+            .may_load(deps.storage, task_id)?
+            .ok_or(ContractError::MarketNotFound(task_id.u64()))?;
+        if !market.options.contains(&option) {
+            return Err(ContractError::UnknownOption(option));
+        }
+        let task = load_task(deps.as_ref(), task_id)?;
+        if task.market_phase(&env) != MarketPhase::Open {
+            return Err(ContractError::BettingClosed);
+        }
+
+        BETS.update(deps.storage, (task_id, &info.sender, &option), |staked| -> StdResult<_> {
+            Ok(staked.unwrap_or_default() + amount)
+        })?;
+        let pool = POOLS.update(deps.storage, (task_id, &option), |pool| -> StdResult<_> {
+            Ok(pool.unwrap_or_default() + amount)
+        })?;
+        market.total += amount;
+        MARKETS.save(deps.storage, task_id, &market)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "bet")
+            .add_attribute("task_id", task_id.to_string())
+            .add_attribute("bettor", info.sender)
+            .add_attribute("option", option)
+            .add_attribute("amount", amount.to_string())
+            .add_attribute("pool", pool.to_string())
+            .add_attribute("total", market.total.to_string()))
+    }
+
+    pub fn claim(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        task_id: TaskId,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+        let market = MARKETS
+            .may_load(deps.storage, task_id)?
+            .ok_or(ContractError::MarketNotFound(task_id.u64()))?;
+        let task = load_task(deps.as_ref(), task_id)?;
+
+        let bets = BETS
+            .prefix((task_id, &info.sender))
+            .range(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        if bets.is_empty() {
+            return Err(ContractError::NothingToClaim);
+        }
+        let staked: Uint128 = bets.iter().map(|(_, amount)| *amount).sum();
+
+        let payout = match task.market_phase(&env) {
+            MarketPhase::Open | MarketPhase::Pending => return Err(ContractError::MarketNotSettled),
+            // a completed scalar task has a result but no winning option
+            MarketPhase::Expired => match (&market.scalar, scalar_result(&task)) {
+                (Some(range), Some(result)) => scalar_payout(deps.as_ref(), task_id, &market, range, result, &bets)?
+                    .unwrap_or(staked),
+                _ => staked,
+            },
+            MarketPhase::Resolved(winner) => {
+                let winning_pool = POOLS.may_load(deps.storage, (task_id, &winner))?.unwrap_or_default();
+                if winning_pool.is_zero() {
+                    // nobody picked the winner, so there is nobody to pay out to
+                    staked
+                } else {
+                    // parimutuel: winners share the whole market by stake, rounding down
+                    bets.iter()
+                        .filter(|(option, _)| option == &winner)
+                        .map(|(_, amount)| amount.multiply_ratio(market.total, winning_pool))
+                        .sum()
+                }
+            }
+        };
+        for (option, _) in bets.iter() {
+            BETS.remove(deps.storage, (task_id, &info.sender, option));
+        }
+
+        let mut res = Response::new()
+            .add_attribute("action", "claim")
+            .add_attribute("task_id", task_id.to_string())
+            .add_attribute("bettor", info.sender.to_string())
+            .add_attribute("staked", staked.to_string())
+            .add_attribute("payout", payout.to_string());
+        if !payout.is_zero() {
+            res = res.add_message(BankMsg::Send {
+                to_address: info.sender.to_string(),
+                amount: coins(payout.u128(), config.denom),
+            });
+        }
+        Ok(res)
+    }
+
+    /// Long bets share `position` of the market and short bets the rest, each side by stake,
+    /// rounding down. `None` if either side is empty, as there is nobody to win from
+    fn scalar_payout(
+        deps: Deps,
+        task_id: TaskId,
+        market: &Market,
+        range: &ScalarRange,
+        result: Decimal,
+        bets: &[(String, Uint128)],
+    ) -> StdResult<Option<Uint128>> {
+        let long_pool = POOLS.may_load(deps.storage, (task_id, LONG))?.unwrap_or_default();
+        let short_pool = POOLS.may_load(deps.storage, (task_id, SHORT))?.unwrap_or_default();
+        if long_pool.is_zero() || short_pool.is_zero() {
+            return Ok(None);
+        }
+        let long_share = market.total.mul_floor(range.position(result));
+        let short_share = market.total - long_share;
+        let payout = bets
+            .iter()
+            .map(|(option, amount)| match option.as_str() {
+                LONG => amount.multiply_ratio(long_share, long_pool),
+                _ => amount.multiply_ratio(short_share, short_pool),
+            })
+            .sum();
+        Ok(Some(payout))
+    }
+
+    /// The verifier completes numeric tasks with `{"price": <decimal>}`
+    fn scalar_result(task: &TaskInfoResponse) -> Option<Decimal> {
+        if !matches!(task.status, Status::Completed { .. }) {
+            return None;
+        }
+        let result = task.result.as_ref()?.get("price")?.as_str()?;
+        Decimal::from_str(result).ok()
+    }
+
+    fn load_task(deps: Deps, task_id: TaskId) -> StdResult<TaskInfoResponse> {
+        let config = CONFIG.load(deps.storage)?;
+        deps.querier
+            .query_wasm_smart(config.task_queue, &TaskQueueQueryMsg::TaskInfo { task_id })
+    }
+}
+
+mod query {
+    use super::*;
+
+    pub fn market(deps: Deps, task_id: TaskId) -> StdResult<MarketResponse> {
+        let market = MARKETS.load(deps.storage, task_id)?;
+        let pools = market
+            .options
+            .into_iter()
+            .map(|option| -> StdResult<_> {
+                let amount = POOLS.may_load(deps.storage, (task_id, &option))?.unwrap_or_default();
+                Ok(OptionStake { option, amount })
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+        Ok(MarketResponse {
+            task_id,
+            creator: market.creator,
+            pools,
+            total: market.total,
+            scalar: market.scalar,
+        })
+    }
+
+    pub fn bets(deps: Deps, task_id: TaskId, bettor: String) -> StdResult<BetsResponse> {
+        let bettor = deps.api.addr_validate(&bettor)?;
+        let bets = BETS
+            .prefix((task_id, &bettor))
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| item.map(|(option, amount)| OptionStake { option, amount }))
+            .collect::<StdResult<Vec<_>>>()?;
+        Ok(BetsResponse { bets })
+    }
+}
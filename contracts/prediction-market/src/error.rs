@@ -0,0 +1,33 @@
+use cosmwasm_std::StdError;
+use cw_utils::PaymentError;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("{0}")]
+    Payment(#[from] PaymentError),
+
+    #[error("A market already exists for task {0}")]
+    MarketExists(u64),
+
+    #[error("No market for task {0}")]
+    MarketNotFound(u64),
+
+    #[error("Markets need a task with options")]
+    NotCategorical,
+
+    #[error("Unknown option: {0}")]
+    UnknownOption(String),
+
+    #[error("Betting is closed, the task is no longer open")]
+    BettingClosed,
+
+    #[error("The task is still open, the market is not settled yet")]
+    MarketNotSettled,
+
+    #[error("Nothing to claim")]
+    NothingToClaim,
+}
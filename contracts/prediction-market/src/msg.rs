@@ -0,0 +1,76 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{Addr, Uint128};
+use lavs_apis::id::TaskId;
+
+pub use crate::state::{Config, ScalarRange, LONG, SHORT};
+
+#[cw_serde]
+pub struct InstantiateMsg {
+    /// Address of the task queue whose tasks settle the markets
+    pub task_queue: String,
+    /// Native token bets are placed in
+    pub denom: String,
+}
+
+#[cw_serde]
+#[derive(cw_orch::ExecuteFns)]
+#[cw_orch(disable_fields_sorting)]
+pub enum ExecuteMsg {
+    /// Opens a market on a task of the task queue. The task must be open and have options,
+    /// or be a scalar task, whose market takes bets on `long` and `short`
+    CreateMarket {
+        task_id: TaskId,
+    },
+    /// Bets the sent tokens on one of the task's options. Only while the task is open
+    #[cw_orch(payable)]
+    Bet {
+        task_id: TaskId,
+        option: String,
+    },
+    /// Pays out the sender's share of the market once the task completed,
+    /// or refunds the sender's bets if it expired. Scalar markets are split along the range
+    Claim {
+        task_id: TaskId,
+    },
+}
+
+#[cw_serde]
+#[derive(QueryResponses, cw_orch::QueryFns)]
+#[cw_orch(disable_fields_sorting)]
+pub enum QueryMsg {
+    #[returns(Config)]
+    Config {},
+    /// The market on a task, with what is staked on each option
+    #[returns(MarketResponse)]
+    Market {
+        task_id: TaskId,
+    },
+    /// What a bettor has staked on a market and not claimed yet
+    #[returns(BetsResponse)]
+    Bets {
+        task_id: TaskId,
+        bettor: String,
+    },
+}
+
+#[cw_serde]
+pub struct MarketResponse {
+    pub task_id: TaskId,
+    pub creator: Addr,
+    /// Every option of the task, in the task's order
+    pub pools: Vec<OptionStake>,
+    pub total: Uint128,
+    /// The range of a scalar task, whose options are `long` and `short`
+    pub scalar: Option<ScalarRange>,
+}
+
+#[cw_serde]
+pub struct BetsResponse {
+    pub bets: Vec<OptionStake>,
+}
+
+#[cw_serde]
+pub struct OptionStake {
+    pub option: String,
+    pub amount: Uint128,
+}
@@ -0,0 +1,49 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Decimal, Uint128};
+use cw_storage_plus::{Item, Map};
+use lavs_apis::id::TaskId;
+
+pub const CONFIG: Item<Config> = Item::new("config");
+pub const MARKETS: Map<TaskId, Market> = Map::new("markets");
+/// Total staked on each option of a market
+pub const POOLS: Map<(TaskId, &str), Uint128> = Map::new("pools");
+/// The sides of a scalar market: long wins as the result nears `max`, short as it nears `min`
+pub const LONG: &str = "long";
+pub const SHORT: &str = "short";
+
+/// What each bettor staked on each option of a market. Removed once claimed
+pub const BETS: Map<(TaskId, &Addr, &str), Uint128> = Map::new("bets");
+
+#[cw_serde]
+pub struct Config {
+    /// The task queue whose tasks settle the markets
+    pub task_queue: Addr,
+    /// Native token all bets are placed in
+    pub denom: String,
+}
+
+#[cw_serde]
+pub struct Market {
+    pub creator: Addr,
+    /// The task's options, copied when the market was created. `long` and `short` for scalar tasks
+    pub options: Vec<String>,
+    /// Everything staked on the market, paid out to the winning side
+    pub total: Uint128,
+    /// The range of a scalar task, which the market is split along
+    pub scalar: Option<ScalarRange>,
+}
+
+#[cw_serde]
+pub struct ScalarRange {
+    pub min: Decimal,
+    pub max: Decimal,
+}
+
+impl ScalarRange {
+    /// How far the result lies from `min` towards `max`, between 0 and 1.
+    /// Results outside the range count as its closest end
+    pub fn position(&self, result: Decimal) -> Decimal {
+        let result = result.clamp(self.min, self.max);
+        (result - self.min) / (self.max - self.min)
+    }
+}
@@ -0,0 +1,380 @@
+use cosmwasm_std::{coin, coins, Decimal, Uint128};
+use cw_orch::environment::{ChainState, CwEnv};
+use cw_orch::prelude::*;
+
+use lavs_apis::id::TaskId;
+use lavs_apis::tasks::{Requestor, TimeoutInfo};
+use lavs_orch::{Addressable, AltSigner};
+use serde_json::json;
+
+use lavs_task_queue::{
+    interface::Contract as TasksContract,
+    msg::{DisputeConfig, ExecuteMsgFns as TasksExecuteMsgFns, InstantiateMsg as TasksInstantiateMsg},
+};
+
+use crate::interface::Contract;
+use crate::msg::{ExecuteMsgFns, InstantiateMsg, OptionStake, QueryMsgFns, LONG, SHORT};
+use crate::ContractError;
+
+pub const BECH_PREFIX: &str = "slay3r";
+pub const DENOM: &str = "uslay";
+/// Signer that resolves tasks in place of a real verifier
+const VERIFIER_INDEX: u32 = 9;
+const TIMEOUT: u64 = 600;
+const DISPUTE_WINDOW: u64 = 100;
+
+pub fn setup<Chain: CwEnv>(chain: Chain, msg: InstantiateMsg) -> Contract<Chain> {
+    let contract = Contract::new(chain);
+    contract.upload().unwrap();
+    contract.instantiate(&msg, None, &[]).unwrap();
+    contract
+}
+
+pub fn winners_share_the_market<C>(chain: C)
+where
+    C: CwEnv + AltSigner,
+    C::Sender: Addressable,
+{
+    let bettor1 = chain.alt_signer(1);
+    let bettor2 = chain.alt_signer(2);
+    let bettor3 = chain.alt_signer(3);
+    let verifier = chain.alt_signer(VERIFIER_INDEX);
+
+    let tasker = setup_task_queue(chain.clone(), None);
+    let market = setup(chain.clone(), instantiate_msg(&tasker));
+
+    // numeric tasks have nothing to bet on
+    let numeric = make_task(&tasker, vec![]);
+    let err = market.create_market(numeric).unwrap_err();
+    assert!(
+        err.root().to_string().contains(&ContractError::NotCategorical.to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+
+    let task_id = make_task(&tasker, vec!["yes".to_string(), "no".to_string()]);
+    market.create_market(task_id).unwrap();
+    let err = market.create_market(task_id).unwrap_err();
+    assert!(
+        err.root()
+            .to_string()
+            .contains(&ContractError::MarketExists(task_id.u64()).to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+
+    let err = market
+        .call_as(&bettor1)
+        .bet(task_id, "maybe".to_string(), &coins(100, DENOM))
+        .unwrap_err();
+    assert!(
+        err.root()
+            .to_string()
+            .contains(&ContractError::UnknownOption("maybe".to_string()).to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+
+    market.call_as(&bettor1).bet(task_id, "yes".to_string(), &coins(100, DENOM)).unwrap();
+    market.call_as(&bettor2).bet(task_id, "no".to_string(), &coins(300, DENOM)).unwrap();
+    market.call_as(&bettor3).bet(task_id, "yes".to_string(), &coins(100, DENOM)).unwrap();
+
+    let info = market.market(task_id).unwrap();
+    assert_eq!(info.total, Uint128::new(500));
+    assert_eq!(info.pools, vec![stake("yes", 200), stake("no", 300)]);
+
+    // nothing to claim while the task is open
+    let err = market.call_as(&bettor1).claim(task_id).unwrap_err();
+    assert!(
+        err.root().to_string().contains(&ContractError::MarketNotSettled.to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+
+    tasker
+        .call_as(&verifier)
+        .resolve_outcome(task_id, "yes".to_string())
+        .unwrap();
+    let err = market
+        .call_as(&bettor2)
+        .bet(task_id, "no".to_string(), &coins(100, DENOM))
+        .unwrap_err();
+    assert!(
+        err.root().to_string().contains(&ContractError::BettingClosed.to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+
+    // the two yes bettors split all 500
+    let res = market.call_as(&bettor1).claim(task_id).unwrap();
+    assert_eq!(res.event_attr_value("wasm", "payout").unwrap(), "250");
+    let res = market.call_as(&bettor3).claim(task_id).unwrap();
+    assert_eq!(res.event_attr_value("wasm", "payout").unwrap(), "250");
+    let res = market.call_as(&bettor2).claim(task_id).unwrap();
+    assert_eq!(res.event_attr_value("wasm", "payout").unwrap(), "0");
+
+    let bets = market.bets(task_id, bettor1.addr().to_string()).unwrap().bets;
+    assert!(bets.is_empty());
+    let err = market.call_as(&bettor1).claim(task_id).unwrap_err();
+    assert!(
+        err.root().to_string().contains(&ContractError::NothingToClaim.to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+}
+
+pub fn scalar_markets_split_along_the_range<C>(chain: C)
+where
+    C: CwEnv + AltSigner,
+    C::Sender: Addressable,
+{
+    let bettor1 = chain.alt_signer(1);
+    let bettor2 = chain.alt_signer(2);
+    let bettor3 = chain.alt_signer(3);
+    let verifier = chain.alt_signer(VERIFIER_INDEX);
+
+    let tasker = setup_task_queue(chain.clone(), None);
+    let market = setup(chain.clone(), instantiate_msg(&tasker));
+
+    let res = tasker
+        .create(
+            "Rainfall".to_string(),
+            None,
+            json!({"scalar": {"min": "0", "max": "100", "unit": "mm"}}),
+            vec![],
+            String::new(),
+            None,
+            &[],
+        )
+        .unwrap();
+    let task_id = TaskId::new(res.event_attr_value("wasm", "task_id").unwrap().parse().unwrap());
+    market.create_market(task_id).unwrap();
+
+    let err = market
+        .call_as(&bettor1)
+        .bet(task_id, "yes".to_string(), &coins(100, DENOM))
+        .unwrap_err();
+    assert!(
+        err.root()
+            .to_string()
+            .contains(&ContractError::UnknownOption("yes".to_string()).to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+    market.call_as(&bettor1).bet(task_id, LONG.to_string(), &coins(100, DENOM)).unwrap();
+    market.call_as(&bettor2).bet(task_id, SHORT.to_string(), &coins(300, DENOM)).unwrap();
+    market.call_as(&bettor3).bet(task_id, LONG.to_string(), &coins(100, DENOM)).unwrap();
+
+    let info = market.market(task_id).unwrap();
+    assert_eq!(info.pools, vec![stake(LONG, 200), stake(SHORT, 300)]);
+    let range = info.scalar.unwrap();
+    assert_eq!((range.min, range.max), (Decimal::zero(), Decimal::percent(10000)));
+
+    let err = market.call_as(&bettor1).claim(task_id).unwrap_err();
+    assert!(
+        err.root().to_string().contains(&ContractError::MarketNotSettled.to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+
+    // 30 is 30% of the way up the range, so long gets 150 of the 500 and short 350
+    tasker
+        .call_as(&verifier)
+        .complete_task(task_id, json!({"price": "30"}))
+        .unwrap();
+    let res = market.call_as(&bettor1).claim(task_id).unwrap();
+    assert_eq!(res.event_attr_value("wasm", "payout").unwrap(), "75");
+    let res = market.call_as(&bettor3).claim(task_id).unwrap();
+    assert_eq!(res.event_attr_value("wasm", "payout").unwrap(), "75");
+    let res = market.call_as(&bettor2).claim(task_id).unwrap();
+    assert_eq!(res.event_attr_value("wasm", "payout").unwrap(), "350");
+}
+
+pub fn expired_tasks_refund<C>(chain: C)
+where
+    C: CwEnv + AltSigner,
+    C::Sender: Addressable,
+{
+    let bettor1 = chain.alt_signer(1);
+    let bettor2 = chain.alt_signer(2);
+
+    let tasker = setup_task_queue(chain.clone(), None);
+    let market = setup(chain.clone(), instantiate_msg(&tasker));
+
+    let task_id = make_task(&tasker, vec!["yes".to_string(), "no".to_string()]);
+    market.create_market(task_id).unwrap();
+    market.call_as(&bettor1).bet(task_id, "yes".to_string(), &coins(100, DENOM)).unwrap();
+    market.call_as(&bettor1).bet(task_id, "no".to_string(), &coins(50, DENOM)).unwrap();
+    market.call_as(&bettor2).bet(task_id, "no".to_string(), &coins(300, DENOM)).unwrap();
+
+    let bets = market.bets(task_id, bettor1.addr().to_string()).unwrap().bets;
+    assert_eq!(bets, vec![stake("no", 50), stake("yes", 100)]);
+
+    // once the task times out, it can no longer be resolved and everyone gets their bets back
+    chain.wait_seconds(TIMEOUT + 1).unwrap();
+    let err = market
+        .call_as(&bettor2)
+        .bet(task_id, "no".to_string(), &coins(100, DENOM))
+        .unwrap_err();
+    assert!(
+        err.root().to_string().contains(&ContractError::BettingClosed.to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+
+    let res = market.call_as(&bettor1).claim(task_id).unwrap();
+    assert_eq!(res.event_attr_value("wasm", "payout").unwrap(), "150");
+    let res = market.call_as(&bettor2).claim(task_id).unwrap();
+    assert_eq!(res.event_attr_value("wasm", "payout").unwrap(), "300");
+}
+
+pub fn payouts_wait_for_the_dispute_window<C>(chain: C)
+where
+    C: CwEnv + AltSigner,
+    C::Sender: Addressable,
+{
+    let bettor1 = chain.alt_signer(1);
+    let bettor2 = chain.alt_signer(2);
+    let verifier = chain.alt_signer(VERIFIER_INDEX);
+
+    let dispute = DisputeConfig {
+        window: DISPUTE_WINDOW,
+        bond: coin(100, DENOM),
+    };
+    let tasker = setup_task_queue(chain.clone(), Some(dispute));
+    let market = setup(chain.clone(), instantiate_msg(&tasker));
+
+    let task_id = make_task(&tasker, vec!["yes".to_string(), "no".to_string()]);
+    market.create_market(task_id).unwrap();
+    market.call_as(&bettor1).bet(task_id, "yes".to_string(), &coins(100, DENOM)).unwrap();
+    market.call_as(&bettor2).bet(task_id, "no".to_string(), &coins(300, DENOM)).unwrap();
+
+    // the result can still be challenged, so betting is closed but nothing pays out yet
+    tasker
+        .call_as(&verifier)
+        .resolve_outcome(task_id, "yes".to_string())
+        .unwrap();
+    let err = market
+        .call_as(&bettor2)
+        .bet(task_id, "no".to_string(), &coins(100, DENOM))
+        .unwrap_err();
+    assert!(
+        err.root().to_string().contains(&ContractError::BettingClosed.to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+    let err = market.call_as(&bettor1).claim(task_id).unwrap_err();
+    assert!(
+        err.root().to_string().contains(&ContractError::MarketNotSettled.to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+
+    // the window closing is not enough, the result has to be finalized
+    chain.wait_seconds(DISPUTE_WINDOW).unwrap();
+    let err = market.call_as(&bettor1).claim(task_id).unwrap_err();
+    assert!(
+        err.root().to_string().contains(&ContractError::MarketNotSettled.to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+    tasker.finalize(task_id).unwrap();
+
+    let res = market.call_as(&bettor1).claim(task_id).unwrap();
+    assert_eq!(res.event_attr_value("wasm", "payout").unwrap(), "400");
+    let res = market.call_as(&bettor2).claim(task_id).unwrap();
+    assert_eq!(res.event_attr_value("wasm", "payout").unwrap(), "0");
+}
+
+pub fn invalid_tasks_refund<C>(chain: C)
+where
+    C: CwEnv + AltSigner,
+    C::Sender: Addressable,
+{
+    let bettor1 = chain.alt_signer(1);
+    let bettor2 = chain.alt_signer(2);
+    let verifier = chain.alt_signer(VERIFIER_INDEX);
+
+    let tasker = setup_task_queue(chain.clone(), None);
+    let market = setup(chain.clone(), instantiate_msg(&tasker));
+
+    let task_id = make_task(&tasker, vec!["yes".to_string(), "no".to_string()]);
+    market.create_market(task_id).unwrap();
+    market.call_as(&bettor1).bet(task_id, "yes".to_string(), &coins(100, DENOM)).unwrap();
+    market.call_as(&bettor2).bet(task_id, "no".to_string(), &coins(300, DENOM)).unwrap();
+
+    // the question turned out unanswerable, so nobody wins and everyone gets their bets back
+    tasker
+        .call_as(&verifier)
+        .invalidate_task(task_id, "Event was cancelled".to_string())
+        .unwrap();
+    let err = market
+        .call_as(&bettor2)
+        .bet(task_id, "no".to_string(), &coins(100, DENOM))
+        .unwrap_err();
+    assert!(
+        err.root().to_string().contains(&ContractError::BettingClosed.to_string()),
+        "Unexpected error: {}",
+        err.root()
+    );
+
+    let res = market.call_as(&bettor1).claim(task_id).unwrap();
+    assert_eq!(res.event_attr_value("wasm", "payout").unwrap(), "100");
+    let res = market.call_as(&bettor2).claim(task_id).unwrap();
+    assert_eq!(res.event_attr_value("wasm", "payout").unwrap(), "300");
+}
+
+pub fn instantiate_msg<C: CwEnv>(tasker: &TasksContract<C>) -> InstantiateMsg {
+    InstantiateMsg {
+        task_queue: tasker.addr_str().unwrap(),
+        denom: DENOM.to_string(),
+    }
+}
+
+pub fn stake(option: &str, amount: u128) -> OptionStake {
+    OptionStake {
+        option: option.to_string(),
+        amount: Uint128::new(amount),
+    }
+}
+
+#[track_caller]
+pub fn make_task<C: ChainState + TxHandler>(tasker: &TasksContract<C>, options: Vec<String>) -> TaskId {
+    let res = tasker
+        .create(
+            "Will it rain?".to_string(),
+            None,
+            json!({"question": "Will it rain?"}),
+            options,
+            String::new(),
+            None,
+            &[],
+        )
+        .unwrap();
+    let id = res
+        .event_attr_value("wasm", "task_id")
+        .unwrap()
+        .parse()
+        .unwrap();
+    TaskId::new(id)
+}
+
+pub fn setup_task_queue<C>(chain: C, dispute: Option<DisputeConfig>) -> TasksContract<C>
+where
+    C: CwEnv + AltSigner,
+    C::Sender: Addressable,
+{
+    let msg = TasksInstantiateMsg {
+        requestor: Requestor::Fixed(chain.sender_addr().into()),
+        timeout: TimeoutInfo::new(TIMEOUT),
+        verifier: chain.alt_signer(VERIFIER_INDEX).addr().to_string(),
+        dispute,
+        arbitrator: None,
+        cancel_fee: None,
+        keeper_reward: None,
+    };
+    let tasker = TasksContract::new(chain);
+    tasker.upload().unwrap();
+    tasker.instantiate(&msg, None, &[]).unwrap();
+    tasker
+}
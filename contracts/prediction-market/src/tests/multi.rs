@@ -0,0 +1,40 @@
+use cosmwasm_std::coins;
+use cw_orch::prelude::MockBech32;
+use lavs_orch::{Addressable, AltSigner};
+
+use super::common::{BECH_PREFIX, DENOM};
+
+fn funded_chain() -> MockBech32 {
+    let chain = MockBech32::new(BECH_PREFIX);
+    for i in [1, 2, 3] {
+        chain
+            .add_balance(&chain.alt_signer(i).addr(), coins(1000, DENOM))
+            .unwrap();
+    }
+    chain
+}
+
+#[test]
+fn winners_share_the_market() {
+    super::common::winners_share_the_market(funded_chain());
+}
+
+#[test]
+fn scalar_markets_split_along_the_range() {
+    super::common::scalar_markets_split_along_the_range(funded_chain());
+}
+
+#[test]
+fn expired_tasks_refund() {
+    super::common::expired_tasks_refund(funded_chain());
+}
+
+#[test]
+fn invalid_tasks_refund() {
+    super::common::invalid_tasks_refund(funded_chain());
+}
+
+#[test]
+fn payouts_wait_for_the_dispute_window() {
+    super::common::payouts_wait_for_the_dispute_window(funded_chain());
+}
@@ -1,19 +1,35 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Uint128, Decimal,
-    CosmosMsg, WasmMsg, Order, OrderBy,
+    to_json_binary, BankMsg, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, WasmMsg, Order, Reply,
+    StdError, SubMsg,
 };
-use cw2::set_contract_version;
+use cw2::{get_contract_version, set_contract_version};
+use semver::Version;
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use crate::migration;
+use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, RequestType, ResponseType, Status, TaskInfoResponse};
 use crate::state::{Config, Task, TASKS, CONFIG};
 use lavs_apis::tasks::{ListOpenResponse, TaskMetadata};
-use serde::Deserialize;
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:task-queue";
-const CONTRACT_VERSION: &str = "1.0.0";
+const CONTRACT_VERSION: &str = "2.0.0";
+
+/// Reply to `RequestArbitration`, only sent if the arbitrator rejects it
+pub const ARBITRATION_REPLY_ID: u64 = 1;
+
+/// Reply to `TaskResolved`, only sent if the callback contract fails or runs out of gas
+pub const CALLBACK_REPLY_ID: u64 = 2;
+
+/// Most tasks `ExpireBatch` handles in one call
+const MAX_EXPIRE_BATCH: u32 = 30;
+
+/// Most tasks of 1.x rewritten in one call, during `migrate` or with `MigrateTasks`
+pub const MAX_MIGRATE_BATCH: u32 = 100;
+
+/// Most gas a task's callback may use. Whoever finalizes the task pays for it
+pub const MAX_CALLBACK_GAS: u64 = 1_000_000;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -23,7 +39,7 @@ pub fn instantiate(
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
-    let config = Config::validate(deps, msg)?;
+    let config = Config::validate(deps.as_ref(), info.sender, msg)?;
     CONFIG.save(deps.storage, &config)?;
     Ok(Response::new().add_attribute("action", "instantiate"))
 }
@@ -42,26 +58,119 @@ pub fn execute(
             payload,
             options,
             proposed_winner,
-        } => execute::create_task(deps, env, info, description, timeout, payload, options, proposed_winner),
+            callback,
+        } => execute::create_task(deps, env, info, description, timeout, payload, options, proposed_winner, callback),
         ExecuteMsg::CompleteTask { task_id, result } => execute::complete_task(deps, env, info, task_id, result),
+        ExecuteMsg::ResolveOutcome { task_id, winner } => execute::resolve_outcome(deps, env, info, task_id, winner),
         ExecuteMsg::ExpireTask { task_id } => execute::expire_task(deps, env, info, task_id),
+        ExecuteMsg::ExpireBatch { limit } => execute::expire_batch(deps, env, info, limit),
+        ExecuteMsg::CancelTask { task_id } => execute::cancel_task(deps, env, info, task_id),
+        ExecuteMsg::Challenge { task_id, proposed_result } => {
+            execute::challenge(deps, env, info, task_id, proposed_result)
+        }
+        ExecuteMsg::Finalize { task_id } => execute::finalize(deps, env, info, task_id),
+        ExecuteMsg::InvalidateTask { task_id, reason } => {
+            execute::invalidate_task(deps, env, info, task_id, reason)
+        }
+        ExecuteMsg::Escalate { task_id } => execute::escalate(deps, env, info, task_id),
+        ExecuteMsg::Arbitrate { task_id, result } => execute::arbitrate(deps, env, info, task_id, result),
+        ExecuteMsg::UpdateConfig { requestor, timeout, verifier, guardian } => {
+            execute::update_config(deps, info, requestor, timeout, verifier, guardian)
+        }
+        ExecuteMsg::TransferOwnership { owner } => execute::transfer_ownership(deps, info, owner),
+        ExecuteMsg::AcceptOwnership {} => execute::accept_ownership(deps, info),
+        ExecuteMsg::Pause {} => execute::set_paused(deps, info, true),
+        ExecuteMsg::Unpause {} => execute::set_paused(deps, info, false),
+        ExecuteMsg::MigrateTasks { limit } => execute::migrate_tasks(deps, info, limit),
+    }
+}
+
+/// Upgrades the state of any older release of this contract, step by step. Downgrades are refused,
+/// as older code cannot read what newer releases store
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+    if stored.contract != CONTRACT_NAME {
+        return Err(ContractError::WrongContract(stored.contract));
+    }
+    let from: Version = stored.version.parse()?;
+    let to: Version = CONTRACT_VERSION.parse()?;
+    if from > to {
+        return Err(ContractError::CannotDowngrade {
+            from: stored.version,
+            to: CONTRACT_VERSION.to_string(),
+        });
+    }
+
+    let mut res = Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("from", stored.version)
+        .add_attribute("to", CONTRACT_VERSION);
+    if from.major < 2 {
+        // only the contract admin can migrate, so it becomes the owner too
+        let owner = deps
+            .querier
+            .query_wasm_contract_info(&env.contract.address)?
+            .admin
+            .ok_or(ContractError::Unauthorized)?;
+        let limit = msg.task_limit.unwrap_or(MAX_MIGRATE_BATCH).min(MAX_MIGRATE_BATCH) as usize;
+        let done = migration::v1_to_v2(deps.storage, &env, owner, limit)?;
+        res = res.add_attribute("tasks_migrated", done.to_string());
+    }
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    Ok(res)
+}
+
+/// The arbitrator may be a plain account or a multisig that does not understand
+/// `RequestArbitration`. The task stays escalated either way.
+/// Likewise, a failing callback contract cannot hold up the task it is told about
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(_deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match msg.id {
+        ARBITRATION_REPLY_ID => Ok(Response::new()
+            .add_attribute("action", "request_arbitration")
+            .add_attribute("delivered", "false")),
+        CALLBACK_REPLY_ID => Ok(Response::new()
+            .add_attribute("action", "task_resolved")
+            .add_attribute("delivered", "false")),
+        id => Err(StdError::generic_err(format!("Unknown reply id: {}", id)).into()),
     }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
-        QueryMsg::ListOpen { start_after, limit } => to_binary(&query::list_open(deps, env, start_after, limit)?),
-        QueryMsg::TaskInfo { task_id } => to_binary(&query::task_info(deps, env, task_id)?),
+        QueryMsg::ListOpen { start_after, limit, order } => {
+            to_json_binary(&query::list_open(deps, env, start_after, limit, order)?)
+        }
+        QueryMsg::ListCompleted { start_after, limit, order } => {
+            to_json_binary(&query::list_completed(deps, env, start_after, limit, order)?)
+        }
+        QueryMsg::ListExpired { start_after, limit, order } => {
+            to_json_binary(&query::list_expired(deps, env, start_after, limit, order)?)
+        }
+        QueryMsg::TaskInfo { task_id } => to_json_binary(&query::task_info(deps, env, task_id)?),
+        QueryMsg::Config {} => to_json_binary(&CONFIG.load(deps.storage)?),
+        QueryMsg::ListByRequestor { requestor, start_after, limit } => {
+            to_json_binary(&query::list_by_requestor(deps, env, requestor, start_after, limit)?)
+        }
     }
 }
 
 mod execute {
-    use cw_utils::nonpayable;
     use lavs_apis::id::TaskId;
 
-    use crate::state::{check_timeout, Timing};
-    use crate::msg::CreateTaskMsg;
+    use cosmwasm_std::{Coin, Coins, Decimal, Event};
+    use cw_storage_plus::Bound;
+    use cw_utils::{must_pay, nonpayable};
+
+    use crate::msg::{
+        ArbitratorExecuteMsg, CallbackExecuteMsg, CallbackInfo, Requestor, TimeoutInfo, VerifierQueryMsg,
+        VoteCountResponse,
+    };
+
+    use crate::state::{check_timeout, validate_timeout_info, Callback, RequestorConfig, Timing};
 
     use super::*;
 
@@ -74,21 +183,39 @@ mod execute {
         payload: RequestType,
         options: Vec<String>,
         proposed_winner: String,
+        callback: Option<CallbackInfo>,
     ) -> Result<Response, ContractError> {
-        nonpayable(&info)?;
         let mut config = CONFIG.load(deps.storage)?;
+        if config.paused {
+            return Err(ContractError::Paused);
+        }
         let timeout = check_timeout(&config.timeout, timeout)?;
-        config.requestor.check_requestor(&info)?;
-        let task_id = TaskId::new(deps.storage);
+        let payment = config.requestor.check_requestor(&info)?;
+        let callback = callback
+            .map(|CallbackInfo { contract, gas_limit }| -> Result<_, ContractError> {
+                if gas_limit == 0 || gas_limit > MAX_CALLBACK_GAS {
+                    return Err(ContractError::InvalidCallbackGasLimit(MAX_CALLBACK_GAS));
+                }
+                let contract = deps.api.addr_validate(&contract)?;
+                Ok(Callback { contract, gas_limit })
+            })
+            .transpose()?;
 
         let task = Task::new(
+            &env,
+            info.sender,
             description,
             timeout,
             payload,
             options,
             proposed_winner,
-        );
+            payment,
+            callback,
+        )?;
 
+        let task_id = config.next_id;
+        config.next_id = TaskId::new(task_id.u64() + 1);
+        CONFIG.save(deps.storage, &config)?;
         TASKS.save(deps.storage, task_id.clone(), &task)?;
 
         Ok(Response::new()
@@ -110,338 +237,1897 @@ mod execute {
             return Err(ContractError::Unauthorized);
         }
 
-        TASKS.update(deps.storage, task_id.clone(), |mut task| -> Result<_, ContractError> {
-            task.complete(&env, result)?;
+        let task = TASKS.update(deps.storage, task_id.clone(), |task| -> Result<_, ContractError> {
+            let mut task = task.ok_or(ContractError::TaskNotFound)?;
+            task.complete(&env, result, config.dispute.as_ref())?;
             Ok(task)
         })?;
 
         Ok(Response::new()
+            .add_messages(pay_verifier(&config, &task))
+            .add_messages(settle_challenge(&config, &task))
+            .add_submessages(notify(task_id.clone(), &task)?)
             .add_attribute("action", "complete_task")
             .add_attribute("task_id", task_id.to_string())
-            .add_attribute("result", "verified"))
+            .add_attribute("result", "verified")
+            .add_attribute("status", task.status.as_str()))
     }
 
-    pub fn expire_task(
+    pub fn resolve_outcome(
         deps: DepsMut,
         env: Env,
         info: MessageInfo,
         task_id: TaskId,
+        winner: String,
     ) -> Result<Response, ContractError> {
         let config = CONFIG.load(deps.storage)?;
 
-        // Only requestor can expire tasks
-        config.requestor.check_requestor(&info)?;
+        // Ensure that only the Oracle Verifier can resolve tasks
+        if info.sender != config.verifier {
+            return Err(ContractError::Unauthorized);
+        }
 
-        TASKS.update(deps.storage, task_id.clone(), |mut task| -> Result<_, ContractError> {
-            task.expire(&env)?;
+        let task = TASKS.update(deps.storage, task_id.clone(), |task| -> Result<_, ContractError> {
+            let mut task = task.ok_or(ContractError::TaskNotFound)?;
+            task.resolve(&env, winner.clone(), config.dispute.as_ref())?;
             Ok(task)
         })?;
 
         Ok(Response::new()
-            .add_attribute("action", "expire_task")
-            .add_attribute("task_id", task_id.to_string()))
+            .add_messages(pay_verifier(&config, &task))
+            .add_messages(settle_challenge(&config, &task))
+            .add_submessages(notify(task_id.clone(), &task)?)
+            .add_attribute("action", "resolve_outcome")
+            .add_attribute("task_id", task_id.to_string())
+            .add_attribute("winner", winner)
+            .add_attribute("status", task.status.as_str()))
     }
-}
 
-mod query {
-    use super::*;
-    use cosmwasm_std::Order;
+    /// The task's payment goes to the verifier, which shares it among the operators.
+    /// It is paid with the first result, a second round after a challenge is not paid again
+    fn pay_verifier(config: &Config, task: &Task) -> Option<BankMsg> {
+        if task.challenge.is_some() {
+            return None;
+        }
+        task.payment.clone().map(|payment| BankMsg::Send {
+            to_address: config.verifier.to_string(),
+            amount: vec![payment],
+        })
+    }
 
-    pub fn list_open(
-        deps: Deps,
-        env: Env,
-        start_after: Option<TaskId>,
-        limit: Option<u32>,
-    ) -> Result<ListOpenResponse, ContractError> {
-        let limit = limit.unwrap_or(10) as usize;
-        let tasks: StdResult<Vec<TaskMetadata>> = TASKS
-            .range(deps.storage, None, None, Order::Ascending)
-            .filter(|item| {
-                if let Ok((_, task)) = item {
-                    matches!(task.status, Status::Open {})
-                } else {
-                    false
-                }
-            })
-            .skip_while(|item| {
-                if let Ok((id, _)) = item {
-                    if let Some(start_id) = &start_after {
-                        return &id.0 <= &start_id.0;
-                    }
-                }
-                false
-            })
-            .take(limit)
-            .map(|item| {
-                item.map(|(id, task)| TaskMetadata {
-                    id,
-                    description: task.description,
-                    status: task.status,
-                    timing: task.timing,
-                    payload: task.payload.clone(),
-                    result: task.result.clone(),
-                })
-            })
-            .collect();
+    /// Once a challenged task completes, the challenger gets the bond back if the result
+    /// was overturned. Otherwise the bond goes to the verifier. Its operators share it if a
+    /// second round confirmed the result; if the arbitrator did, the verifier's owner withdraws it
+    fn settle_challenge(config: &Config, task: &Task) -> Option<BankMsg> {
+        let upheld = task.challenge_upheld()?;
+        let challenge = task.challenge.as_ref()?;
+        let to_address = if upheld {
+            challenge.challenger.to_string()
+        } else {
+            config.verifier.to_string()
+        };
+        Some(BankMsg::Send {
+            to_address,
+            amount: vec![challenge.bond.clone()],
+        })
+    }
 
-        Ok(ListOpenResponse { tasks: tasks? })
+    /// Tells the task's callback contract once it is final. Results held back for
+    /// a dispute window are only reported once `Finalize` makes them final
+    fn notify(task_id: TaskId, task: &Task) -> StdResult<Option<SubMsg>> {
+        let callback = match &task.callback {
+            Some(callback) if task.status.is_final() => callback,
+            _ => return Ok(None),
+        };
+        let msg = WasmMsg::Execute {
+            contract_addr: callback.contract.to_string(),
+            msg: to_json_binary(&CallbackExecuteMsg::TaskResolved {
+                task_id,
+                status: task.status.clone(),
+                result: task.result.clone(),
+                outcome: task.outcome.clone(),
+            })?,
+            funds: vec![],
+        };
+        Ok(Some(
+            SubMsg::reply_on_error(msg, CALLBACK_REPLY_ID).with_gas_limit(callback.gas_limit),
+        ))
     }
 
-    pub fn task_info(
-        deps: Deps,
+    pub fn challenge(
+        deps: DepsMut,
         env: Env,
+        info: MessageInfo,
         task_id: TaskId,
-    ) -> Result<TaskMetadata, ContractError> {
-        let task = TASKS.may_load(deps.storage, task_id.clone())?.ok_or(ContractError::TaskNotFound)?;
+        proposed_result: ResponseType,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+        let dispute = config.dispute.ok_or(ContractError::DisputesDisabled)?;
+        let paid = must_pay(&info, &dispute.bond.denom)?;
+        if paid < dispute.bond.amount {
+            return Err(ContractError::InsufficientPayment(dispute.bond.amount, dispute.bond.denom));
+        }
 
-        Ok(TaskMetadata {
-            id: task_id,
-            description: task.description,
-            status: task.status,
-            timing: task.timing,
-            payload: task.payload,
-            result: task.result,
-        })
+        let bond = Coin::new(paid, dispute.bond.denom);
+        let task = TASKS.update(deps.storage, task_id.clone(), |task| -> Result<_, ContractError> {
+            let mut task = task.ok_or(ContractError::TaskNotFound)?;
+            task.challenge(&env, info.sender.clone(), bond.clone(), proposed_result)?;
+            Ok(task)
+        })?;
+
+        Ok(Response::new()
+            .add_attribute("action", "challenge")
+            .add_attribute("task_id", task_id.to_string())
+            .add_attribute("challenger", info.sender)
+            .add_attribute("bond", bond.to_string())
+            .add_attribute("expires_at", task.timing.expires_at.to_string()))
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-    use cosmwasm_std::{Addr, from_binary, coins, Uint128};
-    use lavs_apis::id::TaskId;
-    use lavs_apis::tasks::{ResponseType, RequestType};
+    pub fn finalize(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        task_id: TaskId,
+    ) -> Result<Response, ContractError> {
+        nonpayable(&info)?;
+        let task = TASKS.update(deps.storage, task_id.clone(), |task| -> Result<_, ContractError> {
+            let mut task = task.ok_or(ContractError::TaskNotFound)?;
+            task.finalize(&env)?;
+            Ok(task)
+        })?;
 
-    #[test]
-    fn test_instantiate_task_queue() {
-        let mut deps = mock_dependencies();
-        let msg = InstantiateMsg {
-            requestor: "requestor".to_string(),
-            verifier: "verifier".to_string(),
-            timeout: 3600,
-        };
-        let info = mock_info("creator", &coins(1000, "earth"));
-        let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
-        assert_eq!(res.attributes.len(), 1);
-        assert_eq!(res.attributes[0].value, "instantiate");
+        Ok(Response::new()
+            .add_submessages(notify(task_id.clone(), &task)?)
+            .add_attribute("action", "finalize")
+            .add_attribute("task_id", task_id.to_string()))
     }
 
-    #[test]
-    fn test_create_task() {
-        let mut deps = mock_dependencies();
-        let msg = InstantiateMsg {
-            requestor: "requestor".to_string(),
-            verifier: "verifier".to_string(),
-            timeout: 3600,
-        };
-        let info = mock_info("requestor", &coins(1000, "earth"));
-        let res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
-        assert_eq!(res.attributes.len(), 1);
-        assert_eq!(res.attributes[0].value, "instantiate");
-       
-        let create_msg = ExecuteMsg::Create {
-            description: "Will Team A win?".to_string(),
-            timeout: Some(7200),
-            payload: RequestType::Json("{\"event\":\"Team A vs Team B\"}".to_string()),
-            options: vec!["Team A".to_string(), "Team B".to_string()],
-            proposed_winner: "Team A".to_string(),
-        };
-        let res = execute(deps.as_mut(), mock_env(), info.clone(), create_msg).unwrap();
-        assert_eq!(res.attributes.len(), 2);
-        assert_eq!(res.attributes[0].value, "create_task");
-        assert_eq!(res.attributes[1].value, "task_id");
+    pub fn invalidate_task(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        task_id: TaskId,
+        reason: String,
+    ) -> Result<Response, ContractError> {
+        nonpayable(&info)?;
+        let config = CONFIG.load(deps.storage)?;
 
-        // Verify task creation
-        let task_id = TaskId::new(1);
-        let query_msg = QueryMsg::TaskInfo { task_id: task_id.clone() };
-        let res = query(deps.as_ref(), mock_env(), query_msg).unwrap();
-        let task: TaskMetadata = from_binary(&res).unwrap();
-        assert_eq!(task.id, task_id);
-        assert_eq!(task.description, "Will Team A win?".to_string());
-        assert!(matches!(task.status, Status::Open {}));
+        let task = TASKS.update(deps.storage, task_id.clone(), |task| -> Result<_, ContractError> {
+            let mut task = task.ok_or(ContractError::TaskNotFound)?;
+            // Once escalated, only the arbitrator decides
+            let authorized = match task.status {
+                Status::Arbitration { .. } => config.arbitrator.as_ref() == Some(&info.sender),
+                _ => info.sender == config.verifier,
+            };
+            if !authorized {
+                return Err(ContractError::Unauthorized);
+            }
+            task.invalidate(&env, reason.clone())?;
+            Ok(task)
+        })?;
+
+        // The operators did the work of finding out, so they are paid like for any other result
+        Ok(Response::new()
+            .add_messages(pay_verifier(&config, &task))
+            .add_messages(settle_challenge(&config, &task))
+            .add_submessages(notify(task_id.clone(), &task)?)
+            .add_attribute("action", "invalidate_task")
+            .add_attribute("task_id", task_id.to_string())
+            .add_attribute("reason", reason))
     }
 
-    #[test]
-    fn test_complete_task_success() {
-        let mut deps = mock_dependencies();
-        let msg = InstantiateMsg {
-            requestor: "requestor".to_string(),
-            verifier: "verifier".to_string(),
-            timeout: 3600,
-        };
-        let info = mock_info("requestor", &coins(1000, "earth"));
-        instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    pub fn escalate(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        task_id: TaskId,
+    ) -> Result<Response, ContractError> {
+        nonpayable(&info)?;
+        let config = CONFIG.load(deps.storage)?;
+        let arbitrator = config.arbitrator.ok_or(ContractError::NoArbitrator)?;
 
-        // Create a task
-        let create_msg = ExecuteMsg::Create {
-            description: "Will Team A win?".to_string(),
-            timeout: Some(7200),
-            payload: RequestType::Json("{\"event\":\"Team A vs Team B\"}".to_string()),
-            options: vec!["Team A".to_string(), "Team B".to_string()],
-            proposed_winner: "Team A".to_string(),
-        };
-        execute(deps.as_mut(), mock_env(), info.clone(), create_msg).unwrap();
+        let mut task = TASKS.may_load(deps.storage, task_id.clone())?.ok_or(ContractError::TaskNotFound)?;
+        // The verifier knows when the second round failed, everyone else has to wait for it to expire
+        if info.sender != config.verifier && !task.timing.is_expired(&env) {
+            return Err(ContractError::Unauthorized);
+        }
+        task.escalate(&env)?;
+        TASKS.save(deps.storage, task_id.clone(), &task)?;
 
-        // Complete the task as Oracle Verifier
-        let complete_msg = ExecuteMsg::CompleteTask {
-            task_id: TaskId::new(1),
-            result: ResponseType::Json("{\"winner\":\"Team A\"}".to_string()),
+        let challenge = task.challenge.ok_or(ContractError::NotChallenged)?;
+        let request = WasmMsg::Execute {
+            contract_addr: arbitrator.to_string(),
+            msg: to_json_binary(&ArbitratorExecuteMsg::RequestArbitration {
+                task_queue: env.contract.address.to_string(),
+                task_id: task_id.clone(),
+                description: task.description,
+                payload: task.payload,
+                options: task.options,
+                disputed: challenge.disputed,
+                proposed_result: challenge.proposed_result,
+            })?,
+            funds: vec![],
         };
-        let verifier_info = mock_info("verifier", &[]);
-        let res = execute(deps.as_mut(), mock_env(), verifier_info, complete_msg).unwrap();
-        assert_eq!(res.attributes.len(), 3);
-        assert_eq!(res.attributes[0].value, "complete_task");
-        assert_eq!(res.attributes[1].value, "task_id");
-        assert_eq!(res.attributes[2].value, "verified");
 
-        // Verify task completion
-        let query_msg = QueryMsg::TaskInfo { task_id: TaskId::new(1) };
-        let res = query(deps.as_ref(), mock_env(), query_msg).unwrap();
-        let task: TaskMetadata = from_binary(&res).unwrap();
-        assert!(matches!(task.status, Status::Completed { .. }));
-        assert_eq!(task.result.unwrap(), ResponseType::Json("{\"winner\":\"Team A\"}".to_string()));
+        Ok(Response::new()
+            .add_submessage(SubMsg::reply_on_error(request, ARBITRATION_REPLY_ID))
+            .add_attribute("action", "escalate")
+            .add_attribute("task_id", task_id.to_string())
+            .add_attribute("arbitrator", arbitrator))
     }
 
-    #[test]
-    fn test_complete_task_unauthorized() {
-        let mut deps = mock_dependencies();
-        let msg = InstantiateMsg {
-            requestor: "requestor".to_string(),
-            verifier: "verifier".to_string(),
-            timeout: 3600,
-        };
-        let info = mock_info("requestor", &coins(1000, "earth"));
-        instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    pub fn arbitrate(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        task_id: TaskId,
+        result: ResponseType,
+    ) -> Result<Response, ContractError> {
+        nonpayable(&info)?;
+        let config = CONFIG.load(deps.storage)?;
+        if config.arbitrator.as_ref() != Some(&info.sender) {
+            return Err(ContractError::Unauthorized);
+        }
 
-        // Create a task
-        let create_msg = ExecuteMsg::Create {
-            description: "Will Team A win?".to_string(),
-            timeout: Some(7200),
-            payload: RequestType::Json("{\"event\":\"Team A vs Team B\"}".to_string()),
-            options: vec!["Team A".to_string(), "Team B".to_string()],
-            proposed_winner: "Team A".to_string(),
-        };
-        execute(deps.as_mut(), mock_env(), info.clone(), create_msg).unwrap();
+        let task = TASKS.update(deps.storage, task_id.clone(), |task| -> Result<_, ContractError> {
+            let mut task = task.ok_or(ContractError::TaskNotFound)?;
+            task.arbitrate(&env, result)?;
+            Ok(task)
+        })?;
 
-        // Attempt to complete the task as an unauthorized user
-        let complete_msg = ExecuteMsg::CompleteTask {
-            task_id: TaskId::new(1),
-            result: ResponseType::Json("{\"winner\":\"Team A\"}".to_string()),
-        };
-        let unauthorized_info = mock_info("intruder", &[]);
-        let err = execute(deps.as_mut(), mock_env(), unauthorized_info, complete_msg).unwrap_err();
-        assert_eq!(err, ContractError::Unauthorized);
+        let mut res = Response::new()
+            .add_messages(settle_challenge(&config, &task))
+            .add_submessages(notify(task_id.clone(), &task)?)
+            .add_attribute("action", "arbitrate")
+            .add_attribute("task_id", task_id.to_string());
+        if let Some(winner) = task.outcome {
+            res = res.add_attribute("winner", winner);
+        }
+        Ok(res)
     }
 
-    #[test]
-    fn test_expire_task_success() {
-        let mut deps = mock_dependencies();
-        let msg = InstantiateMsg {
-            requestor: "requestor".to_string(),
-            verifier: "verifier".to_string(),
-            timeout: 1, // 1 second for testing
-        };
-        let info = mock_info("requestor", &coins(1000, "earth"));
-        instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
-
-        // Create a task with a short timeout
-        let create_msg = ExecuteMsg::Create {
-            description: "Will Team A win?".to_string(),
-            timeout: Some(1),
-            payload: RequestType::Json("{\"event\":\"Team A vs Team B\"}".to_string()),
-            options: vec!["Team A".to_string(), "Team B".to_string()],
-            proposed_winner: "Team A".to_string(),
-        };
-        execute(deps.as_mut(), mock_env(), info.clone(), create_msg).unwrap();
+    pub fn expire_task(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        task_id: TaskId,
+    ) -> Result<Response, ContractError> {
+        nonpayable(&info)?;
+        let config = CONFIG.load(deps.storage)?;
 
-        // Fast-forward time to trigger expiration
-        let mut env = mock_env();
-        env.block.time = env.block.time.plus_seconds(2);
-        env.block.height += 2;
+        let task = TASKS.update(deps.storage, task_id.clone(), |task| -> Result<_, ContractError> {
+            let mut task = task.ok_or(ContractError::TaskNotFound)?;
+            // Nobody may expire an open task before its timeout, anyone may after it
+            if matches!(task.status, Status::Open {}) && !task.timing.is_expired(&env) {
+                return Err(ContractError::Unauthorized);
+            }
+            if task.challenge.is_some() && config.arbitrator.is_some() {
+                return Err(ContractError::MustEscalate);
+            }
+            task.expire(&env)?;
+            Ok(task)
+        })?;
 
-        // Expire the task
-        let expire_msg = ExecuteMsg::ExpireTask {
-            task_id: TaskId::new(1),
-        };
-        let res = execute(deps.as_mut(), env.clone(), info.clone(), expire_msg).unwrap();
-        assert_eq!(res.attributes.len(), 2);
-        assert_eq!(res.attributes[0].value, "expire_task");
-        assert_eq!(res.attributes[1].value, "task_id");
+        Ok(Response::new()
+            .add_messages(refund_challenger(&task))
+            .add_messages(
+                split_payment(&task, Decimal::zero()).and_then(|(_, refund)| refund_requestor(&task, refund)),
+            )
+            .add_submessages(notify(task_id.clone(), &task)?)
+            .add_attribute("action", "expire_task")
+            .add_attribute("task_id", task_id.to_string()))
+    }
 
-        // Verify task expiration
-        let query_msg = QueryMsg::TaskInfo { task_id: TaskId::new(1) };
-        let res = query(deps.as_ref(), env, query_msg).unwrap();
-        let task: TaskMetadata = from_binary(&res).unwrap();
-        assert!(matches!(task.status, Status::Expired {}));
+    pub fn expire_batch(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        limit: Option<u32>,
+    ) -> Result<Response, ContractError> {
+        nonpayable(&info)?;
+        let config = CONFIG.load(deps.storage)?;
+        let limit = limit.unwrap_or(10).min(MAX_EXPIRE_BATCH) as usize;
+
+        // Only open tasks that timed out by now are read, at most `limit` of them. Challenged
+        // tasks among them wait for the arbitrator instead, see `expire_task`, and anyone can
+        // escalate them to make room
+        let now = env.block.time.seconds();
+        let lapsed = TASKS
+            .idx
+            .expiry
+            .sub_prefix(Status::Open {}.as_str())
+            .range(
+                deps.storage,
+                None,
+                Some(Bound::inclusive((now, TaskId::new(u64::MAX)))),
+                Order::Ascending,
+            )
+            .take(limit)
+            .filter(|item| {
+                item.as_ref().map_or(true, |(_, task)| !(task.challenge.is_some() && config.arbitrator.is_some()))
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+
+        let expired = lapsed.len();
+        let reward_share = config.keeper_reward.unwrap_or_default();
+        let mut rewards = Coins::default();
+        let mut res = Response::new();
+        for (task_id, mut task) in lapsed {
+            task.expire(&env)?;
+            TASKS.save(deps.storage, task_id.clone(), &task)?;
+            let refund = match split_payment(&task, reward_share) {
+                Some((reward, refund)) => {
+                    rewards.add(reward)?;
+                    refund_requestor(&task, refund)
+                }
+                None => None,
+            };
+            res = res
+                .add_messages(refund)
+                .add_messages(refund_challenger(&task))
+                .add_submessages(notify(task_id.clone(), &task)?)
+                .add_event(Event::new("expire_task").add_attribute("task_id", task_id.to_string()));
+        }
+
+        if !rewards.is_empty() {
+            res = res.add_message(BankMsg::Send {
+                to_address: info.sender.to_string(),
+                amount: rewards.into_vec(),
+            });
+        }
+        Ok(res
+            .add_attribute("action", "expire_batch")
+            .add_attribute("keeper", info.sender)
+            .add_attribute("expired", expired.to_string()))
+    }
+
+    /// Splits an expired task's payment into the keeper's reward and what the requestor gets back.
+    /// Challenged tasks have nothing left to split, the payment went to the verifier with the
+    /// first result, see `pay_verifier`
+    fn split_payment(task: &Task, reward_share: Decimal) -> Option<(Coin, Coin)> {
+        let payment = task.payment.as_ref().filter(|_| task.challenge.is_none())?;
+        let reward = payment.amount.mul_floor(reward_share);
+        Some((
+            Coin::new(reward, payment.denom.clone()),
+            Coin::new(payment.amount - reward, payment.denom.clone()),
+        ))
+    }
+
+    fn refund_requestor(task: &Task, refund: Coin) -> Option<BankMsg> {
+        (!refund.amount.is_zero()).then(|| BankMsg::Send {
+            to_address: task.requestor.to_string(),
+            amount: vec![refund],
+        })
+    }
+
+    /// A second round that never concluded did not prove the challenger wrong
+    fn refund_challenger(task: &Task) -> Option<BankMsg> {
+        task.challenge.as_ref().map(|challenge| BankMsg::Send {
+            to_address: challenge.challenger.to_string(),
+            amount: vec![challenge.bond.clone()],
+        })
+    }
+
+    pub fn cancel_task(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        task_id: TaskId,
+    ) -> Result<Response, ContractError> {
+        nonpayable(&info)?;
+        let config = CONFIG.load(deps.storage)?;
+
+        let mut task = TASKS.may_load(deps.storage, task_id.clone())?.ok_or(ContractError::TaskNotFound)?;
+        if info.sender != task.requestor {
+            return Err(ContractError::Unauthorized);
+        }
+        task.cancel(&env)?;
+
+        // Commits are hidden until the reveal, so only the verifier knows whether anyone voted
+        let votes: VoteCountResponse = deps.querier.query_wasm_smart(
+            &config.verifier,
+            &VerifierQueryMsg::VoteCount {
+                task_contract: env.contract.address.to_string(),
+                task_id: task_id.clone(),
+            },
+        )?;
+        if votes.count > 0 {
+            return Err(ContractError::TaskHasVotes);
+        }
+        TASKS.save(deps.storage, task_id.clone(), &task)?;
+
+        let mut res = Response::new()
+            .add_submessages(notify(task_id.clone(), &task)?)
+            .add_attribute("action", "cancel_task")
+            .add_attribute("task_id", task_id.to_string());
+        if let Some(payment) = task.payment {
+            let fee = payment.amount.mul_floor(config.cancel_fee.unwrap_or_default());
+            let refund = payment.amount - fee;
+            if !refund.is_zero() {
+                res = res.add_message(BankMsg::Send {
+                    to_address: task.requestor.to_string(),
+                    amount: vec![Coin::new(refund, payment.denom.clone())],
+                });
+            }
+            // The verifier only credits fees of tasks it resolves, so the owner keeps this one
+            if !fee.is_zero() {
+                res = res.add_message(BankMsg::Send {
+                    to_address: config.owner.to_string(),
+                    amount: vec![Coin::new(fee, payment.denom)],
+                });
+            }
+            res = res.add_attribute("refund", refund.to_string()).add_attribute("fee", fee.to_string());
+        }
+        Ok(res)
+    }
+
+    /// Timeouts only apply to tasks created afterwards. Tasks in flight are completed
+    /// by the new verifier, and keep the requestor that created them
+    pub fn update_config(
+        deps: DepsMut,
+        info: MessageInfo,
+        requestor: Option<Requestor>,
+        timeout: Option<TimeoutInfo>,
+        verifier: Option<String>,
+        guardian: Option<String>,
+    ) -> Result<Response, ContractError> {
+        nonpayable(&info)?;
+        let mut config = assert_owner(deps.as_ref(), &info)?;
+        if let Some(requestor) = requestor {
+            config.requestor = RequestorConfig::validate(deps.as_ref(), requestor)?;
+        }
+        if let Some(timeout) = timeout {
+            config.timeout = validate_timeout_info(timeout)?;
+        }
+        if let Some(verifier) = verifier {
+            config.verifier = deps.api.addr_validate(&verifier)?;
+        }
+        if let Some(guardian) = guardian {
+            config.guardian = Some(deps.api.addr_validate(&guardian)?);
+        }
+        CONFIG.save(deps.storage, &config)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "update_config")
+            .add_attribute("verifier", config.verifier))
+    }
+
+    /// Only proposes the new owner, who has to accept. The current owner can replace
+    /// the proposal until then
+    pub fn transfer_ownership(
+        deps: DepsMut,
+        info: MessageInfo,
+        owner: String,
+    ) -> Result<Response, ContractError> {
+        nonpayable(&info)?;
+        let mut config = assert_owner(deps.as_ref(), &info)?;
+        let pending = deps.api.addr_validate(&owner)?;
+        config.pending_owner = Some(pending.clone());
+        CONFIG.save(deps.storage, &config)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "transfer_ownership")
+            .add_attribute("pending_owner", pending))
+    }
+
+    pub fn accept_ownership(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+        nonpayable(&info)?;
+        let mut config = CONFIG.load(deps.storage)?;
+        if config.pending_owner.as_ref() != Some(&info.sender) {
+            return Err(ContractError::Unauthorized);
+        }
+        config.owner = info.sender;
+        config.pending_owner = None;
+        CONFIG.save(deps.storage, &config)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "accept_ownership")
+            .add_attribute("owner", config.owner))
+    }
+
+    pub fn set_paused(deps: DepsMut, info: MessageInfo, paused: bool) -> Result<Response, ContractError> {
+        nonpayable(&info)?;
+        let mut config = CONFIG.load(deps.storage)?;
+        if info.sender != config.owner && config.guardian.as_ref() != Some(&info.sender) {
+            return Err(ContractError::Unauthorized);
+        }
+        config.paused = paused;
+        CONFIG.save(deps.storage, &config)?;
+
+        Ok(Response::new()
+            .add_attribute("action", if paused { "pause" } else { "unpause" })
+            .add_attribute("sender", info.sender))
+    }
+
+    pub fn migrate_tasks(deps: DepsMut, info: MessageInfo, limit: Option<u32>) -> Result<Response, ContractError> {
+        nonpayable(&info)?;
+        let limit = limit.unwrap_or(MAX_MIGRATE_BATCH).min(MAX_MIGRATE_BATCH) as usize;
+        let (migrated, done) = migration::migrate_tasks(deps.storage, limit)?;
+        if migrated == 0 && done {
+            return Err(ContractError::NothingToMigrate);
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "migrate_tasks")
+            .add_attribute("migrated", migrated.to_string())
+            .add_attribute("tasks_migrated", done.to_string()))
+    }
+
+    fn assert_owner(deps: Deps, info: &MessageInfo) -> Result<Config, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+        if info.sender != config.owner {
+            return Err(ContractError::Unauthorized);
+        }
+        Ok(config)
+    }
+}
+
+mod query {
+    use super::*;
+    use cosmwasm_std::Order;
+    use cw_storage_plus::Bound;
+    use lavs_apis::id::TaskId;
+
+    use crate::msg::ListTasksResponse;
+
+    pub fn list_open(
+        deps: Deps,
+        env: Env,
+        start_after: Option<TaskId>,
+        limit: Option<u32>,
+        order: Option<Order>,
+    ) -> Result<ListOpenResponse, ContractError> {
+        let limit = limit.unwrap_or(10) as usize;
+        // Lapsed tasks stay open until somebody expires them, so only those still running are read
+        let now = env.block.time.seconds();
+        let min = Some(Bound::exclusive((now, TaskId::new(u64::MAX))));
+        let tasks = by_timeout(deps, &Status::Open {}, min, None, start_after, order)?
+            .into_iter()
+            .take(limit)
+            .map(|(id, task)| TaskMetadata {
+                id,
+                description: task.description,
+                status: task.status.into(),
+                timing: task.timing,
+                payload: task.payload,
+                result: task.result,
+            })
+            .collect();
+
+        Ok(ListOpenResponse { tasks })
+    }
+
+    pub fn list_completed(
+        deps: Deps,
+        _env: Env,
+        start_after: Option<TaskId>,
+        limit: Option<u32>,
+        order: Option<Order>,
+    ) -> Result<ListTasksResponse, ContractError> {
+        let limit = limit.unwrap_or(10) as usize;
+        let tasks = by_status(deps, &Status::Completed { completed: 0 }, start_after, order)
+            .take(limit)
+            .map(|item| item.map(|(id, task)| task_response(id, task)))
+            .collect::<StdResult<Vec<_>>>()?;
+        Ok(ListTasksResponse { tasks })
+    }
+
+    /// Expired tasks, including open ones past their timeout that nobody expired yet.
+    /// Invalid and cancelled tasks are final without a result too, but are not listed here
+    pub fn list_expired(
+        deps: Deps,
+        env: Env,
+        start_after: Option<TaskId>,
+        limit: Option<u32>,
+        order: Option<Order>,
+    ) -> Result<ListTasksResponse, ContractError> {
+        let limit = limit.unwrap_or(10) as usize;
+        let order = order.unwrap_or(Order::Descending);
+        let expired = by_status(deps, &Status::Expired {}, start_after.clone(), Some(order))
+            .take(limit)
+            .collect::<StdResult<Vec<_>>>()?;
+        let now = env.block.time.seconds();
+        let max = Some(Bound::inclusive((now, TaskId::new(u64::MAX))));
+        let lapsed = by_timeout(deps, &Status::Open {}, None, max, start_after, Some(order))?;
+
+        // Both lists are sorted, so the first `limit` of them combined are the page
+        let mut tasks: Vec<_> = expired.into_iter().chain(lapsed.into_iter().take(limit)).collect();
+        tasks.sort_by_key(|(id, _)| id.clone());
+        if order == Order::Descending {
+            tasks.reverse();
+        }
+        let tasks = tasks
+            .into_iter()
+            .take(limit)
+            .map(|(id, mut task)| {
+                task.status = task.validate_status(&env);
+                task_response(id, task)
+            })
+            .collect();
+        Ok(ListTasksResponse { tasks })
+    }
+
+    /// Tasks in the given status from the index, newest first unless `order` says otherwise
+    fn by_status<'a>(
+        deps: Deps<'a>,
+        status: &Status,
+        start_after: Option<TaskId>,
+        order: Option<Order>,
+    ) -> Box<dyn Iterator<Item = StdResult<(TaskId, Task)>> + 'a> {
+        let order = order.unwrap_or(Order::Descending);
+        let (min, max) = match order {
+            Order::Ascending => (start_after.map(Bound::exclusive), None),
+            Order::Descending => (None, start_after.map(Bound::exclusive)),
+        };
+        TASKS
+            .idx
+            .status
+            .prefix(status.as_str())
+            .range(deps.storage, min, max, order)
+    }
+
+    /// Tasks in the given status whose timeout lies within `min` and `max`, sorted like `by_status`.
+    /// The expiry index is sorted by timeout, so all tasks in the range are read before sorting
+    fn by_timeout(
+        deps: Deps,
+        status: &Status,
+        min: Option<Bound<(u64, TaskId)>>,
+        max: Option<Bound<(u64, TaskId)>>,
+        start_after: Option<TaskId>,
+        order: Option<Order>,
+    ) -> StdResult<Vec<(TaskId, Task)>> {
+        let order = order.unwrap_or(Order::Descending);
+        let mut tasks = TASKS
+            .idx
+            .expiry
+            .sub_prefix(status.as_str())
+            .range(deps.storage, min, max, Order::Ascending)
+            .filter(|item| match (item, &start_after) {
+                (Ok((id, _)), Some(start)) => match order {
+                    Order::Ascending => id > start,
+                    Order::Descending => id < start,
+                },
+                _ => true,
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+        tasks.sort_by_key(|(id, _)| id.clone());
+        if order == Order::Descending {
+            tasks.reverse();
+        }
+        Ok(tasks)
+    }
+
+    pub fn task_info(
+        deps: Deps,
+        env: Env,
+        task_id: TaskId,
+    ) -> Result<TaskInfoResponse, ContractError> {
+        let task = TASKS.may_load(deps.storage, task_id.clone())?.ok_or(ContractError::TaskNotFound)?;
+        Ok(task_response(task_id, task))
+    }
+
+    pub fn list_by_requestor(
+        deps: Deps,
+        _env: Env,
+        requestor: String,
+        start_after: Option<TaskId>,
+        limit: Option<u32>,
+    ) -> Result<ListTasksResponse, ContractError> {
+        let requestor = deps.api.addr_validate(&requestor)?;
+        let limit = limit.unwrap_or(10) as usize;
+        let start = start_after.map(Bound::exclusive);
+        let tasks = TASKS
+            .idx
+            .requestor
+            .prefix(requestor)
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| item.map(|(id, task)| task_response(id, task)))
+            .collect::<StdResult<Vec<_>>>()?;
+        Ok(ListTasksResponse { tasks })
+    }
+
+    fn task_response(task_id: TaskId, task: Task) -> TaskInfoResponse {
+        TaskInfoResponse {
+            id: task_id,
+            requestor: task.requestor,
+            description: task.description,
+            status: task.status,
+            timing: task.timing,
+            payload: task.payload,
+            options: task.options,
+            proposed_winner: task.proposed_winner,
+            outcome: task.outcome,
+            result: task.result,
+            payment: task.payment,
+            challenge: task.challenge,
+            callback: task.callback,
+            kind: task.kind,
+        }
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::{message_info, mock_dependencies, mock_env, MockApi, MockQuerier, MockStorage};
+    use cosmwasm_std::{
+        coin, coins, from_json, Addr, ContractResult, CosmosMsg, Decimal, OwnedDeps, SystemResult, Uint128, WasmQuery,
+    };
+    use lavs_apis::id::TaskId;
+    use crate::msg::{
+        CallbackExecuteMsg, CallbackInfo, DisputeConfig, ListTasksResponse, Requestor, TaskPayload, TimeoutInfo,
+        VerifierQueryMsg, VoteCountResponse, INVALID_OPTION,
+    };
 
     #[test]
-    fn test_expire_task_unauthorized() {
+    fn test_instantiate_task_queue() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            requestor: Requestor::Fixed(addr("requestor").to_string()),
+            verifier: addr("verifier").to_string(),
+            timeout: TimeoutInfo::new(3600),
+            dispute: None,
+            arbitrator: None,
+            cancel_fee: None,
+            keeper_reward: None,
+        };
+        let info = message_info(&addr("creator"), &coins(1000, "earth"));
+        let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(res.attributes.len(), 1);
+        assert_eq!(res.attributes[0].value, "instantiate");
+    }
+
+    #[test]
+    fn test_create_task() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            requestor: Requestor::Fixed(addr("requestor").to_string()),
+            verifier: addr("verifier").to_string(),
+            timeout: TimeoutInfo::new(3600),
+            dispute: None,
+            arbitrator: None,
+            cancel_fee: None,
+            keeper_reward: None,
+        };
+        let info = message_info(&addr("requestor"), &[]);
+        let res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        assert_eq!(res.attributes.len(), 1);
+        assert_eq!(res.attributes[0].value, "instantiate");
+       
+        let create_msg = ExecuteMsg::Create {
+            description: "Will Team A win?".to_string(),
+            timeout: None,
+            payload: serde_json::json!({"event": "Team A vs Team B"}),
+            options: vec!["Team A".to_string(), "Team B".to_string()],
+            proposed_winner: "Team A".to_string(),
+            callback: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), create_msg).unwrap();
+        assert_eq!(res.attributes.len(), 2);
+        assert_eq!(res.attributes[0].value, "create_task");
+        assert_eq!(res.attributes[1].value, "1");
+
+        // Verify task creation
+        let task_id = TaskId::new(1);
+        let query_msg = QueryMsg::TaskInfo { task_id: task_id.clone() };
+        let res = query(deps.as_ref(), mock_env(), query_msg).unwrap();
+        let task: TaskInfoResponse = from_json(&res).unwrap();
+        assert_eq!(task.id, task_id);
+        assert_eq!(task.description, "Will Team A win?".to_string());
+        assert!(matches!(task.status, Status::Open {}));
+    }
+
+    #[test]
+    fn test_complete_task_success() {
         let mut deps = mock_dependencies();
         let msg = InstantiateMsg {
-            requestor: "requestor".to_string(),
-            verifier: "verifier".to_string(),
-            timeout: 3600,
+            requestor: Requestor::Fixed(addr("requestor").to_string()),
+            verifier: addr("verifier").to_string(),
+            timeout: TimeoutInfo::new(3600),
+            dispute: None,
+            arbitrator: None,
+            cancel_fee: None,
+            keeper_reward: None,
         };
-        let info = mock_info("requestor", &coins(1000, "earth"));
+        let info = message_info(&addr("requestor"), &[]);
         instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
 
         // Create a task
         let create_msg = ExecuteMsg::Create {
             description: "Will Team A win?".to_string(),
-            timeout: Some(7200),
-            payload: RequestType::Json("{\"event\":\"Team A vs Team B\"}".to_string()),
+            timeout: None,
+            payload: serde_json::json!({"event": "Team A vs Team B"}),
             options: vec!["Team A".to_string(), "Team B".to_string()],
             proposed_winner: "Team A".to_string(),
+            callback: None,
         };
         execute(deps.as_mut(), mock_env(), info.clone(), create_msg).unwrap();
 
-        // Attempt to expire the task as an unauthorized user
-        let expire_msg = ExecuteMsg::ExpireTask {
+        // Complete the task as Oracle Verifier
+        let complete_msg = ExecuteMsg::CompleteTask {
             task_id: TaskId::new(1),
+            result: serde_json::json!({"winner": "Team A"}),
         };
-        let unauthorized_info = mock_info("intruder", &[]);
-        let err = execute(deps.as_mut(), mock_env(), unauthorized_info, expire_msg).unwrap_err();
-        assert_eq!(err, ContractError::Unauthorized);
+        let verifier_info = message_info(&addr("verifier"), &[]);
+        let res = execute(deps.as_mut(), mock_env(), verifier_info, complete_msg).unwrap();
+        assert_eq!(res.attributes.len(), 4);
+        assert_eq!(res.attributes[0].value, "complete_task");
+        assert_eq!(res.attributes[1].value, "1");
+        assert_eq!(res.attributes[2].value, "verified");
+        assert_eq!(res.attributes[3].value, "completed");
+
+        // Verify task completion
+        let query_msg = QueryMsg::TaskInfo { task_id: TaskId::new(1) };
+        let res = query(deps.as_ref(), mock_env(), query_msg).unwrap();
+        let task: TaskInfoResponse = from_json(&res).unwrap();
+        assert!(matches!(task.status, Status::Completed { .. }));
+        assert_eq!(task.result.unwrap(), serde_json::json!({"winner": "Team A"}));
     }
 
     #[test]
-    fn test_complete_task_already_completed() {
+    fn test_complete_task_unauthorized() {
         let mut deps = mock_dependencies();
         let msg = InstantiateMsg {
-            requestor: "requestor".to_string(),
-            verifier: "verifier".to_string(),
-            timeout: 3600,
+            requestor: Requestor::Fixed(addr("requestor").to_string()),
+            verifier: addr("verifier").to_string(),
+            timeout: TimeoutInfo::new(3600),
+            dispute: None,
+            arbitrator: None,
+            cancel_fee: None,
+            keeper_reward: None,
         };
-        let info = mock_info("requestor", &coins(1000, "earth"));
+        let info = message_info(&addr("requestor"), &[]);
         instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
 
         // Create a task
         let create_msg = ExecuteMsg::Create {
             description: "Will Team A win?".to_string(),
-            timeout: Some(7200),
-            payload: RequestType::Json("{\"event\":\"Team A vs Team B\"}".to_string()),
+            timeout: None,
+            payload: serde_json::json!({"event": "Team A vs Team B"}),
             options: vec!["Team A".to_string(), "Team B".to_string()],
             proposed_winner: "Team A".to_string(),
+            callback: None,
         };
         execute(deps.as_mut(), mock_env(), info.clone(), create_msg).unwrap();
 
-        // Complete the task as Oracle Verifier
+        // Attempt to complete the task as an unauthorized user
         let complete_msg = ExecuteMsg::CompleteTask {
             task_id: TaskId::new(1),
-            result: ResponseType::Json("{\"winner\":\"Team A\"}".to_string()),
+            result: serde_json::json!({"winner": "Team A"}),
         };
-        let verifier_info = mock_info("verifier", &[]);
-        execute(deps.as_mut(), mock_env(), verifier_info.clone(), complete_msg).unwrap();
+        let unauthorized_info = message_info(&addr("intruder"), &[]);
+        let err = execute(deps.as_mut(), mock_env(), unauthorized_info, complete_msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized);
+    }
 
-        // Attempt to complete the same task again
-        let complete_again_msg = ExecuteMsg::CompleteTask {
+    #[test]
+    fn test_expire_task_success() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            requestor: Requestor::Fixed(addr("requestor").to_string()),
+            verifier: addr("verifier").to_string(),
+            timeout: TimeoutInfo::new(1),
+            dispute: None,
+            arbitrator: None,
+            cancel_fee: None,
+            keeper_reward: None,
+        };
+        let info = message_info(&addr("requestor"), &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        // Create a task with a short timeout
+        let create_msg = ExecuteMsg::Create {
+            description: "Will Team A win?".to_string(),
+            timeout: Some(1),
+            payload: serde_json::json!({"event": "Team A vs Team B"}),
+            options: vec!["Team A".to_string(), "Team B".to_string()],
+            proposed_winner: "Team A".to_string(),
+            callback: None,
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), create_msg).unwrap();
+
+        // Fast-forward time to trigger expiration
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(2);
+        env.block.height += 2;
+
+        // Expire the task
+        let expire_msg = ExecuteMsg::ExpireTask {
             task_id: TaskId::new(1),
-            result: ResponseType::Json("{\"winner\":\"Team B\"}".to_string()),
+        };
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), expire_msg).unwrap();
+        assert_eq!(res.attributes.len(), 2);
+        assert_eq!(res.attributes[0].value, "expire_task");
+        assert_eq!(res.attributes[1].value, "1");
+
+        // Verify task expiration
+        let query_msg = QueryMsg::TaskInfo { task_id: TaskId::new(1) };
+        let res = query(deps.as_ref(), env, query_msg).unwrap();
+        let task: TaskInfoResponse = from_json(&res).unwrap();
+        assert!(matches!(task.status, Status::Expired {}));
+    }
+
+    #[test]
+    fn test_expire_task_unauthorized() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            requestor: Requestor::Fixed(addr("requestor").to_string()),
+            verifier: addr("verifier").to_string(),
+            timeout: TimeoutInfo::new(3600),
+            dispute: None,
+            arbitrator: None,
+            cancel_fee: None,
+            keeper_reward: None,
+        };
+        let info = message_info(&addr("requestor"), &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        // Create a task
+        let create_msg = ExecuteMsg::Create {
+            description: "Will Team A win?".to_string(),
+            timeout: None,
+            payload: serde_json::json!({"event": "Team A vs Team B"}),
+            options: vec!["Team A".to_string(), "Team B".to_string()],
+            proposed_winner: "Team A".to_string(),
+            callback: None,
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), create_msg).unwrap();
+
+        // Attempt to expire the task as an unauthorized user
+        let expire_msg = ExecuteMsg::ExpireTask {
+            task_id: TaskId::new(1),
+        };
+        let unauthorized_info = message_info(&addr("intruder"), &[]);
+        let err = execute(deps.as_mut(), mock_env(), unauthorized_info, expire_msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized);
+    }
+
+    #[test]
+    fn test_expire_task_refunds_requestor() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            requestor: Requestor::OpenPayment(coin(100, "earth")),
+            verifier: addr("verifier").to_string(),
+            timeout: TimeoutInfo::new(3600),
+            dispute: None,
+            arbitrator: None,
+            cancel_fee: None,
+            keeper_reward: Some(Decimal::percent(5)),
+        };
+        instantiate(deps.as_mut(), mock_env(), message_info(&addr("creator"), &[]), msg).unwrap();
+
+        let create_msg = ExecuteMsg::Create {
+            description: "Get price".to_string(),
+            timeout: None,
+            payload: serde_json::json!({"action": "get_price"}),
+            options: vec![],
+            proposed_winner: String::new(),
+            callback: None,
+        };
+        execute(deps.as_mut(), mock_env(), message_info(&addr("requestor"), &coins(200, "earth")), create_msg)
+            .unwrap();
+
+        // Paying again is not how one gets to expire a task
+        let expire_msg = ExecuteMsg::ExpireTask {
+            task_id: TaskId::new(1),
+        };
+        let mut later = mock_env();
+        later.block.time = later.block.time.plus_seconds(3601);
+        let err = execute(
+            deps.as_mut(),
+            later.clone(),
+            message_info(&addr("requestor"), &coins(100, "earth")),
+            expire_msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Payment(_)));
+
+        // Once lapsed anyone may expire it, and the requestor gets the whole payment back
+        let res = execute(deps.as_mut(), later, message_info(&addr("anyone"), &[]), expire_msg).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: addr("requestor").to_string(),
+                amount: coins(200, "earth"),
+            })
+        );
+    }
+
+    #[test]
+    fn test_complete_task_already_completed() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            requestor: Requestor::Fixed(addr("requestor").to_string()),
+            verifier: addr("verifier").to_string(),
+            timeout: TimeoutInfo::new(3600),
+            dispute: None,
+            arbitrator: None,
+            cancel_fee: None,
+            keeper_reward: None,
+        };
+        let info = message_info(&addr("requestor"), &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        // Create a task
+        let create_msg = ExecuteMsg::Create {
+            description: "Will Team A win?".to_string(),
+            timeout: None,
+            payload: serde_json::json!({"event": "Team A vs Team B"}),
+            options: vec!["Team A".to_string(), "Team B".to_string()],
+            proposed_winner: "Team A".to_string(),
+            callback: None,
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), create_msg).unwrap();
+
+        // Complete the task as Oracle Verifier
+        let complete_msg = ExecuteMsg::CompleteTask {
+            task_id: TaskId::new(1),
+            result: serde_json::json!({"winner": "Team A"}),
+        };
+        let verifier_info = message_info(&addr("verifier"), &[]);
+        execute(deps.as_mut(), mock_env(), verifier_info.clone(), complete_msg).unwrap();
+
+        // Attempt to complete the same task again
+        let complete_again_msg = ExecuteMsg::CompleteTask {
+            task_id: TaskId::new(1),
+            result: serde_json::json!({"winner": "Team B"}),
         };
         let res = execute(deps.as_mut(), mock_env(), verifier_info, complete_again_msg);
         assert!(res.is_err());
         assert_eq!(res.unwrap_err(), ContractError::TaskCompleted);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_resolve_outcome() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            requestor: Requestor::Fixed(addr("requestor").to_string()),
+            verifier: addr("verifier").to_string(),
+            timeout: TimeoutInfo::new(3600),
+            dispute: None,
+            arbitrator: None,
+            cancel_fee: None,
+            keeper_reward: None,
+        };
+        let info = message_info(&addr("requestor"), &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        // Proposed winner must be one of the options
+        let create_msg = ExecuteMsg::Create {
+            description: "Will Team A win?".to_string(),
+            timeout: None,
+            payload: serde_json::json!({"event": "Team A vs Team B"}),
+            options: vec!["Team A".to_string(), "Team B".to_string()],
+            proposed_winner: "Team C".to_string(),
+            callback: None,
+        };
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), create_msg).unwrap_err();
+        assert_eq!(err, ContractError::UnknownOption("Team C".to_string()));
+
+        let create_msg = ExecuteMsg::Create {
+            description: "Will Team A win?".to_string(),
+            timeout: None,
+            payload: serde_json::json!({"event": "Team A vs Team B"}),
+            options: vec!["Team A".to_string(), "Team B".to_string()],
+            proposed_winner: "Team A".to_string(),
+            callback: None,
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), create_msg).unwrap();
+
+        // Only the verifier can resolve, and only to a known option
+        let resolve_msg = ExecuteMsg::ResolveOutcome {
+            task_id: TaskId::new(1),
+            winner: "Team B".to_string(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), resolve_msg.clone()).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized);
+
+        let verifier_info = message_info(&addr("verifier"), &[]);
+        let unknown_msg = ExecuteMsg::ResolveOutcome {
+            task_id: TaskId::new(1),
+            winner: "Team C".to_string(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), verifier_info.clone(), unknown_msg).unwrap_err();
+        assert_eq!(err, ContractError::UnknownOption("Team C".to_string()));
+
+        let res = execute(deps.as_mut(), mock_env(), verifier_info, resolve_msg).unwrap();
+        assert_eq!(res.attributes[0].value, "resolve_outcome");
+
+        let query_msg = QueryMsg::TaskInfo { task_id: TaskId::new(1) };
+        let res = query(deps.as_ref(), mock_env(), query_msg).unwrap();
+        let task: TaskInfoResponse = from_json(&res).unwrap();
+        assert!(matches!(task.status, Status::Completed { .. }));
+        assert_eq!(task.outcome, Some("Team B".to_string()));
+    }
+
+    #[test]
+    fn test_payment_goes_to_verifier() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            requestor: Requestor::OpenPayment(coin(100, "earth")),
+            verifier: addr("verifier").to_string(),
+            timeout: TimeoutInfo::new(3600),
+            dispute: None,
+            arbitrator: None,
+            cancel_fee: None,
+            keeper_reward: None,
+        };
+        instantiate(deps.as_mut(), mock_env(), message_info(&addr("creator"), &[]), msg).unwrap();
+
+        // Anyone can create a task by paying at least the fee
+        let create_msg = ExecuteMsg::Create {
+            description: "Get price".to_string(),
+            timeout: None,
+            payload: serde_json::json!({"action": "get_price"}),
+            options: vec![],
+            proposed_winner: String::new(),
+            callback: None,
+        };
+        let err = execute(deps.as_mut(), mock_env(), message_info(&addr("anyone"), &coins(99, "earth")), create_msg.clone())
+            .unwrap_err();
+        assert_eq!(err, ContractError::InsufficientPayment(Uint128::new(100), "earth".to_string()));
+        execute(deps.as_mut(), mock_env(), message_info(&addr("anyone"), &coins(150, "earth")), create_msg).unwrap();
+
+        let query_msg = QueryMsg::TaskInfo { task_id: TaskId::new(1) };
+        let res = query(deps.as_ref(), mock_env(), query_msg).unwrap();
+        let task: TaskInfoResponse = from_json(&res).unwrap();
+        assert_eq!(task.payment, Some(coin(150, "earth")));
+
+        // Completing the task forwards the whole payment to the verifier
+        let complete_msg = ExecuteMsg::CompleteTask {
+            task_id: TaskId::new(1),
+            result: serde_json::json!({"price": "100"}),
+        };
+        let res = execute(deps.as_mut(), mock_env(), message_info(&addr("verifier"), &[]), complete_msg).unwrap();
+        assert_eq!(res.messages.len(), 1);
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: addr("verifier").to_string(),
+                amount: coins(150, "earth"),
+            })
+        );
+    }
+
+    #[test]
+    fn test_cancel_task() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            requestor: Requestor::OpenPayment(coin(100, "earth")),
+            verifier: addr("verifier").to_string(),
+            timeout: TimeoutInfo::new(3600),
+            dispute: None,
+            arbitrator: None,
+            cancel_fee: Some(Decimal::percent(10)),
+            keeper_reward: None,
+        };
+        instantiate(deps.as_mut(), mock_env(), message_info(&addr("creator"), &[]), msg).unwrap();
+
+        let create_msg = ExecuteMsg::Create {
+            description: "Get price".to_string(),
+            timeout: None,
+            payload: serde_json::json!({"action": "get_price"}),
+            options: vec![],
+            proposed_winner: String::new(),
+            callback: None,
+        };
+        execute(deps.as_mut(), mock_env(), message_info(&addr("alice"), &coins(150, "earth")), create_msg.clone()).unwrap();
+        execute(deps.as_mut(), mock_env(), message_info(&addr("bob"), &coins(100, "earth")), create_msg).unwrap();
+
+        // The verifier has a vote on the second task only
+        deps.querier.update_wasm(|query| {
+            let count = match query {
+                WasmQuery::Smart { msg, .. } => match from_json(msg).unwrap() {
+                    VerifierQueryMsg::VoteCount { task_id, .. } => u32::from(task_id == TaskId::new(2)),
+                },
+                _ => panic!("Unexpected query"),
+            };
+            SystemResult::Ok(ContractResult::Ok(to_json_binary(&VoteCountResponse { count }).unwrap()))
+        });
+
+        let cancel_msg = |id: u64| ExecuteMsg::CancelTask { task_id: TaskId::new(id) };
+        let err = execute(deps.as_mut(), mock_env(), message_info(&addr("bob"), &[]), cancel_msg(1)).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized);
+        let err = execute(deps.as_mut(), mock_env(), message_info(&addr("bob"), &[]), cancel_msg(2)).unwrap_err();
+        assert_eq!(err, ContractError::TaskHasVotes);
+
+        // The requestor gets the payment back, minus the 10% fee for the owner
+        let res = execute(deps.as_mut(), mock_env(), message_info(&addr("alice"), &[]), cancel_msg(1)).unwrap();
+        assert_eq!(
+            res.messages.into_iter().map(|msg| msg.msg).collect::<Vec<_>>(),
+            vec![
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: addr("alice").to_string(),
+                    amount: coins(135, "earth"),
+                }),
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: addr("creator").to_string(),
+                    amount: coins(15, "earth"),
+                }),
+            ]
+        );
+        let task = task_info(deps.as_ref(), mock_env());
+        assert_eq!(task.status, Status::Cancelled {});
+        assert_eq!(task.requestor, addr("alice"));
+
+        let err = execute(deps.as_mut(), mock_env(), message_info(&addr("alice"), &[]), cancel_msg(1)).unwrap_err();
+        assert_eq!(err, ContractError::TaskCancelled);
+    }
+
+    #[test]
+    fn test_list_by_requestor() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            requestor: Requestor::OpenPayment(coin(100, "earth")),
+            verifier: addr("verifier").to_string(),
+            timeout: TimeoutInfo::new(3600),
+            dispute: None,
+            arbitrator: None,
+            cancel_fee: None,
+            keeper_reward: None,
+        };
+        instantiate(deps.as_mut(), mock_env(), message_info(&addr("creator"), &[]), msg).unwrap();
+
+        let create_msg = ExecuteMsg::Create {
+            description: "Get price".to_string(),
+            timeout: None,
+            payload: serde_json::json!({"action": "get_price"}),
+            options: vec![],
+            proposed_winner: String::new(),
+            callback: None,
+        };
+        for requestor in ["alice", "bob", "alice", "alice"] {
+            let info = message_info(&addr(requestor), &coins(100, "earth"));
+            execute(deps.as_mut(), mock_env(), info, create_msg.clone()).unwrap();
+        }
+
+        let list = |deps: Deps, requestor: &str, start_after: Option<u64>| -> Vec<TaskId> {
+            let query_msg = QueryMsg::ListByRequestor {
+                requestor: addr(requestor).to_string(),
+                start_after: start_after.map(TaskId::new),
+                limit: Some(2),
+            };
+            let res: ListTasksResponse = from_json(&query(deps, mock_env(), query_msg).unwrap()).unwrap();
+            res.tasks.into_iter().map(|task| task.id).collect()
+        };
+        assert_eq!(list(deps.as_ref(), "alice", None), vec![TaskId::new(1), TaskId::new(3)]);
+        assert_eq!(list(deps.as_ref(), "alice", Some(3)), vec![TaskId::new(4)]);
+        assert_eq!(list(deps.as_ref(), "bob", None), vec![TaskId::new(2)]);
+        assert_eq!(list(deps.as_ref(), "carol", None), vec![]);
+    }
+
+    #[test]
+    fn test_list_by_status() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            requestor: Requestor::Fixed(addr("requestor").to_string()),
+            verifier: addr("verifier").to_string(),
+            timeout: TimeoutInfo::new(3600),
+            dispute: None,
+            arbitrator: None,
+            cancel_fee: None,
+            keeper_reward: None,
+        };
+        instantiate(deps.as_mut(), mock_env(), message_info(&addr("creator"), &[]), msg).unwrap();
+
+        let create_msg = ExecuteMsg::Create {
+            description: "Get price".to_string(),
+            timeout: None,
+            payload: serde_json::json!({"action": "get_price"}),
+            options: vec![],
+            proposed_winner: String::new(),
+            callback: None,
+        };
+        for _ in 0..4 {
+            execute(deps.as_mut(), mock_env(), message_info(&addr("requestor"), &[]), create_msg.clone()).unwrap();
+        }
+        let complete_msg = ExecuteMsg::CompleteTask {
+            task_id: TaskId::new(2),
+            result: serde_json::json!({"price": "100"}),
+        };
+        execute(deps.as_mut(), mock_env(), message_info(&addr("verifier"), &[]), complete_msg).unwrap();
+
+        // Task 3 is expired explicitly, the others only time out
+        let mut later = mock_env();
+        later.block.time = later.block.time.plus_seconds(3601);
+        let expire_msg = ExecuteMsg::ExpireTask { task_id: TaskId::new(3) };
+        execute(deps.as_mut(), later.clone(), message_info(&addr("requestor"), &[]), expire_msg).unwrap();
+
+        let open = |env: Env, start_after: Option<u64>, order: Option<Order>| -> Vec<TaskId> {
+            let query_msg = QueryMsg::ListOpen {
+                start_after: start_after.map(TaskId::new),
+                limit: Some(2),
+                order,
+            };
+            let res: ListOpenResponse = from_json(&query(deps.as_ref(), env, query_msg).unwrap()).unwrap();
+            res.tasks.into_iter().map(|task| task.id).collect()
+        };
+        let list = |env: Env, query_msg: QueryMsg| -> Vec<TaskId> {
+            let res: ListTasksResponse = from_json(&query(deps.as_ref(), env, query_msg).unwrap()).unwrap();
+            res.tasks.into_iter().map(|task| task.id).collect()
+        };
+        let expired = |start_after: Option<u64>, order: Option<Order>| QueryMsg::ListExpired {
+            start_after: start_after.map(TaskId::new),
+            limit: Some(2),
+            order,
+        };
+
+        assert_eq!(open(mock_env(), None, None), vec![TaskId::new(4), TaskId::new(1)]);
+        assert_eq!(open(mock_env(), None, Some(Order::Ascending)), vec![TaskId::new(1), TaskId::new(4)]);
+        assert_eq!(open(mock_env(), Some(4), None), vec![TaskId::new(1)]);
+        assert_eq!(open(mock_env(), Some(1), Some(Order::Ascending)), vec![TaskId::new(4)]);
+        assert_eq!(open(later.clone(), None, None), vec![]);
+
+        let completed = QueryMsg::ListCompleted {
+            start_after: None,
+            limit: None,
+            order: None,
+        };
+        assert_eq!(list(later.clone(), completed), vec![TaskId::new(2)]);
+
+        // Lapsed open tasks count as expired
+        assert_eq!(list(mock_env(), expired(None, None)), vec![TaskId::new(3)]);
+        let page = list(later.clone(), expired(None, None));
+        assert_eq!(page, vec![TaskId::new(4), TaskId::new(3)]);
+        let page = list(later.clone(), expired(Some(3), None));
+        assert_eq!(page, vec![TaskId::new(1)]);
+        let page = list(later.clone(), expired(None, Some(Order::Ascending)));
+        assert_eq!(page, vec![TaskId::new(1), TaskId::new(3)]);
+        let res = query(deps.as_ref(), later, expired(None, None)).unwrap();
+        let res: ListTasksResponse = from_json(&res).unwrap();
+        assert!(res.tasks.iter().all(|task| task.status == Status::Expired {}));
+    }
+
+    #[test]
+    fn test_expire_batch() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            requestor: Requestor::OpenPayment(coin(100, "earth")),
+            verifier: addr("verifier").to_string(),
+            timeout: TimeoutInfo::new(3600),
+            dispute: None,
+            arbitrator: None,
+            cancel_fee: None,
+            keeper_reward: Some(Decimal::percent(5)),
+        };
+        instantiate(deps.as_mut(), mock_env(), message_info(&addr("creator"), &[]), msg).unwrap();
+
+        let create_msg = ExecuteMsg::Create {
+            description: "Get price".to_string(),
+            timeout: None,
+            payload: serde_json::json!({"action": "get_price"}),
+            options: vec![],
+            proposed_winner: String::new(),
+            callback: None,
+        };
+        for _ in 0..3 {
+            let info = message_info(&addr("requestor"), &coins(200, "earth"));
+            execute(deps.as_mut(), mock_env(), info, create_msg.clone()).unwrap();
+        }
+        let complete_msg = ExecuteMsg::CompleteTask {
+            task_id: TaskId::new(2),
+            result: serde_json::json!({"price": "100"}),
+        };
+        execute(deps.as_mut(), mock_env(), message_info(&addr("verifier"), &[]), complete_msg).unwrap();
+
+        // Nothing lapsed yet
+        let batch_msg = ExecuteMsg::ExpireBatch { limit: Some(1) };
+        let res = execute(deps.as_mut(), mock_env(), message_info(&addr("keeper"), &[]), batch_msg.clone()).unwrap();
+        assert_eq!(res.attributes[2].value, "0");
+        assert!(res.messages.is_empty());
+
+        // Anyone can sweep lapsed tasks, one event and 5% of the payment per task.
+        // The requestor gets the rest back
+        let mut later = mock_env();
+        later.block.time = later.block.time.plus_seconds(3601);
+        let res = execute(deps.as_mut(), later.clone(), message_info(&addr("keeper"), &[]), batch_msg.clone()).unwrap();
+        assert_eq!(res.attributes[2].value, "1");
+        assert_eq!(res.events.len(), 1);
+        assert_eq!(res.events[0].attributes[0].value, "1");
+        assert_eq!(
+            res.messages.into_iter().map(|msg| msg.msg).collect::<Vec<_>>(),
+            vec![
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: addr("requestor").to_string(),
+                    amount: coins(190, "earth"),
+                }),
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: addr("keeper").to_string(),
+                    amount: coins(10, "earth"),
+                }),
+            ]
+        );
+
+        let batch_msg = ExecuteMsg::ExpireBatch { limit: None };
+        let res = execute(deps.as_mut(), later.clone(), message_info(&addr("keeper"), &[]), batch_msg).unwrap();
+        assert_eq!(res.events[0].attributes[0].value, "3");
+        let query_msg = QueryMsg::TaskInfo { task_id: TaskId::new(3) };
+        let task: TaskInfoResponse = from_json(&query(deps.as_ref(), later, query_msg).unwrap()).unwrap();
+        assert_eq!(task.status, Status::Expired {});
+    }
+
+    #[test]
+    fn test_update_config() {
+        let mut deps = dispute_queue();
+        let update = |timeout: Option<TimeoutInfo>, verifier: Option<&str>| ExecuteMsg::UpdateConfig {
+            requestor: None,
+            timeout,
+            verifier: verifier.map(|verifier| addr(verifier).to_string()),
+            guardian: None,
+        };
+
+        // Only the owner, who instantiated the queue, may update it
+        let err = execute(deps.as_mut(), mock_env(), message_info(&addr("requestor"), &[]), update(None, Some("new_verifier")))
+            .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized);
+        let invalid = TimeoutInfo {
+            default: 600,
+            minimum: Some(900),
+            maximum: None,
+        };
+        let err = execute(deps.as_mut(), mock_env(), message_info(&addr("creator"), &[]), update(Some(invalid), None))
+            .unwrap_err();
+        assert_eq!(err, ContractError::InvalidTimeoutInfo);
+
+        let msg = update(Some(TimeoutInfo::new(600)), Some("new_verifier"));
+        execute(deps.as_mut(), mock_env(), message_info(&addr("creator"), &[]), msg).unwrap();
+        let config: Config = from_json(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+        assert_eq!(config.verifier, addr("new_verifier"));
+        assert_eq!(config.timeout.default, 600);
+
+        // Tasks in flight are completed by the new verifier
+        let resolve_msg = |winner: &str| ExecuteMsg::ResolveOutcome {
+            task_id: TaskId::new(1),
+            winner: winner.to_string(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), message_info(&addr("verifier"), &[]), resolve_msg("Team A")).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized);
+        execute(deps.as_mut(), mock_env(), message_info(&addr("new_verifier"), &[]), resolve_msg("Team A")).unwrap();
+    }
+
+    #[test]
+    fn test_transfer_ownership() {
+        let mut deps = dispute_queue();
+        let transfer = |owner: &str| ExecuteMsg::TransferOwnership { owner: addr(owner).to_string() };
+        let update = ExecuteMsg::UpdateConfig {
+            requestor: None,
+            timeout: None,
+            verifier: Some(addr("verifier").to_string()),
+            guardian: None,
+        };
+
+        let err = execute(deps.as_mut(), mock_env(), message_info(&addr("requestor"), &[]), transfer("requestor")).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized);
+
+        // A proposal changes nothing until it is accepted, so a typo can be replaced
+        execute(deps.as_mut(), mock_env(), message_info(&addr("creator"), &[]), transfer("typo")).unwrap();
+        execute(deps.as_mut(), mock_env(), message_info(&addr("creator"), &[]), update.clone()).unwrap();
+        execute(deps.as_mut(), mock_env(), message_info(&addr("creator"), &[]), transfer("new_owner")).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), message_info(&addr("typo"), &[]), ExecuteMsg::AcceptOwnership {})
+            .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized);
+
+        execute(deps.as_mut(), mock_env(), message_info(&addr("new_owner"), &[]), ExecuteMsg::AcceptOwnership {}).unwrap();
+        let config: Config = from_json(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+        assert_eq!(config.owner, addr("new_owner"));
+        assert_eq!(config.pending_owner, None);
+        let err = execute(deps.as_mut(), mock_env(), message_info(&addr("creator"), &[]), update.clone()).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized);
+        execute(deps.as_mut(), mock_env(), message_info(&addr("new_owner"), &[]), update).unwrap();
+    }
+
+    #[test]
+    fn test_pause() {
+        let mut deps = dispute_queue();
+        let create_msg = ExecuteMsg::Create {
+            description: "Will Team B win?".to_string(),
+            timeout: None,
+            payload: serde_json::json!({"event": "Team A vs Team B"}),
+            options: vec!["Team A".to_string(), "Team B".to_string()],
+            proposed_winner: String::new(),
+            callback: None,
+        };
+        let update = ExecuteMsg::UpdateConfig {
+            requestor: None,
+            timeout: None,
+            verifier: None,
+            guardian: Some(addr("guardian").to_string()),
+        };
+        execute(deps.as_mut(), mock_env(), message_info(&addr("creator"), &[]), update).unwrap();
+
+        // Only the guardian and the owner may pause
+        let err = execute(deps.as_mut(), mock_env(), message_info(&addr("requestor"), &[]), ExecuteMsg::Pause {}).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized);
+        execute(deps.as_mut(), mock_env(), message_info(&addr("guardian"), &[]), ExecuteMsg::Pause {}).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), message_info(&addr("requestor"), &[]), create_msg.clone()).unwrap_err();
+        assert_eq!(err, ContractError::Paused);
+
+        // Open tasks can still run out and refund
+        let mut expired = mock_env();
+        expired.block.time = expired.block.time.plus_seconds(3600);
+        let expire_msg = ExecuteMsg::ExpireTask { task_id: TaskId::new(1) };
+        execute(deps.as_mut(), expired, message_info(&addr("requestor"), &[]), expire_msg).unwrap();
+        let task = task_info(deps.as_ref(), mock_env());
+        assert_eq!(task.status, Status::Expired {});
+
+        execute(deps.as_mut(), mock_env(), message_info(&addr("creator"), &[]), ExecuteMsg::Unpause {}).unwrap();
+        execute(deps.as_mut(), mock_env(), message_info(&addr("requestor"), &[]), create_msg).unwrap();
+    }
+
+    #[test]
+    fn test_callback() {
+        let mut deps = dispute_queue();
+        let create_msg = |gas_limit: u64| ExecuteMsg::Create {
+            description: "Will Team B win?".to_string(),
+            timeout: None,
+            payload: serde_json::json!({"event": "Team A vs Team B"}),
+            options: vec!["Team A".to_string(), "Team B".to_string()],
+            proposed_winner: String::new(),
+            callback: Some(CallbackInfo {
+                contract: addr("market").to_string(),
+                gas_limit,
+            }),
+        };
+        let err = execute(deps.as_mut(), mock_env(), message_info(&addr("requestor"), &[]), create_msg(MAX_CALLBACK_GAS + 1))
+            .unwrap_err();
+        assert_eq!(err, ContractError::InvalidCallbackGasLimit(MAX_CALLBACK_GAS));
+        execute(deps.as_mut(), mock_env(), message_info(&addr("requestor"), &[]), create_msg(200_000)).unwrap();
+
+        // Nothing is reported while the result can still be challenged
+        let resolve_msg = ExecuteMsg::ResolveOutcome {
+            task_id: TaskId::new(2),
+            winner: "Team B".to_string(),
+        };
+        let res = execute(deps.as_mut(), mock_env(), message_info(&addr("verifier"), &[]), resolve_msg).unwrap();
+        assert!(res.messages.is_empty());
+
+        let mut later = mock_env();
+        later.block.time = later.block.time.plus_seconds(100);
+        let finalize_msg = ExecuteMsg::Finalize { task_id: TaskId::new(2) };
+        let res = execute(deps.as_mut(), later.clone(), message_info(&addr("anyone"), &[]), finalize_msg).unwrap();
+        assert_eq!(res.messages.len(), 1);
+        let callback = &res.messages[0];
+        assert_eq!(callback.id, CALLBACK_REPLY_ID);
+        assert_eq!(callback.reply_on, cosmwasm_std::ReplyOn::Error);
+        assert_eq!(callback.gas_limit, Some(200_000));
+        let expected = CallbackExecuteMsg::TaskResolved {
+            task_id: TaskId::new(2),
+            status: Status::Completed { completed: later.block.time.seconds() },
+            result: Some(serde_json::json!({"winner": "Team B"})),
+            outcome: Some("Team B".to_string()),
+        };
+        assert_eq!(
+            callback.msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: addr("market").to_string(),
+                msg: to_json_binary(&expected).unwrap(),
+                funds: vec![],
+            })
+        );
+
+        // Tasks without a callback are not reported
+        let mut expired = mock_env();
+        expired.block.time = expired.block.time.plus_seconds(3600);
+        let expire_msg = ExecuteMsg::ExpireTask { task_id: TaskId::new(1) };
+        let res = execute(deps.as_mut(), expired, message_info(&addr("requestor"), &[]), expire_msg).unwrap();
+        assert!(res.messages.is_empty());
+    }
+
+    #[test]
+    fn test_typed_payloads() {
+        let mut deps = dispute_queue();
+        let now = mock_env().block.time.seconds();
+        let create_msg = |payload: serde_json::Value, options: &[&str], proposed_winner: &str| ExecuteMsg::Create {
+            description: "Typed".to_string(),
+            timeout: None,
+            payload,
+            options: options.iter().map(|option| option.to_string()).collect(),
+            proposed_winner: proposed_winner.to_string(),
+            callback: None,
+        };
+        let mut create = |msg: ExecuteMsg| execute(deps.as_mut(), mock_env(), message_info(&addr("requestor"), &[]), msg);
+        let task_id = |res: Response| -> TaskId {
+            let id = res.attributes.iter().find(|attr| attr.key == "task_id").unwrap();
+            TaskId::new(id.value.parse().unwrap())
+        };
+
+        // Price feeds are numeric
+        let feed = serde_json::json!({"price_feed": {"base": "ATOM", "quote": "USD", "aggregation_window": 60}});
+        let err = create(create_msg(feed.clone(), &["up", "down"], "")).unwrap_err();
+        assert_eq!(err, ContractError::OptionsMismatch);
+        let same = serde_json::json!({"price_feed": {"base": "ATOM", "quote": "ATOM", "aggregation_window": 60}});
+        let err = create(create_msg(same, &[], "")).unwrap_err();
+        assert_eq!(err, ContractError::InvalidPriceFeed);
+        let feed = task_id(create(create_msg(feed, &[], "")).unwrap());
+
+        // Binary events resolve to yes or no, after they closed but before the task expires
+        let event = |close_time: u64| {
+            serde_json::json!({"binary_event": {
+                "question": "Will it rain?",
+                "resolution_source": "https://weather.example",
+                "close_time": close_time,
+            }})
+        };
+        let err = create(create_msg(event(now + 3600), &[], "")).unwrap_err();
+        assert_eq!(err, ContractError::InvalidBinaryEvent);
+        let err = create(create_msg(event(now + 600), &[], "maybe")).unwrap_err();
+        assert_eq!(err, ContractError::UnknownOption("maybe".to_string()));
+        let event = task_id(create(create_msg(event(now + 600), &[], "yes")).unwrap());
+
+        // Scalars need a range, categorical payloads their options
+        let scalar = serde_json::json!({"scalar": {"min": "10", "max": "10", "unit": "mm"}});
+        let err = create(create_msg(scalar, &[], "")).unwrap_err();
+        assert_eq!(err, ContractError::InvalidRange(Decimal::percent(1000), Decimal::percent(1000)));
+        let categorical = serde_json::json!({"categorical": {"options": ["red", "blue"]}});
+        let err = create(create_msg(categorical.clone(), &["red", "green"], "")).unwrap_err();
+        assert_eq!(err, ContractError::OptionsMismatch);
+        let categorical = task_id(create(create_msg(categorical, &["red", "blue"], "blue")).unwrap());
+
+        // Tagged payloads must be complete, untagged ones are taken as they are
+        let err = create(create_msg(serde_json::json!({"scalar": {"min": "1"}}), &[], "")).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidPayload(_)));
+        let untyped = task_id(create(create_msg(serde_json::json!({"scalar_ish": true}), &[], "")).unwrap());
+
+        let task = |task_id: TaskId| -> TaskInfoResponse {
+            from_json(&query(deps.as_ref(), mock_env(), QueryMsg::TaskInfo { task_id }).unwrap()).unwrap()
+        };
+        assert!(matches!(task(feed.clone()).kind, Some(TaskPayload::PriceFeed { .. })));
+        assert!(task(feed).options.is_empty());
+        assert_eq!(task(event).options, vec!["yes".to_string(), "no".to_string()]);
+        assert!(matches!(task(categorical).kind, Some(TaskPayload::Categorical { .. })));
+        assert_eq!(task(untyped).kind, None);
+    }
+
+    fn addr(name: &str) -> Addr {
+        MockApi::default().addr_make(name)
+    }
+
+    /// A queue with a 100 second dispute window, an arbitrator and one categorical task
+    fn dispute_queue() -> OwnedDeps<MockStorage, MockApi, MockQuerier> {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            requestor: Requestor::Fixed(addr("requestor").to_string()),
+            verifier: addr("verifier").to_string(),
+            timeout: TimeoutInfo::new(3600),
+            dispute: Some(DisputeConfig {
+                window: 100,
+                bond: coin(50, "earth"),
+            }),
+            arbitrator: Some(addr("arbitrator").to_string()),
+            cancel_fee: None,
+            keeper_reward: None,
+        };
+        instantiate(deps.as_mut(), mock_env(), message_info(&addr("creator"), &[]), msg).unwrap();
+
+        let create_msg = ExecuteMsg::Create {
+            description: "Will Team A win?".to_string(),
+            timeout: None,
+            payload: serde_json::json!({"event": "Team A vs Team B"}),
+            options: vec!["Team A".to_string(), "Team B".to_string()],
+            proposed_winner: String::new(),
+            callback: None,
+        };
+        execute(deps.as_mut(), mock_env(), message_info(&addr("requestor"), &[]), create_msg).unwrap();
+        deps
+    }
+
+    fn task_info(deps: Deps, env: Env) -> TaskInfoResponse {
+        let res = query(deps, env, QueryMsg::TaskInfo { task_id: TaskId::new(1) }).unwrap();
+        from_json(&res).unwrap()
+    }
+
+    #[test]
+    fn test_challenge_overturns_result() {
+        let mut deps = dispute_queue();
+        let resolve_msg = |winner: &str| ExecuteMsg::ResolveOutcome {
+            task_id: TaskId::new(1),
+            winner: winner.to_string(),
+        };
+
+        // The first result waits out the dispute window
+        let res = execute(deps.as_mut(), mock_env(), message_info(&addr("verifier"), &[]), resolve_msg("Team A")).unwrap();
+        assert_eq!(res.attributes[3].value, "pending");
+        let task = task_info(deps.as_ref(), mock_env());
+        let dispute_ends = mock_env().block.time.seconds() + 100;
+        assert_eq!(task.status, Status::Pending { dispute_ends });
+        assert_eq!(task.outcome, Some("Team A".to_string()));
+
+        let finalize_msg = ExecuteMsg::Finalize { task_id: TaskId::new(1) };
+        let err = execute(deps.as_mut(), mock_env(), message_info(&addr("anyone"), &[]), finalize_msg).unwrap_err();
+        assert_eq!(err, ContractError::DisputeWindowOpen);
+
+        // Challenging needs the full bond and a different result
+        let challenge_msg = |winner: &str| ExecuteMsg::Challenge {
+            task_id: TaskId::new(1),
+            proposed_result: serde_json::json!({ "winner": winner }),
+        };
+        let err = execute(deps.as_mut(), mock_env(), message_info(&addr("challenger"), &coins(49, "earth")), challenge_msg("Team B"))
+            .unwrap_err();
+        assert_eq!(err, ContractError::InsufficientPayment(Uint128::new(50), "earth".to_string()));
+        let err = execute(deps.as_mut(), mock_env(), message_info(&addr("challenger"), &coins(50, "earth")), challenge_msg("Team A"))
+            .unwrap_err();
+        assert_eq!(err, ContractError::ChallengeMatchesResult);
+
+        // A challenge re-opens the task with a fresh timeout
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(50);
+        execute(deps.as_mut(), env.clone(), message_info(&addr("challenger"), &coins(50, "earth")), challenge_msg("Team B")).unwrap();
+        let task = task_info(deps.as_ref(), env.clone());
+        assert_eq!(task.status, Status::Open {});
+        assert_eq!(task.outcome, None);
+        assert_eq!(task.result, None);
+        assert_eq!(task.timing.expires_at, env.block.time.seconds() + 3600);
+        let challenge = task.challenge.unwrap();
+        assert_eq!(challenge.challenger, addr("challenger"));
+        assert_eq!(challenge.disputed, serde_json::json!({ "winner": "Team A" }));
+
+        // The second round is final, and overturning the result returns the bond
+        let res = execute(deps.as_mut(), env.clone(), message_info(&addr("verifier"), &[]), resolve_msg("Team B")).unwrap();
+        assert_eq!(res.attributes[3].value, "completed");
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: addr("challenger").to_string(),
+                amount: coins(50, "earth"),
+            })
+        );
+        let task = task_info(deps.as_ref(), env);
+        assert!(matches!(task.status, Status::Completed { .. }));
+        assert_eq!(task.outcome, Some("Team B".to_string()));
+    }
+
+    #[test]
+    fn test_unchallenged_results_finalize() {
+        let mut deps = dispute_queue();
+        let resolve_msg = ExecuteMsg::ResolveOutcome {
+            task_id: TaskId::new(1),
+            winner: "Team A".to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), message_info(&addr("verifier"), &[]), resolve_msg.clone()).unwrap();
+
+        // Pending results can neither be completed again nor expired
+        let err = execute(deps.as_mut(), mock_env(), message_info(&addr("verifier"), &[]), resolve_msg).unwrap_err();
+        assert_eq!(err, ContractError::TaskPending);
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(100);
+        let challenge_msg = ExecuteMsg::Challenge {
+            task_id: TaskId::new(1),
+            proposed_result: serde_json::json!({ "winner": "Team B" }),
+        };
+        let err = execute(deps.as_mut(), env.clone(), message_info(&addr("challenger"), &coins(50, "earth")), challenge_msg)
+            .unwrap_err();
+        assert_eq!(err, ContractError::DisputeWindowClosed);
+
+        let finalize_msg = ExecuteMsg::Finalize { task_id: TaskId::new(1) };
+        execute(deps.as_mut(), env.clone(), message_info(&addr("anyone"), &[]), finalize_msg.clone()).unwrap();
+        let task = task_info(deps.as_ref(), env.clone());
+        assert_eq!(task.status, Status::Completed { completed: env.block.time.seconds() });
+        let err = execute(deps.as_mut(), env, message_info(&addr("anyone"), &[]), finalize_msg).unwrap_err();
+        assert_eq!(err, ContractError::TaskCompleted);
+    }
+
+    #[test]
+    fn test_failed_challenge_forfeits_bond() {
+        let mut deps = dispute_queue();
+        let resolve_msg = ExecuteMsg::ResolveOutcome {
+            task_id: TaskId::new(1),
+            winner: "Team A".to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), message_info(&addr("verifier"), &[]), resolve_msg.clone()).unwrap();
+        let challenge_msg = ExecuteMsg::Challenge {
+            task_id: TaskId::new(1),
+            proposed_result: serde_json::json!({ "winner": "Team B" }),
+        };
+        execute(deps.as_mut(), mock_env(), message_info(&addr("challenger"), &coins(60, "earth")), challenge_msg).unwrap();
+
+        // The second round agrees with the first, so the whole bond goes to the verifier
+        let res = execute(deps.as_mut(), mock_env(), message_info(&addr("verifier"), &[]), resolve_msg).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: addr("verifier").to_string(),
+                amount: coins(60, "earth"),
+            })
+        );
+    }
+
+    #[test]
+    fn test_arbitration() {
+        let mut deps = dispute_queue();
+        let resolve_msg = ExecuteMsg::ResolveOutcome {
+            task_id: TaskId::new(1),
+            winner: "Team A".to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), message_info(&addr("verifier"), &[]), resolve_msg.clone()).unwrap();
+        let challenge_msg = ExecuteMsg::Challenge {
+            task_id: TaskId::new(1),
+            proposed_result: serde_json::json!({ "winner": "Team B" }),
+        };
+        execute(deps.as_mut(), mock_env(), message_info(&addr("challenger"), &coins(50, "earth")), challenge_msg).unwrap();
+
+        // Only the verifier can escalate a second round that is still running
+        let escalate_msg = ExecuteMsg::Escalate { task_id: TaskId::new(1) };
+        let err = execute(deps.as_mut(), mock_env(), message_info(&addr("anyone"), &[]), escalate_msg.clone()).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized);
+        let expire_msg = ExecuteMsg::ExpireTask { task_id: TaskId::new(1) };
+        let mut expired = mock_env();
+        expired.block.time = expired.block.time.plus_seconds(3600);
+        let err = execute(deps.as_mut(), expired, message_info(&addr("requestor"), &[]), expire_msg).unwrap_err();
+        assert_eq!(err, ContractError::MustEscalate);
+
+        let res = execute(deps.as_mut(), mock_env(), message_info(&addr("verifier"), &[]), escalate_msg).unwrap();
+        assert_eq!(res.messages.len(), 1);
+        assert_eq!(res.messages[0].id, ARBITRATION_REPLY_ID);
+        assert_eq!(res.messages[0].reply_on, cosmwasm_std::ReplyOn::Error);
+        let task = task_info(deps.as_ref(), mock_env());
+        assert!(matches!(task.status, Status::Arbitration { .. }));
+
+        // The verifier has no say anymore, only the arbitrator
+        let err = execute(deps.as_mut(), mock_env(), message_info(&addr("verifier"), &[]), resolve_msg).unwrap_err();
+        assert_eq!(err, ContractError::TaskInArbitration);
+        let arbitrate_msg = |winner: &str| ExecuteMsg::Arbitrate {
+            task_id: TaskId::new(1),
+            result: serde_json::json!({ "winner": winner }),
+        };
+        let err = execute(deps.as_mut(), mock_env(), message_info(&addr("verifier"), &[]), arbitrate_msg("Team B")).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized);
+        let err = execute(deps.as_mut(), mock_env(), message_info(&addr("arbitrator"), &[]), arbitrate_msg("Team C")).unwrap_err();
+        assert_eq!(err, ContractError::InvalidRuling);
+
+        // Ruling for the challenger returns the bond
+        let res = execute(deps.as_mut(), mock_env(), message_info(&addr("arbitrator"), &[]), arbitrate_msg("Team B")).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: addr("challenger").to_string(),
+                amount: coins(50, "earth"),
+            })
+        );
+        let task = task_info(deps.as_ref(), mock_env());
+        assert!(matches!(task.status, Status::Completed { .. }));
+        assert_eq!(task.outcome, Some("Team B".to_string()));
+    }
+
+    #[test]
+    fn test_invalidate_task() {
+        let mut deps = dispute_queue();
+
+        // Operators vote for the reserved option, so tasks cannot use it
+        let create_msg = ExecuteMsg::Create {
+            description: "Will Team A win?".to_string(),
+            timeout: None,
+            payload: serde_json::json!({"event": "Team A vs Team B"}),
+            options: vec!["Team A".to_string(), INVALID_OPTION.to_string()],
+            proposed_winner: String::new(),
+            callback: None,
+        };
+        let err = execute(deps.as_mut(), mock_env(), message_info(&addr("requestor"), &[]), create_msg).unwrap_err();
+        assert_eq!(err, ContractError::ReservedOption(INVALID_OPTION.to_string()));
+
+        let invalidate_msg = |reason: &str| ExecuteMsg::InvalidateTask {
+            task_id: TaskId::new(1),
+            reason: reason.to_string(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), message_info(&addr("requestor"), &[]), invalidate_msg("cancelled"))
+            .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized);
+        let err = execute(deps.as_mut(), mock_env(), message_info(&addr("verifier"), &[]), invalidate_msg("")).unwrap_err();
+        assert_eq!(err, ContractError::MissingReason);
+
+        // Invalid is final right away, there is nothing to dispute
+        let res = execute(deps.as_mut(), mock_env(), message_info(&addr("verifier"), &[]), invalidate_msg("cancelled")).unwrap();
+        assert_eq!(res.attributes[0].value, "invalidate_task");
+        let task = task_info(deps.as_ref(), mock_env());
+        assert_eq!(task.status, Status::Invalid { reason: "cancelled".to_string() });
+        assert_eq!(task.outcome, None);
+
+        let resolve_msg = ExecuteMsg::ResolveOutcome {
+            task_id: TaskId::new(1),
+            winner: "Team A".to_string(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), message_info(&addr("verifier"), &[]), resolve_msg).unwrap_err();
+        assert_eq!(err, ContractError::TaskInvalid);
+    }
+}
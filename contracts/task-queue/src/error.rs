@@ -1,10 +1,13 @@
 rust:contracts/task-queue/src/error.rs
-use cosmwasm_std::{StdError, Uint128};
+use cosmwasm_std::{Decimal, StdError, Uint128};
+use cw_utils::PaymentError;
 use thiserror::Error;
-#[derive(Error, Debug)]
+#[derive(Error, Debug, PartialEq)]
 pub enum ContractError {
 #[error("{0}")]
 Std(#[from] StdError),
+#[error("{0}")]
+Payment(#[from] PaymentError),
 #[error("Unauthorized")]
 Unauthorized,
 #[error("Insufficient payment: needed {0} {1}")]
@@ -27,4 +30,78 @@ TaskNotExpired,
 CompleteTaskError,
 #[error("Failed to expire task")]
 ExpireTaskError,
+#[error("Options must be empty or at least two distinct, non-empty values")]
+InvalidOptions,
+#[error("Not one of the task options: {0}")]
+UnknownOption(String),
+#[error("Task has no options to resolve")]
+NotCategorical,
+#[error("Task result is pending until its dispute window closes")]
+TaskPending,
+#[error("Task has no pending result")]
+NotPending,
+#[error("Disputes are not enabled on this task queue")]
+DisputesDisabled,
+#[error("Dispute window must be positive and the bond non-zero")]
+InvalidDisputeConfig,
+#[error("Dispute window is closed")]
+DisputeWindowClosed,
+#[error("Dispute window is still open")]
+DisputeWindowOpen,
+#[error("Challenge must propose a different result")]
+ChallengeMatchesResult,
+#[error("No arbitrator configured")]
+NoArbitrator,
+#[error("Only challenged tasks can be escalated")]
+NotChallenged,
+#[error("Challenged tasks go to the arbitrator instead of expiring")]
+MustEscalate,
+#[error("Task is waiting for the arbitrator's ruling")]
+TaskInArbitration,
+#[error("Task is not in arbitration")]
+NotInArbitration,
+#[error("Ruling must name one of the task options as winner")]
+InvalidRuling,
+#[error("Task was marked invalid")]
+TaskInvalid,
+#[error("A reason is required to mark a task invalid")]
+MissingReason,
+#[error("Option is reserved: {0}")]
+ReservedOption(String),
+#[error("Cancel fee must be at most 1, got {0}")]
+InvalidCancelFee(Decimal),
+#[error("Keeper reward must be at most 1, got {0}")]
+InvalidKeeperReward(Decimal),
+#[error("Task was cancelled")]
+TaskCancelled,
+#[error("Operators already voted on the task, it can no longer be cancelled")]
+TaskHasVotes,
+#[error("Invalid payload: {0}")]
+InvalidPayload(String),
+#[error("Price feeds need a base, a different quote and an aggregation window")]
+InvalidPriceFeed,
+#[error("Binary events need a question, a resolution source and to close before the task expires")]
+InvalidBinaryEvent,
+#[error("Scalar range is empty: min {0} must be below max {1}")]
+InvalidRange(Decimal, Decimal),
+#[error("Options must be empty or match the payload")]
+OptionsMismatch,
+#[error("Callback gas limit must be greater than 0 and at most {0}")]
+InvalidCallbackGasLimit(u64),
+#[error("Task creation is paused")]
+Paused,
+#[error("Cannot migrate from a different contract: {0}")]
+WrongContract(String),
+#[error("Cannot migrate from {from} down to {to}")]
+CannotDowngrade { from: String, to: String },
+#[error("Invalid version: {0}")]
+SemVer(String),
+#[error("All tasks are migrated already")]
+NothingToMigrate,
+}
+
+impl From<semver::Error> for ContractError {
+    fn from(err: semver::Error) -> Self {
+        ContractError::SemVer(err.to_string())
+    }
 }
\ No newline at end of file
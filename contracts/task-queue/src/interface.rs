@@ -1,8 +1,7 @@
 use cw_orch::{interface, prelude::*};
-use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
 use cosmwasm_std::Empty;
 
-type MigrateMsg = cosmwasm_std::Empty;
 pub const CONTRACT_ID: &str = "task_queue";
 
 #[interface(InstantiateMsg, ExecuteMsg, QueryMsg, MigrateMsg, id = CONTRACT_ID)]
@@ -23,7 +22,9 @@ impl<Chain> Uploadable for Contract<Chain> {
                 crate::contract::execute,
                 crate::contract::instantiate,
                 crate::contract::query,
-            ), // .with_migrate(crate::contract::migrate),
+            )
+            .with_reply(crate::contract::reply)
+            .with_migrate(crate::contract::migrate),
         )
     }
 }
\ No newline at end of file
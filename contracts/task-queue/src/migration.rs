@@ -0,0 +1,132 @@
+//! State upgrades from earlier releases. Each step reads the state as the older version
+//! stored it and rewrites it in the current layout.
+
+use cosmwasm_std::{Addr, Env, Order, StdResult, Storage};
+use cw_storage_plus::Bound;
+
+use crate::state::{Config, Migrating, RequestorConfig, Status, Task, CONFIG, MIGRATING, TASKS};
+
+/// The state of 1.x releases
+pub mod v1 {
+    use cosmwasm_schema::cw_serde;
+    use cosmwasm_std::Addr;
+    use cw_storage_plus::{Item, Map};
+    use lavs_apis::id::TaskId;
+    use lavs_apis::tasks::{Status, TimeoutConfig};
+
+    use crate::msg::{RequestType, ResponseType};
+    use crate::state::{RequestorConfig, Timing};
+
+    pub const CONFIG: Item<Config> = Item::new("config");
+    pub const TASKS: Map<TaskId, Task> = Map::new("tasks");
+
+    #[cw_serde]
+    pub struct Config {
+        pub next_id: TaskId,
+        pub requestor: RequestorConfig,
+        pub timeout: TimeoutConfig,
+        pub verifier: Addr,
+    }
+
+    #[cw_serde]
+    pub struct Task {
+        pub description: String,
+        pub status: Status,
+        pub timing: Timing,
+        pub payload: RequestType,
+        pub result: Option<ResponseType>,
+    }
+}
+
+/// Turns off everything 2.0 added to the config and rewrites the first `limit` tasks, see
+/// `migrate_tasks` for the rest. `owner` takes over the role nobody had in 1.x.
+/// Returns whether every task was rewritten
+pub fn v1_to_v2(storage: &mut dyn Storage, env: &Env, owner: Addr, limit: usize) -> StdResult<bool> {
+    let old = v1::CONFIG.load(storage)?;
+    // 1.x did not record who created a task. With a fixed requestor it can only be them,
+    // otherwise the contract stands in. Only the requestor may cancel, so nobody can cancel these tasks
+    let requestor = match &old.requestor {
+        RequestorConfig::Fixed(addr) => addr.clone(),
+        RequestorConfig::OpenPayment(_) => env.contract.address.clone(),
+    };
+    let migrating = Migrating {
+        requestor,
+        end: old.next_id.clone(),
+        last: None,
+    };
+    MIGRATING.save(storage, &migrating)?;
+
+    let config = Config {
+        next_id: old.next_id,
+        requestor: old.requestor,
+        timeout: old.timeout,
+        verifier: old.verifier,
+        dispute: None,
+        arbitrator: None,
+        cancel_fee: None,
+        keeper_reward: None,
+        owner,
+        pending_owner: None,
+        guardian: None,
+        paused: false,
+    };
+    CONFIG.save(storage, &config)?;
+
+    let (_, done) = migrate_tasks(storage, limit)?;
+    Ok(done)
+}
+
+/// Fills in the new fields of the next `limit` tasks of 1.x, which adds them to the requestor
+/// and expiry indexes. Tasks that are not rewritten yet cannot be loaded.
+/// Returns how many were rewritten and whether that was the last of them
+pub fn migrate_tasks(storage: &mut dyn Storage, limit: usize) -> StdResult<(usize, bool)> {
+    let Some(mut migrating) = MIGRATING.may_load(storage)? else {
+        return Ok((0, true));
+    };
+    // tasks created since the migration share the map, but are stored in the new layout already
+    let tasks = v1::TASKS
+        .range(
+            storage,
+            migrating.last.clone().map(Bound::exclusive),
+            Some(Bound::exclusive(migrating.end.clone())),
+            Order::Ascending,
+        )
+        .take(limit + 1)
+        .collect::<StdResult<Vec<_>>>()?;
+    let done = tasks.len() <= limit;
+    let migrated = tasks.len().min(limit);
+
+    for (task_id, old) in tasks.into_iter().take(limit) {
+        let status = match old.status {
+            lavs_apis::tasks::Status::Open {} => Status::Open {},
+            lavs_apis::tasks::Status::Completed { completed } => Status::Completed { completed },
+            lavs_apis::tasks::Status::Expired {} => Status::Expired {},
+        };
+        let task = Task {
+            requestor: migrating.requestor.clone(),
+            description: old.description,
+            status,
+            timing: old.timing,
+            payload: old.payload,
+            options: vec![],
+            proposed_winner: String::new(),
+            outcome: None,
+            result: old.result,
+            // 1.x did not record payments per task, so these tasks neither refund nor pay out
+            payment: None,
+            challenge: None,
+            callback: None,
+            kind: None,
+        };
+        // the old value has the same status index entry, so there is nothing to remove first
+        TASKS.replace(storage, task_id.clone(), Some(&task), None)?;
+        migrating.last = Some(task_id);
+    }
+
+    if done {
+        MIGRATING.remove(storage);
+    } else {
+        MIGRATING.save(storage, &migrating)?;
+    }
+    Ok((migrated, done))
+}
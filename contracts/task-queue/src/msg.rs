@@ -1,13 +1,34 @@
-use cosmwasm_schema::cw_serde;
-use lavs_apis::interfaces::tasks::;
-use lavs_apis::interfaces::voting::;
-use cosmwasm_std::Decimal;
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{Addr, Coin, Decimal, Env, Order};
+use lavs_apis::id::TaskId;
+pub use crate::state::{
+    Callback, Challenge, Config, DisputeConfig, Status, TaskPayload, Timing, BINARY_OPTIONS, INVALID_OPTION,
+};
+pub use lavs_apis::tasks::{
+    ListOpenResponse, RequestType, Requestor, ResponseType, TaskMetadata, TimeoutInfo,
+};
 
 #[cw_serde]
 pub struct InstantiateMsg {
-    pub requestor: String,
+    pub requestor: Requestor,
     pub verifier: String, // Address of the Oracle Verifier contract
-    pub timeout: u64,
+    pub timeout: TimeoutInfo,
+    /// Holds verified results back for a dispute window. Leave empty to make them final right away
+    pub dispute: Option<DisputeConfig>,
+    /// Address that rules on challenged tasks the operators could not settle, e.g. a DAO or multisig
+    pub arbitrator: Option<String>,
+    /// Share of the payment kept when the requestor cancels a task, sent to the owner. Nothing if empty
+    pub cancel_fee: Option<Decimal>,
+    /// Share of each expired task's payment paid to the caller of `ExpireBatch`. Nothing if empty
+    pub keeper_reward: Option<Decimal>,
+}
+
+/// Upgrades from 1.x leave everything added since turned off
+#[cw_serde]
+pub struct MigrateMsg {
+    /// Most tasks of 1.x rewritten during the migration, `MAX_MIGRATE_BATCH` if empty.
+    /// `MigrateTasks` rewrites the rest
+    pub task_limit: Option<u32>,
 }
 
 #[cw_serde]
@@ -15,33 +36,253 @@ pub struct InstantiateMsg {
 #[cw_orch(disable_fields_sorting)]
 pub enum ExecuteMsg {
     /// Creates a new task
+    #[cw_orch(payable)]
     Create {
         description: String,
         timeout: Option<u64>,
+        /// Any JSON. Tagged with one of the `TaskPayload` kinds, it is validated
+        /// and decides whether the task is numeric or categorical
         payload: RequestType,
+        /// Possible outcomes for a categorical task, empty for a numeric one.
+        /// `invalid` is reserved for operators voting the task cannot be answered.
+        /// Can be left empty for typed payloads, which imply their options
         options: Vec<String>,
+        /// Must be one of `options`, or empty
         proposed_winner: String,
+        /// Contract to send `TaskResolved` once the task is final, instead of polling `TaskInfo`
+        callback: Option<CallbackInfo>,
     },
     /// Completes a task with the verified result
     CompleteTask {
         task_id: TaskId,
         result: ResponseType,
     },
-    /// Expires a task if not completed within the timeout
+    /// Completes a categorical task with the option the operators voted for.
+    /// Only callable by the verifier
+    ResolveOutcome {
+        task_id: TaskId,
+        winner: String,
+    },
+    /// Expires a task if not completed within the timeout and refunds its payment to the requestor.
+    /// Anyone may call this once the timeout passed
     ExpireTask {
         task_id: TaskId,
     },
+    /// Expires up to `limit` tasks past their timeout, earliest timeout first. Anyone may call this,
+    /// and is paid the keeper reward out of each task's payment. The rest goes back to the requestor
+    ExpireBatch {
+        limit: Option<u32>,
+    },
+    /// Withdraws an open task nobody voted on yet, refunding the payment minus the cancel fee.
+    /// Only callable by the task's requestor
+    CancelTask {
+        task_id: TaskId,
+    },
+    /// Disputes a pending result by posting the configured bond. The task re-opens for a
+    /// second round of votes. The bond is returned if that round overturns the result,
+    /// and forfeited to the verifier otherwise
+    #[cw_orch(payable)]
+    Challenge {
+        task_id: TaskId,
+        proposed_result: ResponseType,
+    },
+    /// Makes a pending result final once its dispute window is over. Anyone may call this
+    Finalize {
+        task_id: TaskId,
+    },
+    /// Marks a task that cannot be answered, e.g. an ambiguous question or a cancelled event.
+    /// Only callable by the verifier while the task is open, or by the arbitrator once escalated
+    InvalidateTask {
+        task_id: TaskId,
+        reason: String,
+    },
+    /// Hands a challenged task to the arbitrator, sending it `RequestArbitration`.
+    /// The verifier calls this when the second round fails, anyone may once that round expired
+    Escalate {
+        task_id: TaskId,
+    },
+    /// The arbitrator's final ruling on an escalated task
+    Arbitrate {
+        task_id: TaskId,
+        result: ResponseType,
+    },
+    /// Replaces the given settings, leaving the others as they are. Only callable by the owner
+    UpdateConfig {
+        requestor: Option<Requestor>,
+        timeout: Option<TimeoutInfo>,
+        verifier: Option<String>,
+        guardian: Option<String>,
+    },
+    /// Proposes a new owner, who takes over once it accepts. Only callable by the owner
+    TransferOwnership {
+        owner: String,
+    },
+    /// Makes the proposed owner the owner. Only callable by the proposed owner
+    AcceptOwnership {},
+    /// Stops new tasks from being created, e.g. while a data source is compromised.
+    /// Only callable by the guardian or the owner
+    Pause {},
+    /// Accepts new tasks again. Only callable by the guardian or the owner
+    Unpause {},
+    /// Rewrites up to `limit` more tasks left over from migrating 1.x. Anyone may call this
+    MigrateTasks {
+        limit: Option<u32>,
+    },
+}
+
+#[cw_serde]
+pub struct CallbackInfo {
+    pub contract: String,
+    /// Most gas the callback may use, at most `MAX_CALLBACK_GAS`. If it runs out or fails,
+    /// the task is final all the same
+    pub gas_limit: u64,
+}
+
+/// What the task queue sends a task's callback contract once the task is final
+#[cw_serde]
+pub enum CallbackExecuteMsg {
+    TaskResolved {
+        task_id: TaskId,
+        status: Status,
+        result: Option<ResponseType>,
+        /// The winning option of a completed categorical task
+        outcome: Option<String>,
+    },
+}
+
+/// What the task queue sends the arbitrator on escalation
+#[cw_serde]
+pub enum ArbitratorExecuteMsg {
+    RequestArbitration {
+        task_queue: String,
+        task_id: TaskId,
+        description: String,
+        payload: RequestType,
+        options: Vec<String>,
+        /// The result the operators first reported
+        disputed: ResponseType,
+        /// The result the challenger proposed instead
+        proposed_result: ResponseType,
+    },
 }
 
+/// What the task queue asks the verifier before a task is cancelled
 #[cw_serde]
+#[derive(QueryResponses)]
+pub enum VerifierQueryMsg {
+    /// How many operators voted on a task of the given task queue
+    #[returns(VoteCountResponse)]
+    VoteCount {
+        task_contract: String,
+        task_id: TaskId,
+    },
+}
+
+#[cw_serde]
+pub struct VoteCountResponse {
+    pub count: u32,
+}
+
+#[cw_serde]
+#[derive(QueryResponses, cw_orch::QueryFns)]
+#[cw_orch(disable_fields_sorting)]
 pub enum QueryMsg {
-    /// Lists all open tasks with optional pagination
+    /// Lists open tasks that have not timed out yet, newest first unless `order` is ascending
+    #[returns(ListOpenResponse)]
     ListOpen {
         start_after: Option<TaskId>,
         limit: Option<u32>,
+        order: Option<Order>,
+    },
+    /// Lists completed tasks. Results still within their dispute window are not included
+    #[returns(ListTasksResponse)]
+    ListCompleted {
+        start_after: Option<TaskId>,
+        limit: Option<u32>,
+        order: Option<Order>,
+    },
+    /// Lists expired tasks, including open ones past their timeout.
+    /// Invalid and cancelled tasks are not included, see `ListByRequestor` or `TaskInfo` for them
+    #[returns(ListTasksResponse)]
+    ListExpired {
+        start_after: Option<TaskId>,
+        limit: Option<u32>,
+        order: Option<Order>,
     },
     /// Retrieves detailed information about a specific task
+    #[returns(TaskInfoResponse)]
     TaskInfo {
         task_id: TaskId,
     },
+    /// Lists all tasks created by one requestor, oldest first
+    #[returns(ListTasksResponse)]
+    ListByRequestor {
+        requestor: String,
+        start_after: Option<TaskId>,
+        limit: Option<u32>,
+    },
+    /// The current settings, owner and proposed owner
+    #[returns(Config)]
+    Config {},
+}
+
+#[cw_serde]
+pub struct ListTasksResponse {
+    pub tasks: Vec<TaskInfoResponse>,
+}
+
+#[cw_serde]
+pub struct TaskInfoResponse {
+    pub id: TaskId,
+    pub requestor: Addr,
+    pub description: String,
+    pub status: Status,
+    pub timing: Timing,
+    pub payload: RequestType,
+    pub options: Vec<String>,
+    pub proposed_winner: String,
+    /// The winning option of a resolved categorical task
+    pub outcome: Option<String>,
+    pub result: Option<ResponseType>,
+    /// What the requestor paid, paid out to the operators on completion
+    pub payment: Option<Coin>,
+    /// The dispute that re-opened the task, if any
+    pub challenge: Option<Challenge>,
+    /// Notified once the task is final
+    pub callback: Option<Callback>,
+    /// What kind of task the payload describes, if it is tagged with one
+    pub kind: Option<TaskPayload>,
+}
+
+/// Where a task stands for contracts that trade on its outcome
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MarketPhase {
+    /// The task is open and unchallenged, trading goes on
+    Open,
+    /// The task has a result that is not final yet. Nothing trades or pays out
+    Pending,
+    /// The task can no longer complete with an option: it expired, was cancelled or marked invalid
+    Expired,
+    /// The task completed with this option
+    Resolved(String),
+}
+
+impl TaskInfoResponse {
+    /// Open tasks past their expiry can no longer be completed, so they count as expired.
+    /// Results within their dispute window, challenged or in arbitration are not final yet
+    pub fn market_phase(&self, env: &Env) -> MarketPhase {
+        let open = self.timing.expires_at > env.block.time.seconds();
+        match &self.status {
+            Status::Completed { .. } => match &self.outcome {
+                Some(winner) => MarketPhase::Resolved(winner.clone()),
+                None => MarketPhase::Expired,
+            },
+            Status::Pending { .. } | Status::Arbitration { .. } => MarketPhase::Pending,
+            Status::Open {} if open && self.challenge.is_some() => MarketPhase::Pending,
+            Status::Open {} if open => MarketPhase::Open,
+            Status::Open {} | Status::Expired {} | Status::Invalid { .. } | Status::Cancelled {} => {
+                MarketPhase::Expired
+            }
+        }
+    }
 }
@@ -1,21 +1,28 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Coin, Deps, Env, MessageInfo, StdError};
+use cosmwasm_std::{Addr, Coin, Decimal, Deps, Env, MessageInfo, StdError};
 use cw_storage_plus::{Index, IndexList, IndexedMap, Item, MultiIndex};
-use cw_utils::must_pay;
+use cw_utils::{must_pay, nonpayable};
 use lavs_apis::id::TaskId;
-use lavs_apis::tasks::{Requestor, Status, TimeoutConfig, ResponseType};
+use lavs_apis::tasks::{Requestor, TimeoutConfig, ResponseType};
+use serde_json::json;
 use crate::error::ContractError;
 use crate::msg::{self, InstantiateMsg, RequestType};
 
 pub const CONFIG: Item<Config> = Item::new("config");
+/// Only while tasks of 1.x are left to rewrite after a migration
+pub const MIGRATING: Item<Migrating> = Item::new("migrating");
 
 pub struct TaskIndexes<'a> {
     pub status: MultiIndex<'a, &'a str, Task, TaskId>,
+    pub requestor: MultiIndex<'a, Addr, Task, TaskId>,
+    /// By status and then timeout, so lapsed open tasks can be found without scanning the others
+    pub expiry: MultiIndex<'a, (&'a str, u64), Task, TaskId>,
 }
 
 impl<'a> IndexList<Task> for TaskIndexes<'a> {
     fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Task>> + '_> {
-        Box::new(std::iter::once(&self.status as &dyn Index<Task>))
+        let v: Vec<&dyn Index<Task>> = vec![&self.status, &self.requestor, &self.expiry];
+        Box::new(v.into_iter())
     }
 }
 
@@ -23,32 +30,100 @@ pub const TASKS: IndexedMap<TaskId, Task, TaskIndexes<'static>> = IndexedMap::ne
     "tasks",
     TaskIndexes {
         status: MultiIndex::new(|_, d: &Task| d.status.as_str(), "tasks", "tasks_status"),
+        requestor: MultiIndex::new(|_, d: &Task| d.requestor.clone(), "tasks", "tasks_requestor"),
+        expiry: MultiIndex::new(
+            |_, d: &Task| (d.status.as_str(), d.timing.expires_at),
+            "tasks",
+            "tasks_expiry",
+        ),
     },
 );
 
+/// How far the tasks of 1.x are rewritten
+#[cw_serde]
+pub struct Migrating {
+    /// Who the rewritten tasks are recorded as requested by
+    pub requestor: Addr,
+    /// The first id handed out after the migration. Later tasks are stored in the new layout
+    pub end: TaskId,
+    /// The last task rewritten so far
+    pub last: Option<TaskId>,
+}
+
 #[cw_serde]
 pub struct Config {
     pub next_id: TaskId,
     pub requestor: RequestorConfig,
     pub timeout: TimeoutConfig,
     pub verifier: Addr, // Address of the Oracle Verifier contract
+    /// Lets anyone dispute verified results for a while. Results are final right away without it
+    pub dispute: Option<DisputeConfig>,
+    /// Rules on challenged tasks the operators could not settle
+    pub arbitrator: Option<Addr>,
+    /// Share of the payment kept when the requestor cancels a task, paid to the owner
+    pub cancel_fee: Option<Decimal>,
+    /// Share of an expired task's payment paid to whoever expires it in a batch
+    pub keeper_reward: Option<Decimal>,
+    /// May update the config and hand over ownership
+    pub owner: Addr,
+    /// Proposed by the owner, takes over once it accepts
+    pub pending_owner: Option<Addr>,
+    /// May pause and unpause the queue, next to the owner. Set by the owner with `UpdateConfig`
+    pub guardian: Option<Addr>,
+    /// No tasks are created while set. Everything else, including refunds, keeps working
+    pub paused: bool,
 }
 
 impl Config {
-    pub fn validate(deps: Deps, input: InstantiateMsg) -> Result<Self, ContractError> {
+    pub fn validate(deps: Deps, owner: Addr, input: InstantiateMsg) -> Result<Self, ContractError> {
         let requestor = RequestorConfig::validate(deps, input.requestor)?;
         let timeout = validate_timeout_info(input.timeout)?;
         let verifier = deps.api.addr_validate(&input.verifier)?;
+        let arbitrator = input
+            .arbitrator
+            .map(|arbitrator| deps.api.addr_validate(&arbitrator))
+            .transpose()?;
+        if let Some(dispute) = &input.dispute {
+            if dispute.window == 0 || dispute.bond.amount.is_zero() {
+                return Err(ContractError::InvalidDisputeConfig);
+            }
+        }
+        if let Some(fee) = input.cancel_fee {
+            if fee > Decimal::one() {
+                return Err(ContractError::InvalidCancelFee(fee));
+            }
+        }
+        if let Some(reward) = input.keeper_reward {
+            if reward > Decimal::one() {
+                return Err(ContractError::InvalidKeeperReward(reward));
+            }
+        }
 
         Ok(Config {
             next_id: TaskId::new(1),
             requestor,
             timeout,
             verifier,
+            dispute: input.dispute,
+            arbitrator,
+            cancel_fee: input.cancel_fee,
+            keeper_reward: input.keeper_reward,
+            owner,
+            pending_owner: None,
+            guardian: None,
+            paused: false,
         })
     }
 }
 
+#[cw_serde]
+pub struct DisputeConfig {
+    /// Seconds a verified result can be challenged before it is final
+    pub window: u64,
+    /// What a challenger has to put up
+    pub bond: Coin,
+}
+
 #[cw_serde]
 pub enum RequestorConfig {
     Fixed(Addr),
@@ -65,24 +140,27 @@ impl RequestorConfig {
         }
     }
 
-    pub fn check_requestor(&self, info: &MessageInfo) -> Result<(), ContractError> {
+    /// Returns the payment for an open task queue. A fixed requestor pays nothing
+    pub fn check_requestor(&self, info: &MessageInfo) -> Result<Option<Coin>, ContractError> {
         match self {
             RequestorConfig::Fixed(addr) => {
                 if info.sender != addr {
                     return Err(ContractError::Unauthorized);
                 }
+                nonpayable(info)?;
+                Ok(None)
             }
             RequestorConfig::OpenPayment(needed) => {
                 let paid = must_pay(info, &needed.denom)?;
                 if paid < needed.amount {
                     return Err(ContractError::InsufficientPayment(
-                        needed.amount.u128(),
+                        needed.amount,
                         needed.denom.clone(),
                     ));
                 }
+                Ok(Some(Coin::new(paid, needed.denom.clone())))
             }
         }
-        Ok(())
     }
 }
 
@@ -111,6 +189,136 @@ pub fn validate_timeout_info(input: msg::TimeoutInfo) -> Result<TimeoutConfig, C
     })
 }
 
+/// Operators vote for this in categorical mode if the task cannot be answered
+pub const INVALID_OPTION: &str = "invalid";
+
+/// The options of a `TaskPayload::BinaryEvent`
+pub const BINARY_OPTIONS: [&str; 2] = ["yes", "no"];
+
+/// The kinds of task the queue knows how to check. Payloads tagged with one of them,
+/// e.g. `{"price_feed": {...}}`, are validated on creation and tell operators and the verifier
+/// what to expect. Any other JSON is passed on as is
+#[cw_serde]
+pub enum TaskPayload {
+    /// Numeric: the price of `base` in `quote`, averaged over `aggregation_window` seconds
+    PriceFeed {
+        base: String,
+        quote: String,
+        aggregation_window: u64,
+    },
+    /// Categorical with the options "yes" and "no". The event closes at `close_time`,
+    /// in UNIX seconds, and is looked up at `resolution_source`
+    BinaryEvent {
+        question: String,
+        resolution_source: String,
+        close_time: u64,
+    },
+    /// Numeric: a value between `min` and `max`, both included
+    Scalar {
+        min: Decimal,
+        max: Decimal,
+        unit: String,
+    },
+    /// Categorical with these options
+    Categorical {
+        options: Vec<String>,
+    },
+}
+
+impl TaskPayload {
+    const KINDS: [&'static str; 4] = ["price_feed", "binary_event", "scalar", "categorical"];
+
+    /// `None` if the payload is not tagged with one of the kinds
+    pub fn parse(payload: &RequestType) -> Result<Option<Self>, ContractError> {
+        match payload.as_object() {
+            Some(fields) if fields.len() == 1 && fields.keys().all(|kind| Self::KINDS.contains(&kind.as_str())) => {
+                serde_json::from_value(payload.clone())
+                    .map(Some)
+                    .map_err(|err| ContractError::InvalidPayload(err.to_string()))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Returns the options of the task. `options` as given on creation must be empty
+    /// or the same as the payload implies
+    pub fn validate(&self, timing: &Timing, options: Vec<String>) -> Result<Vec<String>, ContractError> {
+        let implied = match self {
+            TaskPayload::PriceFeed { base, quote, aggregation_window } => {
+                if base.is_empty() || quote.is_empty() || base == quote || *aggregation_window == 0 {
+                    return Err(ContractError::InvalidPriceFeed);
+                }
+                vec![]
+            }
+            TaskPayload::BinaryEvent { question, resolution_source, close_time } => {
+                if question.is_empty()
+                    || resolution_source.is_empty()
+                    || *close_time < timing.created_at
+                    || *close_time >= timing.expires_at
+                {
+                    return Err(ContractError::InvalidBinaryEvent);
+                }
+                BINARY_OPTIONS.iter().map(|option| option.to_string()).collect()
+            }
+            TaskPayload::Scalar { min, max, .. } => {
+                if min >= max {
+                    return Err(ContractError::InvalidRange(*min, *max));
+                }
+                vec![]
+            }
+            TaskPayload::Categorical { options } => {
+                // empty options would make it a numeric task
+                if options.is_empty() {
+                    return Err(ContractError::InvalidOptions);
+                }
+                options.clone()
+            }
+        };
+        if !options.is_empty() && options != implied {
+            return Err(ContractError::OptionsMismatch);
+        }
+        Ok(implied)
+    }
+
+    /// The range numeric results must fall into, if any
+    pub fn range(&self) -> Option<(Decimal, Decimal)> {
+        match self {
+            TaskPayload::Scalar { min, max, .. } => Some((*min, *max)),
+            _ => None,
+        }
+    }
+
+    /// When the outcome becomes known, if the task is about an event. Nothing can be voted before
+    pub fn close_time(&self) -> Option<u64> {
+        match self {
+            TaskPayload::BinaryEvent { close_time, .. } => Some(*close_time),
+            _ => None,
+        }
+    }
+}
+
+/// Categorical tasks need at least two distinct options, and the proposed winner must be one of them
+pub fn validate_options(options: &[String], proposed_winner: &str) -> Result<(), ContractError> {
+    if options.is_empty() {
+        return Ok(());
+    }
+    if options.len() < 2 {
+        return Err(ContractError::InvalidOptions);
+    }
+    for (i, option) in options.iter().enumerate() {
+        if option.is_empty() || options[..i].contains(option) {
+            return Err(ContractError::InvalidOptions);
+        }
+        if option == INVALID_OPTION {
+            return Err(ContractError::ReservedOption(option.clone()));
+        }
+    }
+    if !proposed_winner.is_empty() && !options.iter().any(|o| o == proposed_winner) {
+        return Err(ContractError::UnknownOption(proposed_winner.to_string()));
+    }
+    Ok(())
+}
+
 pub fn check_timeout(config: &TimeoutConfig, timeout: Option<u64>) -> Result<u64, ContractError> {
     match timeout {
         Some(t) if t < config.minimum => Err(ContractError::TimeoutTooShort(config.minimum)),
@@ -120,21 +328,155 @@ pub fn check_timeout(config: &TimeoutConfig, timeout: Option<u64>) -> Result<u64
     }
 }
 
+/// The task status, with the dispute window on top of the shared one
+#[cw_serde]
+pub enum Status {
+    Open {},
+    /// The verifier reported a result, which can be challenged until `dispute_ends`
+    Pending { dispute_ends: u64 },
+    /// The operators could not settle a challenged task, it waits for the arbitrator's ruling
+    Arbitration { escalated: u64 },
+    Completed { completed: u64 },
+    Expired {},
+    /// The task cannot be answered, e.g. the question is ambiguous or the event was cancelled
+    Invalid { reason: String },
+    /// The requestor withdrew the task before anyone voted on it
+    Cancelled {},
+}
+
+impl Status {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Status::Open {} => "open",
+            Status::Pending { .. } => "pending",
+            Status::Arbitration { .. } => "arbitration",
+            Status::Completed { .. } => "completed",
+            Status::Expired {} => "expired",
+            Status::Invalid { .. } => "invalid",
+            Status::Cancelled {} => "cancelled",
+        }
+    }
+
+    /// Whether the task will never change again
+    pub fn is_final(&self) -> bool {
+        match self {
+            Status::Open {} | Status::Pending { .. } | Status::Arbitration { .. } => false,
+            Status::Completed { .. } | Status::Expired {} | Status::Invalid { .. } | Status::Cancelled {} => true,
+        }
+    }
+}
+
+/// Pending and arbitrated results are not final yet, so they show up as open to anyone
+/// who only knows the shared status. Invalid and cancelled tasks will never complete, like expired ones
+impl From<Status> for lavs_apis::tasks::Status {
+    fn from(val: Status) -> Self {
+        match val {
+            Status::Open {} | Status::Pending { .. } | Status::Arbitration { .. } => {
+                lavs_apis::tasks::Status::Open {}
+            }
+            Status::Completed { completed } => lavs_apis::tasks::Status::Completed { completed },
+            Status::Expired {} | Status::Invalid { .. } | Status::Cancelled {} => {
+                lavs_apis::tasks::Status::Expired {}
+            }
+        }
+    }
+}
+
+/// Contract told about the task once it is final, see `msg::CallbackExecuteMsg`
+#[cw_serde]
+pub struct Callback {
+    pub contract: Addr,
+    /// Most gas the callback may use
+    pub gas_limit: u64,
+}
+
+/// A dispute of the first verified result of a task
+#[cw_serde]
+pub struct Challenge {
+    pub challenger: Addr,
+    pub bond: Coin,
+    /// What the challenger claims the result should be
+    pub proposed_result: ResponseType,
+    /// The result that was challenged
+    pub disputed: ResponseType,
+    /// UNIX seconds at which the second round of votes started
+    pub challenged_at: u64,
+}
+
 #[cw_serde]
 pub struct Task {
+    /// Who created the task, and may cancel it
+    pub requestor: Addr,
     pub description: String,
     pub status: Status,
     pub timing: Timing,
     pub payload: RequestType,
+    /// The possible outcomes of a categorical task. Empty for numeric tasks
+    pub options: Vec<String>,
+    /// The outcome the requestor expects, if any
+    pub proposed_winner: String,
+    /// The winning option, once the verifier resolved a categorical task
+    pub outcome: Option<String>,
     pub result: Option<ResponseType>, // Stores the result from Oracle Verifier
+    /// What the requestor paid. Sent to the verifier as operator rewards on completion
+    pub payment: Option<Coin>,
+    /// Set once somebody disputed the first result, re-opening the task
+    pub challenge: Option<Challenge>,
+    /// Notified once the task completes, expires, is marked invalid or cancelled
+    pub callback: Option<Callback>,
+    /// What kind of task the payload describes, if it is tagged with one
+    pub kind: Option<TaskPayload>,
 }
 
 impl Task {
+    pub fn new(
+        env: &Env,
+        requestor: Addr,
+        description: String,
+        timeout: u64,
+        payload: RequestType,
+        options: Vec<String>,
+        proposed_winner: String,
+        payment: Option<Coin>,
+        callback: Option<Callback>,
+    ) -> Result<Self, ContractError> {
+        let timing = Timing::new(env, timeout);
+        let kind = TaskPayload::parse(&payload)?;
+        let options = match &kind {
+            Some(kind) => kind.validate(&timing, options)?,
+            None => options,
+        };
+        validate_options(&options, &proposed_winner)?;
+        Ok(Task {
+            requestor,
+            description,
+            status: Status::Open {},
+            timing,
+            payload,
+            options,
+            proposed_winner,
+            outcome: None,
+            result: None,
+            payment,
+            challenge: None,
+            callback,
+            kind,
+        })
+    }
+
+    pub fn is_categorical(&self) -> bool {
+        !self.options.is_empty()
+    }
+
     pub fn validate_status(&self, env: &Env) -> Status {
         match self.status {
             Status::Open {} if !self.timing.is_expired(env) => self.status.clone(),
             Status::Expired {} | Status::Open {} => Status::Expired {},
-            Status::Completed { .. } => self.status.clone(),
+            Status::Pending { .. }
+            | Status::Arbitration { .. }
+            | Status::Completed { .. }
+            | Status::Invalid { .. }
+            | Status::Cancelled {} => self.status.clone(),
         }
     }
 }
@@ -164,24 +506,200 @@ impl Timing {
 }
 
 impl Task {
-    pub fn complete(&mut self, env: &Env, result: ResponseType) -> Result<(), ContractError> {
+    /// Stores the verified result. With a dispute window, the first result stays pending
+    /// until the window closes. The result of a challenged task is final right away
+    pub fn complete(
+        &mut self,
+        env: &Env,
+        result: ResponseType,
+        dispute: Option<&DisputeConfig>,
+    ) -> Result<(), ContractError> {
         match self.status {
             Status::Open {} if !self.timing.is_expired(env) => {}
             Status::Open {} | Status::Expired {} => return Err(ContractError::TaskExpired),
+            Status::Pending { .. } => return Err(ContractError::TaskPending),
+            Status::Arbitration { .. } => return Err(ContractError::TaskInArbitration),
+            Status::Completed { .. } => return Err(ContractError::TaskCompleted),
+            Status::Invalid { .. } => return Err(ContractError::TaskInvalid),
+            Status::Cancelled {} => return Err(ContractError::TaskCancelled),
+        };
+
+        let now = env.block.time.seconds();
+        self.status = match dispute {
+            Some(dispute) if self.challenge.is_none() => Status::Pending {
+                dispute_ends: now + dispute.window,
+            },
+            _ => Status::Completed { completed: now },
+        };
+        self.result = Some(result);
+        Ok(())
+    }
+
+    /// Completes a categorical task with the winning option
+    pub fn resolve(
+        &mut self,
+        env: &Env,
+        winner: String,
+        dispute: Option<&DisputeConfig>,
+    ) -> Result<(), ContractError> {
+        if !self.is_categorical() {
+            return Err(ContractError::NotCategorical);
+        }
+        if !self.options.contains(&winner) {
+            return Err(ContractError::UnknownOption(winner));
+        }
+        self.complete(env, json!({ "winner": winner }), dispute)?;
+        self.outcome = Some(winner);
+        Ok(())
+    }
+
+    /// Disputes the pending result. The task re-opens for a second round of votes,
+    /// with as much time as it was originally given
+    pub fn challenge(
+        &mut self,
+        env: &Env,
+        challenger: Addr,
+        bond: Coin,
+        proposed_result: ResponseType,
+    ) -> Result<(), ContractError> {
+        let now = env.block.time.seconds();
+        match self.status {
+            Status::Pending { dispute_ends } if dispute_ends > now => {}
+            Status::Pending { .. } => return Err(ContractError::DisputeWindowClosed),
+            _ => return Err(ContractError::NotPending),
+        };
+        let disputed = self.result.take().ok_or(ContractError::NotPending)?;
+        if proposed_result == disputed {
+            return Err(ContractError::ChallengeMatchesResult);
+        }
+
+        self.outcome = None;
+        self.timing.expires_at = now + (self.timing.expires_at - self.timing.created_at);
+        self.status = Status::Open {};
+        self.challenge = Some(Challenge {
+            challenger,
+            bond,
+            proposed_result,
+            disputed,
+            challenged_at: now,
+        });
+        Ok(())
+    }
+
+    /// Makes the pending result final once nobody can challenge it anymore
+    pub fn finalize(&mut self, env: &Env) -> Result<(), ContractError> {
+        let now = env.block.time.seconds();
+        match self.status {
+            Status::Pending { dispute_ends } if dispute_ends <= now => {}
+            Status::Pending { .. } => return Err(ContractError::DisputeWindowOpen),
             Status::Completed { .. } => return Err(ContractError::TaskCompleted),
+            Status::Open {}
+            | Status::Arbitration { .. }
+            | Status::Expired {}
+            | Status::Invalid { .. }
+            | Status::Cancelled {} => return Err(ContractError::NotPending),
         };
 
-        self.status = Status::Completed { result: result.clone() };
+        self.status = Status::Completed { completed: now };
+        Ok(())
+    }
+
+    /// Hands a challenged task the operators could not settle to the arbitrator
+    pub fn escalate(&mut self, env: &Env) -> Result<(), ContractError> {
+        match self.status {
+            Status::Open {} if self.challenge.is_some() => {}
+            _ => return Err(ContractError::NotChallenged),
+        };
+
+        self.status = Status::Arbitration {
+            escalated: env.block.time.seconds(),
+        };
+        Ok(())
+    }
+
+    /// Completes an escalated task with the arbitrator's ruling. On a categorical task,
+    /// the ruling must name one of the options as `winner`, like the verifier's results do
+    pub fn arbitrate(&mut self, env: &Env, result: ResponseType) -> Result<(), ContractError> {
+        if !matches!(self.status, Status::Arbitration { .. }) {
+            return Err(ContractError::NotInArbitration);
+        }
+        if self.is_categorical() {
+            let winner = result
+                .get("winner")
+                .and_then(|winner| winner.as_str())
+                .filter(|winner| self.options.iter().any(|option| option == winner))
+                .ok_or(ContractError::InvalidRuling)?;
+            self.outcome = Some(winner.to_string());
+        }
+
+        self.status = Status::Completed {
+            completed: env.block.time.seconds(),
+        };
         self.result = Some(result);
         Ok(())
     }
 
+    /// Marks a task that cannot be answered. This is final right away, without a dispute window.
+    /// The verifier does so while the task is open, the arbitrator when ruling on it
+    pub fn invalidate(&mut self, env: &Env, reason: String) -> Result<(), ContractError> {
+        match self.status {
+            Status::Open {} if !self.timing.is_expired(env) => {}
+            Status::Arbitration { .. } => {}
+            Status::Open {} | Status::Expired {} => return Err(ContractError::TaskExpired),
+            Status::Pending { .. } => return Err(ContractError::TaskPending),
+            Status::Completed { .. } => return Err(ContractError::TaskCompleted),
+            Status::Invalid { .. } => return Err(ContractError::TaskInvalid),
+            Status::Cancelled {} => return Err(ContractError::TaskCancelled),
+        };
+        if reason.is_empty() {
+            return Err(ContractError::MissingReason);
+        }
+
+        self.status = Status::Invalid { reason };
+        self.outcome = None;
+        self.result = None;
+        Ok(())
+    }
+
+    /// Withdraws a task nobody voted on yet. Whether anyone did is up to the verifier,
+    /// this only checks the task itself is still open and unchallenged
+    pub fn cancel(&mut self, env: &Env) -> Result<(), ContractError> {
+        match self.status {
+            Status::Open {} if self.challenge.is_some() => return Err(ContractError::TaskHasVotes),
+            Status::Open {} if !self.timing.is_expired(env) => {}
+            Status::Open {} | Status::Expired {} => return Err(ContractError::TaskExpired),
+            Status::Pending { .. } => return Err(ContractError::TaskPending),
+            Status::Arbitration { .. } => return Err(ContractError::TaskInArbitration),
+            Status::Completed { .. } => return Err(ContractError::TaskCompleted),
+            Status::Invalid { .. } => return Err(ContractError::TaskInvalid),
+            Status::Cancelled {} => return Err(ContractError::TaskCancelled),
+        };
+
+        self.status = Status::Cancelled {};
+        Ok(())
+    }
+
+    /// Whether the second round overturned the challenged result.
+    /// `None` until a challenged task completes. A challenged task turning out
+    /// invalid overturns the result as well
+    pub fn challenge_upheld(&self) -> Option<bool> {
+        match (&self.status, &self.challenge, &self.result) {
+            (Status::Completed { .. }, Some(challenge), Some(result)) => Some(result != &challenge.disputed),
+            (Status::Invalid { .. }, Some(_), _) => Some(true),
+            _ => None,
+        }
+    }
+
     pub fn expire(&mut self, env: &Env) -> Result<(), ContractError> {
         match self.status {
             Status::Open {} if self.timing.is_expired(env) => {}
             Status::Open {} => return Err(ContractError::TaskNotExpired),
             Status::Expired {} => return Err(ContractError::TaskExpired),
+            Status::Pending { .. } => return Err(ContractError::TaskPending),
+            Status::Arbitration { .. } => return Err(ContractError::TaskInArbitration),
             Status::Completed { .. } => return Err(ContractError::TaskCompleted),
+            Status::Invalid { .. } => return Err(ContractError::TaskInvalid),
+            Status::Cancelled {} => return Err(ContractError::TaskCancelled),
         };
 
         self.status = Status::Expired {};
@@ -1,19 +1,15 @@
 use cw_orch::environment::{ChainState, CwEnv, Environment, IndexResponse, QueryHandler};
 use cw_orch::prelude::*;
 use lavs_apis::id::TaskId;
-use lavs_apis::tasks::TaskStatus;
 use serde_json::json;
 
 use crate::error::ContractError;
 use crate::interface::Contract as TaskContract;
 use crate::msg::{
-    CompletedTaskOverview, InstantiateMsg, ListCompletedResponse, ListOpenResponse,
-    OpenTaskOverview, Requestor, Status, TimeoutInfo,
+    ExecuteMsgFns, InstantiateMsg, ListOpenResponse, ListTasksResponse, QueryMsgFns, Requestor, Status,
+    TimeoutInfo,
 };
-
-// FIXME: any way to get these as one import, rather than import all sub traits?
-// Maybe combining them in a super-trait?
-use crate::msg::{CustomExecuteMsgFns, CustomQueryMsgFns, TaskExecuteMsgFns, TaskQueryMsgFns};
+use crate::state::RequestorConfig;
 
 use lavs_orch::{Addressable, AltSigner};
 
@@ -36,6 +32,10 @@ where
         requestor: Requestor::Fixed(chain.sender_addr().into()),
         timeout: mock_timeout(timeout),
         verifier: verifier.addr().into(),
+        dispute: None,
+        arbitrator: None,
+        cancel_fee: None,
+        keeper_reward: None,
     };
 
     let contract = setup(chain.clone(), msg);
@@ -71,30 +71,30 @@ where
     assert_eq!(two, TaskId::new(one.u64() + 1));
 
     // query for open tasks
-    let open = contract.list_open(None, None).unwrap();
+    let open = contract.list_open(None, None, None).unwrap();
     assert_eq!(open.tasks.len(), 2);
-    let closed = contract.list_completed(None, None).unwrap();
+    let closed = contract.list_completed(None, None, None).unwrap();
     assert_eq!(closed.tasks.len(), 0);
 
     // fail to verify one task
-    let err = contract.complete(one, result.clone()).unwrap_err();
+    let err = contract.complete_task(one, result.clone()).unwrap_err();
     println!("Bad verfier error: {:?}", err);
 
     // complete one task
     contract
         .call_as(&verifier)
-        .complete(one, result.clone())
+        .complete_task(one, result.clone())
         .unwrap();
     let end = get_time(contract.environment());
 
     // check the list queries
-    let open = contract.list_open(None, None).unwrap();
+    let open = contract.list_open(None, None, None).unwrap();
     assert_eq!(open.tasks.len(), 1);
-    let closed = contract.list_completed(None, None).unwrap();
+    let closed = contract.list_completed(None, None, None).unwrap();
     assert_eq!(closed.tasks.len(), 1);
 
     // check the details of the completed task
-    let details = contract.task(one).unwrap();
+    let details = contract.task_info(one).unwrap();
     // We need to allow a little leeway, as start and end times may be off by a second
     // Thus no equals comparision, but the destructuring test on status
     assert_eq!(details.id, one);
@@ -128,15 +128,15 @@ where
     assert_eq!(config.timeout.maximum, 400);
     assert_eq!(
         config.requestor,
-        Requestor::Fixed(chain.sender_addr().into())
+        RequestorConfig::Fixed(chain.sender_addr())
     );
-    assert_eq!(config.verifier, verifier.addr().into_string());
+    assert_eq!(config.verifier, verifier.addr());
 
     // Note: you need the root error to get that from the contract.
     // {} will just show the method call,
     // {:#} or {:?} will show the full error chain (but :# is nicer to read)
     let err = contract
-        .create("Too Short".to_string(), Some(4), payload.clone(), &[])
+        .create("Too Short".to_string(), Some(4), payload.clone(), vec![], String::new(), None, &[])
         .unwrap_err();
     assert!(
         err.root()
@@ -147,7 +147,7 @@ where
     );
 
     let one = contract
-        .create("One".to_string(), None, payload.clone(), &[])
+        .create("One".to_string(), None, payload.clone(), vec![], String::new(), None, &[])
         .unwrap();
     let task_one = one.event_attr_value("wasm", "task_id").unwrap();
     assert_eq!(task_one, "1");
@@ -155,7 +155,7 @@ where
     assert_eq!(task_one, 1u64);
 
     let two = contract
-        .create("Two".to_string(), None, payload.clone(), &[])
+        .create("Two".to_string(), None, payload.clone(), vec![], String::new(), None, &[])
         .unwrap();
     let task_two = get_task_id(&two);
     assert_eq!(task_two, TaskId::new(2u64));
@@ -183,49 +183,36 @@ where
     chain.next_block().unwrap();
     let three = make_task(&contract, "Two", None, &payload_three); // uses default of 200
 
-    let ListOpenResponse { tasks } = contract.list_open(None, None).unwrap();
+    let ListOpenResponse { tasks } = contract.list_open(None, None, None).unwrap();
     assert_eq!(tasks.len(), 3);
-    assert_eq!(
-        tasks[0],
-        OpenTaskOverview {
-            id: three,
-            expires: start + 200 + 2 * block_time + offset, // we waited two blocks to create
-            payload: payload_three,
-        }
-    );
-    assert_eq!(
-        tasks[1],
-        OpenTaskOverview {
-            id: two,
-            expires: start + 100 + block_time + offset, // we waited one block to create
-            payload: payload_two,
-        }
-    );
-    assert_eq!(
-        tasks[2],
-        OpenTaskOverview {
-            id: one,
-            expires: start + 300 + offset,
-            payload: payload_one,
-        }
-    );
+    assert_eq!(tasks[0].id, three);
+    // we waited two blocks to create
+    assert_eq!(tasks[0].timing.expires_at, start + 200 + 2 * block_time + offset);
+    assert_eq!(tasks[0].payload, payload_three);
+    assert_eq!(tasks[1].id, two);
+    // we waited one block to create
+    assert_eq!(tasks[1].timing.expires_at, start + 100 + block_time + offset);
+    assert_eq!(tasks[1].payload, payload_two);
+    assert_eq!(tasks[2].id, one);
+    assert_eq!(tasks[2].timing.expires_at, start + 300 + offset);
+    assert_eq!(tasks[2].payload, payload_one);
 
     // now let's wait a bit so some expire
     chain.wait_seconds(150).unwrap();
-    let ListOpenResponse { tasks } = contract.list_open(None, None).unwrap();
+    let ListOpenResponse { tasks } = contract.list_open(None, None, None).unwrap();
     assert_eq!(tasks.len(), 2);
     assert_eq!(tasks[0].id, three);
     assert_eq!(tasks[1].id, one);
 
     // and the next expiration
     chain.wait_seconds(100).unwrap();
-    let ListOpenResponse { tasks } = contract.list_open(None, None).unwrap();
+    let ListOpenResponse { tasks } = contract.list_open(None, None, None).unwrap();
     assert_eq!(tasks.len(), 1);
     assert_eq!(tasks[0].id, one);
 
     // and them all
     chain.wait_seconds(100).unwrap();
-    let ListOpenResponse { tasks } = contract.list_open(None, None).unwrap();
+    let ListOpenResponse { tasks } = contract.list_open(None, None, None).unwrap();
     assert_eq!(tasks.len(), 0);
 }
 
@@ -243,11 +230,11 @@ where
     let two = make_task(&contract, "Two", 100, &payload);
 
     // list completed empty
-    let ListCompletedResponse { tasks } = contract.list_completed(None, None).unwrap();
+    let ListTasksResponse { tasks } = contract.list_completed(None, None, None).unwrap();
     assert_eq!(tasks.len(), 0);
 
     // normal user cannot complete
-    let err = contract.complete(one, result.clone()).unwrap_err();
+    let err = contract.complete_task(one, result.clone()).unwrap_err();
     assert!(
         err.root()
             .to_string()
@@ -259,7 +246,7 @@ where
     // verifier can complete
     contract
         .call_as(&verifier)
-        .complete(one, result.clone())
+        .complete_task(one, result.clone())
         .unwrap();
     let completion_time = chain.block_info().unwrap().time.seconds();
     chain.next_block().unwrap();
@@ -267,7 +254,7 @@ where
     // cannot complete already completed
     let err = contract
         .call_as(&verifier)
-        .complete(one, result.clone())
+        .complete_task(one, result.clone())
         .unwrap_err();
     assert!(
         err.root()
@@ -280,7 +267,7 @@ where
     // cannot complete unknown task ids
     let err = contract
         .call_as(&verifier)
-        .complete(TaskId::new(two.u64() + 1), result.clone())
+        .complete_task(TaskId::new(two.u64() + 1), result.clone())
         .unwrap_err();
     assert!(err.root().to_string().contains("not found"));
 
@@ -288,7 +275,7 @@ where
     chain.wait_seconds(100).unwrap();
     let err = contract
         .call_as(&verifier)
-        .complete(two, result.clone())
+        .complete_task(two, result.clone())
         .unwrap_err();
     assert!(
         err.root()
@@ -299,16 +286,11 @@ where
     );
 
     // list completed
-    let ListCompletedResponse { tasks } = contract.list_completed(None, None).unwrap();
+    let ListTasksResponse { tasks } = contract.list_completed(None, None, None).unwrap();
     assert_eq!(tasks.len(), 1);
-    assert_eq!(
-        tasks[0],
-        CompletedTaskOverview {
-            id: one,
-            completed: completion_time,
-            result,
-        }
-    );
+    assert_eq!(tasks[0].id, one);
+    assert_eq!(tasks[0].status, Status::Completed { completed: completion_time });
+    assert_eq!(tasks[0].result, Some(result));
 }
 
 pub fn task_status_works<C>(chain: C)
@@ -325,36 +307,38 @@ where
     let two = make_task(&contract, "Two", 100, &payload);
 
     // check open status
-    let status_one = contract.task_status(one).unwrap();
-    let status_two = contract.task_status(two).unwrap();
-    assert_eq!(status_one.created_time + 300, status_one.expires_time);
-    assert_eq!(status_two.created_time + 100, status_two.expires_time);
-    assert!(status_one.created_height < status_two.created_height);
-    assert_eq!(status_one.status, TaskStatus::Open);
-    assert_eq!(status_two.status, TaskStatus::Open);
+    let status_one = contract.task_info(one).unwrap();
+    let status_two = contract.task_info(two).unwrap();
+    assert_eq!(status_one.timing.created_at + 300, status_one.timing.expires_at);
+    assert_eq!(status_two.timing.created_at + 100, status_two.timing.expires_at);
+    assert!(status_one.timing.created_height < status_two.timing.created_height);
+    assert_eq!(status_one.status, Status::Open {});
+    assert_eq!(status_two.status, Status::Open {});
 
     // verifier can complete
     contract
         .call_as(&verifier)
-        .complete(one, result.clone())
+        .complete_task(one, result.clone())
         .unwrap();
     chain.next_block().unwrap();
 
     // contract one changed
-    let status_one = contract.task_status(one).unwrap();
-    assert_eq!(status_one.status, TaskStatus::Completed);
+    let status_one = contract.task_info(one).unwrap();
+    assert!(matches!(status_one.status, Status::Completed { .. }));
     // contract two unchanged
-    let status_two = contract.task_status(two).unwrap();
-    assert_eq!(status_two.status, TaskStatus::Open);
+    let status_two = contract.task_info(two).unwrap();
+    assert_eq!(status_two.status, Status::Open {});
 
-    // expried contracts automatically update return value
+    // expired tasks are listed as such before anybody expires them
     chain.wait_seconds(200).unwrap();
     // contract one unchanged
-    let status_one = contract.task_status(one).unwrap();
-    assert_eq!(status_one.status, TaskStatus::Completed);
+    let status_one = contract.task_info(one).unwrap();
+    assert!(matches!(status_one.status, Status::Completed { .. }));
     // contract two expired
-    let status_two = contract.task_status(two).unwrap();
-    assert_eq!(status_two.status, TaskStatus::Expired);
+    let ListTasksResponse { tasks } = contract.list_expired(None, None, None).unwrap();
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0].id, two);
+    assert_eq!(tasks[0].status, Status::Expired {});
 }
 
 pub fn task_pagination_works<C>(chain: C)
@@ -381,7 +365,7 @@ where
     // Get the total number of open tasks
     let ListOpenResponse {
         tasks: all_open_tasks,
-    } = contract.list_open(None, None).unwrap();
+    } = contract.list_open(None, None, None).unwrap();
     let total_open_tasks = all_open_tasks.len();
 
     // Test pagination with different limits
@@ -392,7 +376,7 @@ where
         let mut start_after = None;
 
         loop {
-            let ListOpenResponse { tasks } = contract.list_open(start_after, Some(limit)).unwrap();
+            let ListOpenResponse { tasks } = contract.list_open(start_after, Some(limit), None).unwrap();
 
             if tasks.is_empty() {
                 break;
@@ -447,7 +431,7 @@ where
     }
 
     // Test with no limit (should return all open tasks)
-    let ListOpenResponse { tasks } = contract.list_open(None, None).unwrap();
+    let ListOpenResponse { tasks } = contract.list_open(None, None, None).unwrap();
     assert_eq!(
         tasks.len(),
         total_open_tasks,
@@ -468,7 +452,7 @@ pub fn make_task<C: ChainState + TxHandler>(
     payload: &serde_json::Value,
 ) -> TaskId {
     let res = contract
-        .create(name.to_string(), timeout.into(), payload.clone(), &[])
+        .create(name.to_string(), timeout.into(), payload.clone(), vec![], String::new(), None, &[])
         .unwrap();
     get_task_id(&res)
 }
@@ -1,8 +1,19 @@
 // use cw_orch::environment::IndexResponse;
 use cw_orch::prelude::*;
 
+use cosmwasm_std::Storage;
+use lavs_apis::id::TaskId;
+use lavs_apis::tasks::TimeoutConfig;
+use serde_json::json;
+
+use crate::error::ContractError;
 use crate::interface::Contract;
-use crate::msg::{InstantiateMsg, Requestor, TimeoutInfo};
+use crate::migration::v1;
+use crate::msg::{
+    Config, ExecuteMsg, InstantiateMsg, ListTasksResponse, MigrateMsg, QueryMsg, Requestor, Status, TaskInfoResponse, Timing,
+    TimeoutInfo,
+};
+use crate::state::RequestorConfig;
 
 // TODO: shared variable
 const BECH_PREFIX: &str = "layer";
@@ -65,8 +76,173 @@ fn sanity_check() {
             maximum: None,
         },
         verifier: verifier.to_string(),
+        dispute: None,
+        arbitrator: None,
+        cancel_fee: None,
+        keeper_reward: None,
     };
     let init_res = tasker.instantiate(&msg, None, &[]).unwrap();
     let contract_addr = init_res.instantiated_contract_address().unwrap();
     assert_eq!(contract_addr, tasker.address().unwrap());
 }
+
+/// Runs `f` on the contract's storage, to leave it the way an older release would have
+fn with_storage<T>(mock: &MockBech32, contract: &Addr, f: impl FnOnce(&mut dyn Storage) -> T) -> T {
+    let mut app = mock.app.borrow_mut();
+    f(app.contract_storage_mut(contract).as_mut())
+}
+
+fn deploy(mock: &MockBech32) -> Contract<MockBech32> {
+    let tasker = Contract::new(mock.clone());
+    tasker.upload().unwrap();
+    let msg = InstantiateMsg {
+        requestor: Requestor::Fixed(mock.sender_addr().into()),
+        timeout: TimeoutInfo {
+            default: 3600,
+            minimum: None,
+            maximum: None,
+        },
+        verifier: mock.addr_make("verifier").to_string(),
+        dispute: None,
+        arbitrator: None,
+        cancel_fee: None,
+        keeper_reward: None,
+    };
+    tasker.instantiate(&msg, Some(&mock.sender_addr()), &[]).unwrap();
+    tasker
+}
+
+#[test]
+fn migrates_v1_state() {
+    let mock = MockBech32::new(BECH_PREFIX);
+    let tasker = deploy(&mock);
+    let requestor = mock.sender_addr();
+    let block = mock.block_info().unwrap();
+    let timing = Timing {
+        created_at: block.time.seconds(),
+        expires_at: block.time.seconds() + 3600,
+        created_height: block.height,
+    };
+
+    with_storage(&mock, &tasker.address().unwrap(), |storage| {
+        cw2::set_contract_version(storage, "crates.io:task-queue", "1.0.0").unwrap();
+        let config = v1::Config {
+            next_id: TaskId::new(3),
+            requestor: RequestorConfig::Fixed(requestor.clone()),
+            timeout: TimeoutConfig {
+                default: 3600,
+                minimum: 3600,
+                maximum: 3600,
+            },
+            verifier: mock.addr_make("verifier"),
+        };
+        v1::CONFIG.save(storage, &config).unwrap();
+        let open = v1::Task {
+            description: "open".to_string(),
+            status: lavs_apis::tasks::Status::Open {},
+            timing: timing.clone(),
+            payload: json!({ "pair": "ETH/USD" }),
+            result: None,
+        };
+        v1::TASKS.save(storage, TaskId::new(1), &open).unwrap();
+        let completed = v1::Task {
+            description: "completed".to_string(),
+            status: lavs_apis::tasks::Status::Completed {
+                completed: block.time.seconds(),
+            },
+            timing: timing.clone(),
+            payload: json!({ "pair": "BTC/USD" }),
+            result: Some(json!({ "price": "64000" })),
+        };
+        v1::TASKS.save(storage, TaskId::new(2), &completed).unwrap();
+    });
+
+    // only the first task is rewritten right away
+    let res = tasker
+        .migrate(&MigrateMsg { task_limit: Some(1) }, tasker.code_id().unwrap())
+        .unwrap();
+    assert_eq!(res.event_attr_value("wasm", "tasks_migrated").unwrap(), "false");
+    tasker.query::<TaskInfoResponse>(&QueryMsg::TaskInfo { task_id: TaskId::new(2) }).unwrap_err();
+
+    // new tasks can be created next to the migrated ones, and are left alone by the rest of the migration
+    let create = ExecuteMsg::Create {
+        description: "new".to_string(),
+        timeout: None,
+        payload: json!({ "pair": "ATOM/USD" }),
+        options: vec![],
+        proposed_winner: String::new(),
+        callback: None,
+    };
+    tasker.execute(&create, &[]).unwrap();
+    let res = tasker.execute(&ExecuteMsg::MigrateTasks { limit: None }, &[]).unwrap();
+    assert_eq!(res.event_attr_value("wasm", "migrated").unwrap(), "1");
+    assert_eq!(res.event_attr_value("wasm", "tasks_migrated").unwrap(), "true");
+    let err = tasker.execute(&ExecuteMsg::MigrateTasks { limit: None }, &[]).unwrap_err();
+    assert!(err.root().to_string().contains(&ContractError::NothingToMigrate.to_string()));
+
+    let version = with_storage(&mock, &tasker.address().unwrap(), |storage| {
+        cw2::get_contract_version(storage).unwrap()
+    });
+    assert_eq!(version.version, "2.0.0");
+    let config: Config = tasker.query(&QueryMsg::Config {}).unwrap();
+    assert_eq!(config.owner, mock.sender_addr());
+    assert_eq!(config.arbitrator, None);
+
+    let open: TaskInfoResponse = tasker.query(&QueryMsg::TaskInfo { task_id: TaskId::new(1) }).unwrap();
+    assert_eq!(open.requestor, requestor);
+    assert_eq!(open.status, Status::Open {});
+    assert_eq!(open.timing, timing);
+    assert!(open.options.is_empty());
+    assert_eq!(open.payment, None);
+
+    let completed: TaskInfoResponse = tasker.query(&QueryMsg::TaskInfo { task_id: TaskId::new(2) }).unwrap();
+    assert_eq!(completed.status, Status::Completed { completed: block.time.seconds() });
+    assert_eq!(completed.result, Some(json!({ "price": "64000" })));
+
+    // both indexes cover the migrated tasks
+    let by_requestor: ListTasksResponse = tasker
+        .query(&QueryMsg::ListByRequestor {
+            requestor: requestor.to_string(),
+            start_after: None,
+            limit: None,
+        })
+        .unwrap();
+    assert_eq!(by_requestor.tasks.len(), 3);
+    let list: ListTasksResponse = tasker
+        .query(&QueryMsg::ListCompleted {
+            start_after: None,
+            limit: None,
+            order: None,
+        })
+        .unwrap();
+    assert_eq!(list.tasks.len(), 1);
+    assert_eq!(list.tasks[0].id, TaskId::new(2));
+    let new: TaskInfoResponse = tasker.query(&QueryMsg::TaskInfo { task_id: TaskId::new(3) }).unwrap();
+    assert_eq!(new.description, "new");
+}
+
+#[test]
+fn migration_refuses_downgrades() {
+    let mock = MockBech32::new(BECH_PREFIX);
+    let tasker = deploy(&mock);
+
+    // migrating to the same version is fine
+    tasker.migrate(&MigrateMsg { task_limit: None }, tasker.code_id().unwrap()).unwrap();
+
+    with_storage(&mock, &tasker.address().unwrap(), |storage| {
+        cw2::set_contract_version(storage, "crates.io:task-queue", "3.0.0").unwrap();
+    });
+    let err = tasker.migrate(&MigrateMsg { task_limit: None }, tasker.code_id().unwrap()).unwrap_err();
+    let expected = ContractError::CannotDowngrade {
+        from: "3.0.0".to_string(),
+        to: "2.0.0".to_string(),
+    };
+    assert!(err.root().to_string().contains(&expected.to_string()));
+
+    with_storage(&mock, &tasker.address().unwrap(), |storage| {
+        cw2::set_contract_version(storage, "crates.io:mock-operators", "1.0.0").unwrap();
+    });
+    let err = tasker.migrate(&MigrateMsg { task_limit: None }, tasker.code_id().unwrap()).unwrap_err();
+    let expected = ContractError::WrongContract("crates.io:mock-operators".to_string());
+    assert!(err.root().to_string().contains(&expected.to_string()));
+}
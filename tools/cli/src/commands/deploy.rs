@@ -174,6 +174,10 @@ pub async fn deploy_contracts(
                 requestor,
                 timeout: task_timeout,
                 verifier: verifier_addr.to_string(),
+                dispute: None,
+                arbitrator: None,
+                cancel_fee: None,
+                keeper_reward: None,
             },
             vec![],
             None,
@@ -183,6 +187,26 @@ pub async fn deploy_contracts(
     tracing::debug!("Task Queue Tx Hash: {}", tx_resp.txhash);
     tracing::debug!("Task Queue Address: {}", task_queue_addr);
 
+    // the verifier only votes on tasks of the queue it was told to serve
+    let tx_resp = client
+        .contract_execute(
+            &verifier_addr,
+            &lavs_oracle_verifier::msg::ExecuteMsg::UpdateConfig {
+                threshold_percent: None,
+                allowed_spread: None,
+                slashable_spread: None,
+                supermajority: None,
+                challenge_threshold: None,
+                guardian: None,
+                task_queue: Some(task_queue_addr.to_string()),
+            },
+            vec![],
+            None,
+        )
+        .await?;
+
+    tracing::debug!("Oracle Verifier Config Tx Hash: {}", tx_resp.txhash);
+
     Ok(DeployContractAddrs {
         operators: operators_addr,
         task_queue: task_queue_addr,